@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+/// What sits at an address, judged from its code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AddressKind {
+    /// Externally owned account, no code.
+    Eoa,
+    /// Contract that is none of the below.
+    Contract,
+    /// Proxy forwarding to an implementation that is not an account.
+    Proxy,
+    /// Contract wallet: an ERC-4337 account, a Safe, or an EOA delegating
+    /// to code through EIP-7702.
+    SmartAccount,
+}
+
+impl AddressKind {
+    pub fn has_code(&self) -> bool {
+        *self != AddressKind::Eoa
+    }
+}
+
+impl std::fmt::Display for AddressKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AddressKind::Eoa => write!(f, "eoa"),
+            AddressKind::Contract => write!(f, "contract"),
+            AddressKind::Proxy => write!(f, "proxy"),
+            AddressKind::SmartAccount => write!(f, "smart_account"),
+        }
+    }
+}
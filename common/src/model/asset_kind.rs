@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+/// Kind of value movement a transfer record represents.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AssetKind {
+    Native,
+    Internal,
+    #[default]
+    Erc20,
+    Erc721,
+    Erc1155,
+}
+
+impl AssetKind {
+    pub const ALL: [AssetKind; 5] = [
+        AssetKind::Native,
+        AssetKind::Internal,
+        AssetKind::Erc20,
+        AssetKind::Erc721,
+        AssetKind::Erc1155,
+    ];
+
+    pub fn is_nft(&self) -> bool {
+        matches!(self, AssetKind::Erc721 | AssetKind::Erc1155)
+    }
+}
+
+impl std::fmt::Display for AssetKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AssetKind::Native => write!(f, "native"),
+            AssetKind::Internal => write!(f, "internal"),
+            AssetKind::Erc20 => write!(f, "erc20"),
+            AssetKind::Erc721 => write!(f, "erc721"),
+            AssetKind::Erc1155 => write!(f, "erc1155"),
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+use super::Wei;
+
+/// Amount of one asset a wallet held at a block.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceSnapshot {
+    /// Block the balance was read at, `None` for the chain head.
+    pub block: Option<u64>,
+    /// Token contract, `None` for the chain's native coin.
+    pub token: Option<String>,
+    pub symbol: String,
+    /// Raw units (wei for the native coin).
+    pub balance: Wei,
+}
+
+impl BalanceSnapshot {
+    pub fn is_native(&self) -> bool {
+        self.token.is_none()
+    }
+}
@@ -5,6 +5,19 @@ use super::TxEthereum;
 #[derive(Debug, Deserialize)]
 pub struct BlockEthereum {
     pub number: String,
+    pub hash: String,
+    #[serde(rename = "parentHash")]
+    pub parent_hash: String,
     pub timestamp: String,
     pub transactions: Vec<TxEthereum>,
 }
+
+impl BlockEthereum {
+    pub fn block_number(&self) -> Option<u64> {
+        u64::from_str_radix(self.number.trim_start_matches("0x"), 16).ok()
+    }
+
+    pub fn timestamp_secs(&self) -> Option<u64> {
+        u64::from_str_radix(self.timestamp.trim_start_matches("0x"), 16).ok()
+    }
+}
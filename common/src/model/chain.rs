@@ -0,0 +1,73 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Chain {
+    #[default]
+    Ethereum,
+    Bsc,
+    Polygon,
+    Arbitrum,
+    Base,
+}
+
+impl Chain {
+    pub fn chain_id(&self) -> u64 {
+        match self {
+            Chain::Ethereum => 1,
+            Chain::Bsc => 56,
+            Chain::Polygon => 137,
+            Chain::Arbitrum => 42_161,
+            Chain::Base => 8_453,
+        }
+    }
+
+    pub fn native_symbol(&self) -> &'static str {
+        match self {
+            Chain::Ethereum | Chain::Arbitrum | Chain::Base => "ETH",
+            Chain::Bsc => "BNB",
+            Chain::Polygon => "POL",
+        }
+    }
+
+    /// Typical time between blocks, in milliseconds.
+    pub fn block_time_ms(&self) -> u64 {
+        match self {
+            Chain::Ethereum => 12_000,
+            Chain::Bsc => 3_000,
+            Chain::Polygon | Chain::Base => 2_000,
+            Chain::Arbitrum => 250,
+        }
+    }
+}
+
+impl std::fmt::Display for Chain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Chain::Ethereum => write!(f, "ethereum"),
+            Chain::Bsc => write!(f, "bsc"),
+            Chain::Polygon => write!(f, "polygon"),
+            Chain::Arbitrum => write!(f, "arbitrum"),
+            Chain::Base => write!(f, "base"),
+        }
+    }
+}
+
+impl FromStr for Chain {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "ethereum" | "eth" | "mainnet" => Ok(Chain::Ethereum),
+            "bsc" | "bnb" => Ok(Chain::Bsc),
+            "polygon" | "matic" => Ok(Chain::Polygon),
+            "arbitrum" | "arb" | "arbitrum-one" => Ok(Chain::Arbitrum),
+            "base" => Ok(Chain::Base),
+            other => Err(format!("unsupported chain: {other}")),
+        }
+    }
+}
@@ -2,14 +2,54 @@ use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
-use super::TxCategory;
+use super::{Chain, Provenance, TxCategory};
 
 pub type Wei = u128;
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Whether a flow record counts, or is a correction withdrawing an earlier
+/// record for a block that was reorganized out of the canonical chain.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FlowStatus {
+    #[default]
+    Confirmed,
+    Retracted,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FlowSummary {
+    #[serde(default)]
+    pub chain: Chain,
     pub block_number: u64,
+    #[serde(default)]
+    pub block_hash: String,
     pub timestamp: u64,
     pub category_totals: HashMap<TxCategory, Wei>,
+    /// ERC-20 totals keyed by token contract address, in raw token units.
+    #[serde(default)]
+    pub token_totals: HashMap<String, HashMap<TxCategory, Wei>>,
     pub tx_count: usize,
+    #[serde(default)]
+    pub status: FlowStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+}
+
+impl FlowSummary {
+    /// Correction entry withdrawing this summary.
+    pub fn retraction(&self) -> Self {
+        Self {
+            status: FlowStatus::Retracted,
+            ..self.clone()
+        }
+    }
+
+    pub fn with_provenance(mut self, provenance: Provenance) -> Self {
+        self.provenance = Some(provenance);
+        self
+    }
+
+    pub fn is_retracted(&self) -> bool {
+        self.status == FlowStatus::Retracted
+    }
 }
@@ -0,0 +1,172 @@
+use serde::{Deserialize, Serialize};
+
+use super::{CallFrame, TraceEthereum, TxTraceEthereum, Wei};
+
+/// Native value moved by a contract-initiated call, create or self-destruct
+/// within a transaction.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InternalTransfer {
+    pub hash: String,
+    /// Position of the call in the transaction's call tree, e.g. `0_1`.
+    pub trace_id: String,
+    pub from: String,
+    pub to: String,
+    pub value: Wei,
+    pub block_number: u64,
+}
+
+impl InternalTransfer {
+    /// Decodes the value-carrying internal calls of a `trace_block` result.
+    /// Top-level traces are the transactions themselves and are skipped, as
+    /// is everything under a reverted frame.
+    pub fn from_traces(traces: &[TraceEthereum]) -> Vec<Self> {
+        let mut reverted: Vec<(&str, &[u64])> = vec![];
+        let mut transfers = vec![];
+
+        for trace in traces {
+            let Some(hash) = trace.transaction_hash.as_deref() else {
+                continue;
+            };
+
+            if trace.error.is_some() {
+                reverted.push((hash, &trace.trace_address));
+                continue;
+            }
+
+            let under_reverted = reverted.iter().any(|(reverted_hash, address)| {
+                *reverted_hash == hash && trace.trace_address.starts_with(address)
+            });
+
+            if trace.trace_address.is_empty() || under_reverted {
+                continue;
+            }
+
+            let action = &trace.action;
+            let (from, to, value) = match trace.trace_type.as_str() {
+                "call" if action.call_type.as_deref() == Some("call") => {
+                    (&action.from, &action.to, &action.value)
+                }
+                "create" => (
+                    &action.from,
+                    match &trace.result {
+                        Some(result) => &result.address,
+                        None => continue,
+                    },
+                    &action.value,
+                ),
+                "suicide" => (&action.address, &action.refund_address, &action.balance),
+                _ => continue,
+            };
+
+            let (Some(from), Some(to), Some(value), Some(block_number)) = (
+                from,
+                to,
+                value.as_deref().and_then(parse_value),
+                trace.block_number,
+            ) else {
+                continue;
+            };
+
+            if value > 0 {
+                transfers.push(InternalTransfer {
+                    hash: hash.to_string(),
+                    trace_id: trace_id(&trace.trace_address),
+                    from: from.to_ascii_lowercase(),
+                    to: to.to_ascii_lowercase(),
+                    value,
+                    block_number,
+                });
+            }
+        }
+
+        transfers
+    }
+
+    /// Decodes the value-carrying internal calls of a `callTracer` frame
+    /// tree, skipping the top-level frame and reverted subtrees.
+    pub fn from_call_frame(hash: &str, frame: &CallFrame, block_number: u64) -> Vec<Self> {
+        let mut transfers = vec![];
+
+        if frame.error.is_none() {
+            for (index, call) in frame.calls.iter().enumerate() {
+                collect_frames(hash, call, vec![index as u64], block_number, &mut transfers);
+            }
+        }
+
+        transfers
+    }
+
+    /// Decodes a block's `debug_traceBlockByNumber` result. `hashes` lists the
+    /// block's transactions in order, for tracers that omit `txHash`.
+    pub fn from_call_traces(
+        traces: &[TxTraceEthereum],
+        hashes: &[String],
+        block_number: u64,
+    ) -> Vec<Self> {
+        traces
+            .iter()
+            .enumerate()
+            .filter_map(|(index, trace)| {
+                let hash = trace.tx_hash.as_ref().or(hashes.get(index))?;
+                Some(InternalTransfer::from_call_frame(
+                    hash,
+                    &trace.result,
+                    block_number,
+                ))
+            })
+            .flatten()
+            .collect()
+    }
+}
+
+fn collect_frames(
+    hash: &str,
+    frame: &CallFrame,
+    path: Vec<u64>,
+    block_number: u64,
+    transfers: &mut Vec<InternalTransfer>,
+) {
+    if frame.error.is_some() {
+        return;
+    }
+
+    let moves_value = matches!(
+        frame.call_type.as_str(),
+        "CALL" | "CREATE" | "CREATE2" | "SELFDESTRUCT"
+    );
+    let value = frame.value.as_deref().and_then(parse_value).unwrap_or(0);
+
+    if let Some(to) = frame.to.as_deref().filter(|_| moves_value && value > 0) {
+        transfers.push(InternalTransfer {
+            hash: hash.to_string(),
+            trace_id: trace_id(&path),
+            from: frame.from.to_ascii_lowercase(),
+            to: to.to_ascii_lowercase(),
+            value,
+            block_number,
+        });
+    }
+
+    for (index, call) in frame.calls.iter().enumerate() {
+        let mut child = path.clone();
+        child.push(index as u64);
+        collect_frames(hash, call, child, block_number, transfers);
+    }
+}
+
+fn trace_id(path: &[u64]) -> String {
+    path.iter()
+        .map(u64::to_string)
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+fn parse_value(value: &str) -> Option<Wei> {
+    let hex = value.trim_start_matches("0x").trim_start_matches('0');
+
+    if hex.is_empty() {
+        Some(0)
+    } else {
+        Wei::from_str_radix(hex, 16).ok()
+    }
+}
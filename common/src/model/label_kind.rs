@@ -0,0 +1,44 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// What kind of entity an address label names.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum LabelKind {
+    #[default]
+    Cex,
+    Bridge,
+    Mixer,
+    Defi,
+    Sanctioned,
+}
+
+impl std::fmt::Display for LabelKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LabelKind::Cex => write!(f, "cex"),
+            LabelKind::Bridge => write!(f, "bridge"),
+            LabelKind::Mixer => write!(f, "mixer"),
+            LabelKind::Defi => write!(f, "defi"),
+            LabelKind::Sanctioned => write!(f, "sanctioned"),
+        }
+    }
+}
+
+impl FromStr for LabelKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "cex" | "exchange" => Ok(LabelKind::Cex),
+            "bridge" => Ok(LabelKind::Bridge),
+            "mixer" => Ok(LabelKind::Mixer),
+            "defi" => Ok(LabelKind::Defi),
+            "sanctioned" | "sanction" => Ok(LabelKind::Sanctioned),
+            other => Err(format!("unknown label kind: {other}")),
+        }
+    }
+}
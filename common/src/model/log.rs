@@ -0,0 +1,14 @@
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogEthereum {
+    pub address: String,
+    pub topics: Vec<String>,
+    pub data: String,
+    pub block_number: Option<String>,
+    pub transaction_hash: Option<String>,
+    pub log_index: Option<String>,
+    #[serde(default)]
+    pub removed: bool,
+}
@@ -1,5 +1,16 @@
+mod address_kind;
+mod asset_kind;
+mod balance;
 mod block;
+mod chain;
 mod flow_summary;
+mod internal_transfer;
+mod label_kind;
+mod log;
+mod provenance;
+mod receipt;
+mod token_transfer;
+mod trace;
 mod tx_category;
 mod tx_ethereum;
 mod tx_record;
@@ -7,11 +18,22 @@ mod tx_response;
 mod tx_sample;
 mod wallet_type;
 
+pub use address_kind::AddressKind;
+pub use asset_kind::AssetKind;
+pub use balance::BalanceSnapshot;
 pub use block::BlockEthereum;
-pub use flow_summary::{FlowSummary, Wei};
+pub use chain::Chain;
+pub use flow_summary::{FlowStatus, FlowSummary, Wei};
+pub use internal_transfer::InternalTransfer;
+pub use label_kind::LabelKind;
+pub use log::LogEthereum;
+pub use provenance::{HeuristicsVersion, LabelFileVersion, Provenance};
+pub use receipt::ReceiptEthereum;
+pub use token_transfer::{TokenTransfer, TRANSFER_EVENT_TOPIC};
+pub use trace::{CallFrame, TraceAction, TraceEthereum, TraceResult, TxTraceEthereum};
 pub use tx_category::TxCategory;
 pub use tx_ethereum::TxEthereum;
 pub use tx_record::TxRecord;
-pub use tx_response::TxResponse;
+pub use tx_response::{TxResponse, TxRowKey};
 pub use tx_sample::TxSample;
 pub use wallet_type::WalletType;
@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+
+use super::AssetKind;
+
+/// Which heuristics a record was classified with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeuristicsVersion {
+    /// Version of the heuristics file, bumped each time its labels change.
+    pub version: u64,
+    /// SHA-256 of the heuristics file alone, as listed by its history.
+    pub hash: String,
+    /// Label files merged into the file's labels, in import order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub label_files: Vec<LabelFileVersion>,
+}
+
+impl std::fmt::Display for HeuristicsVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "v{} ({})", self.version, short_hash(&self.hash))?;
+        for file in &self.label_files {
+            write!(f, " + {} ({})", file.path, short_hash(&file.hash))?;
+        }
+        Ok(())
+    }
+}
+
+/// A label file, e.g. `data/indonesia-cex.json`, and the SHA-256 of the
+/// labels it held.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LabelFileVersion {
+    pub path: String,
+    pub hash: String,
+}
+
+fn short_hash(hash: &str) -> &str {
+    &hash[..hash.len().min(12)]
+}
+
+/// What produced a record, enough to run the same classification again.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Provenance {
+    pub heuristics: HeuristicsVersion,
+    /// Name and version of the tool that wrote the record.
+    pub tool: String,
+    /// Where the data came from, e.g. `etherscan` or `archive:<dir>`.
+    pub source: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_block: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_block: Option<u64>,
+    /// Asset kinds the source could see, when not all of them; transfers of
+    /// other kinds are missing from the record.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asset_kinds: Option<Vec<AssetKind>>,
+}
+
+impl Provenance {
+    pub fn new(
+        heuristics: HeuristicsVersion,
+        tool: impl Into<String>,
+        source: impl Into<String>,
+    ) -> Self {
+        Self {
+            heuristics,
+            tool: tool.into(),
+            source: source.into(),
+            start_block: None,
+            end_block: None,
+            asset_kinds: None,
+        }
+    }
+
+    /// Blocks the record covers, both inclusive.
+    pub fn with_block_range(mut self, start_block: u64, end_block: u64) -> Self {
+        self.start_block = Some(start_block);
+        self.end_block = Some(end_block);
+        self
+    }
+
+    /// Asset kinds the source could see. Only recorded when some are
+    /// missing.
+    pub fn with_asset_kinds(mut self, kinds: &[AssetKind]) -> Self {
+        self.asset_kinds = (kinds.len() < AssetKind::ALL.len()).then(|| kinds.to_vec());
+        self
+    }
+}
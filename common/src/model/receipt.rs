@@ -0,0 +1,21 @@
+use serde::Deserialize;
+
+use super::LogEthereum;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceiptEthereum {
+    pub transaction_hash: String,
+    pub block_number: String,
+    pub from: String,
+    pub to: Option<String>,
+    pub status: Option<String>,
+    pub logs: Vec<LogEthereum>,
+}
+
+impl ReceiptEthereum {
+    /// Pre-Byzantium receipts carry no `status`, so they count as successful.
+    pub fn is_success(&self) -> bool {
+        self.status.as_deref() != Some("0x0")
+    }
+}
@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+
+use super::{LogEthereum, ReceiptEthereum, Wei};
+
+/// `keccak256("Transfer(address,address,uint256)")`
+pub const TRANSFER_EVENT_TOPIC: &str =
+    "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+
+/// ERC-20 transfer decoded from a `Transfer` event log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenTransfer {
+    pub hash: String,
+    pub log_index: u64,
+    pub contract_address: String,
+    pub from: String,
+    pub to: String,
+    pub value: Wei,
+    pub block_number: u64,
+}
+
+impl TokenTransfer {
+    /// Decodes an ERC-20 `Transfer` log. ERC-721 transfers share the event
+    /// signature but index the token id as a fourth topic, so they are
+    /// skipped, as are amounts that do not fit in a `u128`.
+    pub fn from_log(log: &LogEthereum) -> Option<Self> {
+        if log.removed || log.topics.len() != 3 {
+            return None;
+        }
+
+        if !log.topics[0].eq_ignore_ascii_case(TRANSFER_EVENT_TOPIC) {
+            return None;
+        }
+
+        let data = log.data.trim_start_matches("0x").trim_start_matches('0');
+        let value = if data.is_empty() {
+            0
+        } else if data.len() > 32 {
+            return None;
+        } else {
+            Wei::from_str_radix(data, 16).ok()?
+        };
+
+        Some(TokenTransfer {
+            hash: log.transaction_hash.clone()?,
+            log_index: parse_hex(log.log_index.as_deref()?)?,
+            contract_address: log.address.to_ascii_lowercase(),
+            from: topic_to_address(&log.topics[1])?,
+            to: topic_to_address(&log.topics[2])?,
+            value,
+            block_number: parse_hex(log.block_number.as_deref()?)?,
+        })
+    }
+
+    /// Decodes the ERC-20 transfers emitted by successful transactions.
+    pub fn from_receipts(receipts: &[ReceiptEthereum]) -> Vec<Self> {
+        receipts
+            .iter()
+            .filter(|receipt| receipt.is_success())
+            .flat_map(|receipt| &receipt.logs)
+            .filter_map(TokenTransfer::from_log)
+            .collect()
+    }
+}
+
+fn topic_to_address(topic: &str) -> Option<String> {
+    let hex = topic.trim_start_matches("0x");
+    let address = hex.get(hex.len().checked_sub(40)?..)?;
+    Some(format!("0x{}", address.to_ascii_lowercase()))
+}
+
+fn parse_hex(value: &str) -> Option<u64> {
+    u64::from_str_radix(value.trim_start_matches("0x"), 16).ok()
+}
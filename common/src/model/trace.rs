@@ -0,0 +1,57 @@
+use serde::Deserialize;
+
+/// Flat trace entry returned by `trace_block` (Parity/Erigon/Nethermind).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceEthereum {
+    pub action: TraceAction,
+    #[serde(default)]
+    pub result: Option<TraceResult>,
+    pub block_number: Option<u64>,
+    pub transaction_hash: Option<String>,
+    #[serde(default)]
+    pub trace_address: Vec<u64>,
+    #[serde(rename = "type")]
+    pub trace_type: String,
+    pub error: Option<String>,
+}
+
+/// Union of the `call`, `create` and `suicide` action shapes.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceAction {
+    pub call_type: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub value: Option<String>,
+    pub address: Option<String>,
+    pub refund_address: Option<String>,
+    pub balance: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TraceResult {
+    pub address: Option<String>,
+}
+
+/// Per-transaction entry returned by `debug_traceBlockByNumber` with the
+/// `callTracer`. Older Geth releases omit `txHash`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxTraceEthereum {
+    pub tx_hash: Option<String>,
+    pub result: CallFrame,
+}
+
+/// Call frame produced by Geth's `callTracer`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CallFrame {
+    #[serde(rename = "type")]
+    pub call_type: String,
+    pub from: String,
+    pub to: Option<String>,
+    pub value: Option<String>,
+    pub error: Option<String>,
+    #[serde(default)]
+    pub calls: Vec<CallFrame>,
+}
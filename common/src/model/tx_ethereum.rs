@@ -2,7 +2,7 @@ use serde::Deserialize;
 
 use crate::utils::Heuristics;
 
-use super::TxCategory;
+use super::{TxCategory, Wei};
 
 #[derive(Debug, Deserialize)]
 pub struct TxEthereum {
@@ -11,24 +11,22 @@ pub struct TxEthereum {
     pub to: Option<String>,
     pub input: String,
     pub value: String,
+    /// Hex block number, `None` while the transaction is still pending.
+    #[serde(rename = "blockNumber", default)]
+    pub block_number: Option<String>,
 }
 
 impl TxEthereum {
     pub fn categorize(&self, heuristics: &Heuristics) -> TxCategory {
-        self.to
-            .as_ref()
-            .map(|addr| addr.to_ascii_lowercase())
-            .map(|to| {
-                if heuristics.is_known_cex(&to) {
-                    TxCategory::Foreign
-                } else if heuristics.is_known_bridge(&to) {
-                    TxCategory::Bridge
-                } else if heuristics.is_known_domestic(&self.from, &to) {
-                    TxCategory::Domestic
-                } else {
-                    TxCategory::Foreign
-                }
-            })
-            .unwrap_or(TxCategory::Unknown)
+        heuristics.categorize(&self.from, self.to.as_deref())
+    }
+
+    pub fn value_wei(&self) -> Option<Wei> {
+        u128::from_str_radix(self.value.trim_start_matches("0x"), 16).ok()
+    }
+
+    pub fn mined_block(&self) -> Option<u64> {
+        let number = self.block_number.as_deref()?;
+        u64::from_str_radix(number.trim_start_matches("0x"), 16).ok()
     }
 }
@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::utils::PriceLookupFn;
 
-use super::{TxResponse, TxSample};
+use super::{AssetKind, TxResponse, TxSample};
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct TxRecord {
@@ -16,6 +16,10 @@ pub struct TxRecord {
     pub gas_price: Option<String>,
     pub gas_used: Option<String>,
     pub hash: String,
+    #[serde(default)]
+    pub kind: AssetKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_id: Option<String>,
 }
 
 impl TxRecord {
@@ -28,7 +32,11 @@ impl TxRecord {
             .naive_utc();
 
         let value_raw: f64 = self.value.parse().ok()?;
-        let value_eth = value_raw / 1e18;
+        let value_eth = if self.kind.is_nft() {
+            value_raw
+        } else {
+            value_raw / 1e18
+        };
 
         let gas_fee_eth = match (&self.gas_price, &self.gas_used) {
             (Some(gp), Some(gu)) => {
@@ -40,6 +48,7 @@ impl TxRecord {
         };
 
         let usd_value = price_lookup
+            .filter(|_| !self.kind.is_nft())
             .and_then(|lookup| lookup(&self.token_symbol, timestamp.and_utc().timestamp()))
             .map(|price| price * value_eth);
 
@@ -53,6 +62,7 @@ impl TxRecord {
             gas_fee_eth,
             contract_address: self.contract_address.clone(),
             hash: self.hash.clone(),
+            kind: self.kind,
         };
 
         Some(sample)
@@ -61,16 +71,24 @@ impl TxRecord {
 
 impl From<TxResponse> for TxRecord {
     fn from(value: TxResponse) -> Self {
+        let amount = match value.kind {
+            AssetKind::Erc721 => "1".to_string(),
+            AssetKind::Erc1155 => value.token_value.unwrap_or(value.value),
+            _ => value.value,
+        };
+
         Self {
             from: value.from,
             to: value.to,
             token_symbol: value.token_symbol,
             time_stamp: value.time_stamp,
-            value: value.value,
+            value: amount,
             contract_address: value.contract_address,
             gas_price: value.gas_price,
             gas_used: value.gas_used,
             hash: value.hash,
+            kind: value.kind,
+            token_id: value.token_id,
         }
     }
 }
@@ -1,16 +1,67 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize)]
+use super::AssetKind;
+
+/// Identity of a [`TxResponse`] row, see [`TxResponse::row_key`].
+pub type TxRowKey = (
+    String,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    AssetKind,
+);
+
+/// Row returned by Etherscan `account` actions (`txlist`, `txlistinternal`,
+/// `tokentx`, `tokennfttx`, `token1155tx`). Fields missing from a given
+/// action default to empty.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TxResponse {
     pub block_number: String,
     pub from: String,
+    #[serde(default)]
     pub to: String,
+    #[serde(default)]
     pub token_symbol: String,
     pub time_stamp: String,
+    #[serde(default)]
     pub value: String,
+    #[serde(default)]
     pub contract_address: String,
     pub gas_price: Option<String>,
     pub gas_used: Option<String>,
     pub hash: String,
+    #[serde(default)]
+    pub log_index: Option<String>,
+    #[serde(default, rename = "tokenID")]
+    pub token_id: Option<String>,
+    #[serde(default)]
+    pub token_value: Option<String>,
+    #[serde(default)]
+    pub trace_id: Option<String>,
+    #[serde(default)]
+    pub is_error: Option<String>,
+    /// Not part of Etherscan rows; tagged from the action that returned them
+    /// and kept when records are dumped and read back.
+    #[serde(default)]
+    pub kind: AssetKind,
+}
+
+impl TxResponse {
+    pub fn is_failed(&self) -> bool {
+        self.is_error.as_deref() == Some("1")
+    }
+
+    /// Identity of the row among every action's rows. One transaction can
+    /// yield several rows: one per log, per internal call, per token of an
+    /// ERC-1155 batch, and one per action that returned it.
+    pub fn row_key(&self) -> TxRowKey {
+        (
+            self.hash.to_ascii_lowercase(),
+            self.log_index.clone(),
+            self.trace_id.clone(),
+            self.token_id.clone(),
+            self.kind,
+        )
+    }
 }
@@ -1,5 +1,7 @@
 use chrono::NaiveDateTime;
 
+use super::AssetKind;
+
 pub struct TxSample {
     pub from: String,
     pub to: String,
@@ -10,4 +12,5 @@ pub struct TxSample {
     pub gas_fee_eth: Option<f64>,
     pub contract_address: String,
     pub hash: String,
+    pub kind: AssetKind,
 }
@@ -7,5 +7,7 @@ pub enum WalletType {
     Foreign,
     Bridge,
     Mixer,
+    /// A contract or proxy rather than someone's wallet, so not scored.
+    Contract,
     Unknown,
 }
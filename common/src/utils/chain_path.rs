@@ -0,0 +1,64 @@
+use std::path::{Path, PathBuf};
+
+use crate::model::Chain;
+
+/// Where `chain` keeps the file or directory configured as `path`, so every
+/// chain writes side by side: Ethereum uses `path` itself, other chains add
+/// their name to the stem (`flow.jsonl` becomes `flow-bsc.jsonl`).
+pub fn chain_path<P>(path: P, chain: Chain) -> PathBuf
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+
+    if chain == Chain::Ethereum {
+        return path.to_path_buf();
+    }
+
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let name = match path.extension() {
+        Some(extension) => format!("{stem}-{chain}.{}", extension.to_string_lossy()),
+        None => format!("{stem}-{chain}"),
+    };
+
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chain_path_keeps_ethereum_on_the_configured_path() {
+        assert_eq!(
+            chain_path("netraflow/data/flow.jsonl", Chain::Ethereum),
+            PathBuf::from("netraflow/data/flow.jsonl")
+        );
+    }
+
+    #[test]
+    fn chain_path_adds_the_chain_to_the_stem() {
+        let cases = [
+            (
+                "netraflow/data/flow.jsonl",
+                Chain::Bsc,
+                "netraflow/data/flow-bsc.jsonl",
+            ),
+            ("checkpoint.json", Chain::Polygon, "checkpoint-polygon.json"),
+            (
+                "netracrawl/data/archive",
+                Chain::Arbitrum,
+                "netracrawl/data/archive-arbitrum",
+            ),
+            ("data/archive/", Chain::Base, "data/archive-base"),
+        ];
+
+        for (path, chain, expected) in cases {
+            assert_eq!(chain_path(path, chain), PathBuf::from(expected), "{path}");
+        }
+    }
+}
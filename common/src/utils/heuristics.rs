@@ -1,33 +1,156 @@
 use std::{
     collections::HashMap,
-    fs::{create_dir_all, read_to_string, File},
+    fs::{create_dir_all, read_dir, read_to_string, File},
     io::Write,
-    path::Path,
+    path::{Path, PathBuf},
+    sync::OnceLock,
 };
 
 use serde::{Deserialize, Serialize};
 use serde_json::{from_str, to_string_pretty};
+use sha2::{Digest, Sha256};
 
+use super::{
+    label_registry::{AddressLabel, LabelRegistry, CURATED_CONFIDENCE, LEARNED_CONFIDENCE},
+    label_set::{is_domestic_region, Label, LabelMetadata, LabelSet, UNTAGGED_CEX_REGION},
+};
 use crate::{
     error::{Error, Result},
-    model::TxCategory,
+    model::{Chain, HeuristicsVersion, LabelFileVersion, LabelKind, TxCategory},
 };
 
-#[derive(Deserialize, Serialize)]
+/// Entity name older transaction scans gave the addresses they learned.
+const LEARNED_ENTITY: &str = "unknown";
+
+/// Provenance recorded for the `cex` and `bridge` maps of the file itself.
+const HEURISTICS_SOURCE: &str = "heuristics";
+
+/// Known CEX and bridge addresses, grouped by entity name, plus imported
+/// region-tagged label sets that decide whether a transfer is domestic.
+///
+/// The top-level labels hold on every chain, which fits exchange hot wallets
+/// reusing one key across EVM chains. Contracts deployed per chain go under
+/// `chains.<chain>`, and [`Heuristics::for_chain`] merges both into the view
+/// a classifier on that chain should use.
+///
+/// Lookups go through a [`LabelRegistry`] built on first use. Change labels
+/// through the methods below, which drop it so it is rebuilt.
+#[derive(Clone, Deserialize, Serialize)]
 pub struct Heuristics {
+    /// Bumped by [`Heuristics::save_version`] whenever the labels change, 0
+    /// for files never saved that way.
+    #[serde(default, skip_serializing_if = "is_unversioned")]
+    pub version: u64,
     pub cex: HashMap<String, Vec<String>>,
     pub bridge: HashMap<String, Vec<String>>,
+    /// Curated label files, see [`Heuristics::import_labels`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<LabelSet>,
+    /// Labels that only hold on one chain. Their own `chains` are ignored.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub chains: HashMap<Chain, Heuristics>,
+    #[serde(skip)]
+    registry: OnceLock<LabelRegistry>,
 }
 
 impl Heuristics {
     pub fn new() -> Self {
         Self {
+            version: 0,
             cex: HashMap::new(),
             bridge: HashMap::new(),
+            labels: Vec::new(),
+            chains: HashMap::new(),
+            registry: OnceLock::new(),
         }
     }
 
+    /// Adds a region-tagged label set, replacing an older copy of the same
+    /// list (same region and source).
+    pub fn import_labels(&mut self, set: LabelSet) {
+        self.registry.take();
+        match self.labels.iter_mut().find(|known| known.same_origin(&set)) {
+            Some(known) => *known = set,
+            None => self.labels.push(set),
+        }
+    }
+
+    /// Imports the label file at `path`, e.g. `data/indonesia-cex.json`.
+    pub fn import_label_file<P>(&mut self, path: P) -> Result<LabelFileVersion>
+    where
+        P: AsRef<Path>,
+    {
+        let set = LabelSet::load(&path)?;
+        let version = LabelFileVersion {
+            path: path.as_ref().display().to_string(),
+            hash: json_hash(&set)?,
+        };
+
+        self.import_labels(set);
+        Ok(version)
+    }
+
+    /// Imports the label files at `paths` and returns what records
+    /// classified with the result should be stamped with: the version of
+    /// these labels as loaded, which matches an entry of
+    /// [`Heuristics::history`], and the hash of each label file.
+    pub fn import_label_files<P>(&mut self, paths: &[P]) -> Result<HeuristicsVersion>
+    where
+        P: AsRef<Path>,
+    {
+        let mut version = self.version_info()?;
+        for path in paths {
+            version.label_files.push(self.import_label_file(path)?);
+        }
+
+        Ok(version)
+    }
+
+    /// Labels as seen on `chain`: the shared ones plus that chain's own.
+    /// A chain's label set joins the shared set of the same region and
+    /// source, its entries overriding shared ones of the same address.
+    pub fn for_chain(&self, chain: Chain) -> Heuristics {
+        let mut scoped = Heuristics {
+            version: self.version,
+            cex: self.cex.clone(),
+            bridge: self.bridge.clone(),
+            labels: self.labels.clone(),
+            chains: HashMap::new(),
+            registry: OnceLock::new(),
+        };
+
+        if let Some(own) = self.chains.get(&chain) {
+            for set in &own.labels {
+                for label in &set.data {
+                    scoped.add_label(set.metadata.clone(), label.clone());
+                }
+            }
+            for (name, addresses) in &own.cex {
+                scoped
+                    .cex
+                    .entry(name.clone())
+                    .or_default()
+                    .extend_from_slice(addresses);
+            }
+            for (name, addresses) in &own.bridge {
+                scoped
+                    .bridge
+                    .entry(name.clone())
+                    .or_default()
+                    .extend_from_slice(addresses);
+            }
+        }
+
+        scoped
+    }
+
+    /// Labels that only hold on `chain`, for adding to.
+    pub fn chain_mut(&mut self, chain: Chain) -> &mut Heuristics {
+        self.chains.entry(chain).or_default()
+    }
+
     pub fn push_cex(&mut self, name: impl Into<String>, address: impl Into<String>) {
+        self.registry.take();
         self.cex
             .entry(name.into())
             .or_default()
@@ -35,18 +158,73 @@ impl Heuristics {
     }
 
     pub fn push_bridge(&mut self, name: impl Into<String>, address: impl Into<String>) {
+        self.registry.take();
         self.bridge
             .entry(name.into())
             .or_default()
             .push(address.into());
     }
 
-    pub fn push_by_tx_category(&mut self, category: &TxCategory, address: impl Into<String>) {
-        match category {
-            TxCategory::Foreign => self.push_cex("unknown", address),
-            TxCategory::Bridge => self.push_bridge("unknown", address),
-            _ => {}
+    /// Adds `label` to the set described by `metadata`, creating the set if
+    /// needed and replacing an earlier label of the same address in it. A
+    /// label joining a set of another kind keeps its own.
+    pub fn add_label(&mut self, metadata: LabelMetadata, mut label: Label) {
+        self.registry.take();
+
+        let set = match self
+            .labels
+            .iter()
+            .position(|known| known.metadata.same_origin(&metadata))
+        {
+            Some(at) => &mut self.labels[at],
+            None => {
+                self.labels.push(LabelSet {
+                    metadata: metadata.clone(),
+                    data: vec![],
+                });
+                self.labels.last_mut().expect("label set just pushed")
+            }
+        };
+
+        if set.metadata.kind != metadata.kind {
+            label.kind.get_or_insert(metadata.kind);
+        }
+        set.data
+            .retain(|known| !known.address.eq_ignore_ascii_case(&label.address));
+        set.data.push(label);
+        set.metadata.last_updated = metadata.last_updated;
+    }
+
+    /// Removes `address` from the maps and label sets, only under `entity`
+    /// when given. Returns how many entries were removed; sets and entities
+    /// left empty are dropped.
+    pub fn remove_address(&mut self, address: &str, entity: Option<&str>) -> usize {
+        self.registry.take();
+
+        let matches = |name: &str, known: &str| {
+            known.eq_ignore_ascii_case(address)
+                && entity.is_none_or(|entity| name.eq_ignore_ascii_case(entity))
+        };
+
+        let mut removed = 0;
+        for map in [&mut self.cex, &mut self.bridge] {
+            for (name, addresses) in map.iter_mut() {
+                let before = addresses.len();
+                addresses.retain(|known| !matches(name, known));
+                removed += before - addresses.len();
+            }
+            map.retain(|_, addresses| !addresses.is_empty());
         }
+
+        self.labels.retain_mut(|set| {
+            let before = set.data.len();
+            set.data
+                .retain(|label| !matches(&label.name, &label.address));
+            removed += before - set.data.len();
+            before == set.data.len() || !set.data.is_empty()
+        });
+
+        removed
     }
 
     pub fn load<P>(path: P) -> Result<Heuristics>
@@ -58,6 +236,18 @@ impl Heuristics {
         Ok(heuristics)
     }
 
+    /// Loads `path`, or starts empty when there is no file yet.
+    pub fn load_or_default<P>(path: P) -> Result<Heuristics>
+    where
+        P: AsRef<Path>,
+    {
+        if path.as_ref().exists() {
+            Heuristics::load(path)
+        } else {
+            Ok(Heuristics::new())
+        }
+    }
+
     pub fn write<P>(&self, path: P) -> Result<()>
     where
         P: AsRef<Path>,
@@ -75,23 +265,227 @@ impl Heuristics {
         Ok(())
     }
 
+    /// SHA-256 over every label held here, chains included. The version
+    /// itself is left out, and maps are hashed in key order, so the same
+    /// labels always give the same hash.
+    pub fn content_hash(&self) -> Result<String> {
+        let mut value = serde_json::to_value(self)?;
+        if let Some(object) = value.as_object_mut() {
+            object.remove("version");
+        }
+
+        json_hash(&value)
+    }
+
+    pub fn version_info(&self) -> Result<HeuristicsVersion> {
+        Ok(HeuristicsVersion {
+            version: self.version,
+            hash: self.content_hash()?,
+            label_files: Vec::new(),
+        })
+    }
+
+    /// Writes these labels to `path` as a new version when they differ from
+    /// the file there, keeping a copy of every version under `history/` next
+    /// to it. Returns the version the file is now at.
+    pub fn save_version<P>(&mut self, path: P) -> Result<HeuristicsVersion>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let previous = match path.exists() {
+            true => Some(Heuristics::load(path)?),
+            false => None,
+        };
+
+        if let Some(previous) = &previous {
+            if previous.content_hash()? == self.content_hash()? {
+                self.version = previous.version;
+                return self.version_info();
+            }
+
+            // Files from before versioning get their first snapshot here.
+            let snapshot = history_path(path, previous.version);
+            if !snapshot.exists() {
+                previous.write(snapshot)?;
+            }
+        }
+
+        let latest = previous.map_or(0, |previous| previous.version);
+        self.version = latest.max(self.version) + 1;
+        self.write(path)?;
+        self.write(history_path(path, self.version))?;
+
+        self.version_info()
+    }
+
+    /// Versions kept for the heuristics file at `path`, oldest first.
+    pub fn history<P>(path: P) -> Result<Vec<(HeuristicsVersion, PathBuf)>>
+    where
+        P: AsRef<Path>,
+    {
+        let dir = history_dir(path.as_ref());
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut versions = Vec::new();
+        for entry in read_dir(&dir)? {
+            let snapshot = entry?.path();
+            let is_version = snapshot
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| history_version(path.as_ref(), name).is_some());
+            if is_version {
+                let version = Heuristics::load(&snapshot)?.version_info()?;
+                versions.push((version, snapshot));
+            }
+        }
+
+        versions.sort_by_key(|(version, _)| version.version);
+        Ok(versions)
+    }
+
+    /// Index over every label held here, built on first use.
+    pub fn registry(&self) -> &LabelRegistry {
+        self.registry.get_or_init(|| self.build_registry())
+    }
+
+    fn build_registry(&self) -> LabelRegistry {
+        let mut registry = LabelRegistry::new();
+        for (address, label) in self.address_labels() {
+            registry.insert(address, label);
+        }
+        registry
+    }
+
+    /// Every label held here as `(address, label)`, in file order. Labels
+    /// under `chains` are not included.
+    pub fn address_labels(&self) -> Vec<(&str, AddressLabel)> {
+        let mut labels = Vec::new();
+
+        let maps = [
+            (LabelKind::Cex, &self.cex, Some(UNTAGGED_CEX_REGION)),
+            (LabelKind::Bridge, &self.bridge, None),
+        ];
+
+        for (kind, map, region) in maps {
+            for (name, addresses) in map {
+                let confidence = match name.as_str() {
+                    LEARNED_ENTITY => LEARNED_CONFIDENCE,
+                    _ => CURATED_CONFIDENCE,
+                };
+
+                for address in addresses {
+                    let label = AddressLabel {
+                        entity: name.clone(),
+                        kind,
+                        region: region.map(str::to_string),
+                        confidence,
+                        source: HEURISTICS_SOURCE.to_string(),
+                        last_updated: None,
+                    };
+                    labels.push((address.as_str(), label));
+                }
+            }
+        }
+
+        for set in &self.labels {
+            labels.extend(set.address_labels());
+        }
+
+        labels
+    }
+
+    /// Labels of `addr`, strongest first.
+    pub fn labels_of(&self, addr: &str) -> &[AddressLabel] {
+        self.registry().labels(addr)
+    }
+
+    /// Region of the exchange behind `addr`, `None` unless it is a labelled
+    /// CEX. CEX entries of the `cex` map count as [`UNTAGGED_CEX_REGION`].
+    pub fn region_of(&self, addr: &str) -> Option<&str> {
+        self.registry().region(addr)
+    }
+
     pub fn is_known_cex(&self, addr: &str) -> bool {
-        self.cex
-            .values()
-            .flatten()
-            .any(|cex| cex.eq_ignore_ascii_case(addr))
+        self.registry().has(addr, LabelKind::Cex)
     }
 
     pub fn is_known_bridge(&self, addr: &str) -> bool {
-        self.bridge
-            .values()
-            .flatten()
-            .any(|cex| cex.eq_ignore_ascii_case(addr))
+        self.registry().has(addr, LabelKind::Bridge)
     }
 
+    /// Whether [`Heuristics::categorize`] calls a transfer from `from` to
+    /// `to` domestic, so both always agree.
     pub fn is_known_domestic(&self, from: &str, to: &str) -> bool {
-        self.is_known_cex(from) && self.is_known_cex(to)
+        matches!(self.categorize(from, Some(to)), TxCategory::Domestic)
     }
+
+    /// Bridge when `to` is a known bridge. Otherwise the labelled ends
+    /// decide: domestic when every labelled end is in [`DOMESTIC_REGION`],
+    /// foreign when one is elsewhere, unknown when neither end is labelled.
+    ///
+    /// [`DOMESTIC_REGION`]: super::DOMESTIC_REGION
+    pub fn categorize(&self, from: &str, to: Option<&str>) -> TxCategory {
+        let Some(to) = to else {
+            return TxCategory::Unknown;
+        };
+
+        if self.is_known_bridge(to) {
+            return TxCategory::Bridge;
+        }
+
+        match (self.region_of(from), self.region_of(to)) {
+            (None, None) => TxCategory::Unknown,
+            (from, to) if from.into_iter().chain(to).all(is_domestic_region) => {
+                TxCategory::Domestic
+            }
+            _ => TxCategory::Foreign,
+        }
+    }
+}
+
+/// SHA-256 of `value` as JSON, with object keys in sorted order.
+fn json_hash<T>(value: &T) -> Result<String>
+where
+    T: Serialize,
+{
+    let value = serde_json::to_value(value)?;
+    Ok(format!(
+        "{:x}",
+        Sha256::digest(value.to_string().as_bytes())
+    ))
+}
+
+fn is_unversioned(version: &u64) -> bool {
+    *version == 0
+}
+
+/// Directory keeping past versions of the heuristics file at `path`.
+fn history_dir(path: &Path) -> PathBuf {
+    path.with_file_name("history")
+}
+
+/// Copy of version `version` of the heuristics file at `path`, e.g.
+/// `history/heuristics-v3.json` next to `heuristics.json`.
+fn history_path(path: &Path, version: u64) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    history_dir(path).join(format!("{stem}-v{version}.json"))
+}
+
+/// Version of the history file `name` of the heuristics file at `path`.
+fn history_version(path: &Path, name: &str) -> Option<u64> {
+    let stem = path.file_stem()?.to_str()?;
+    name.strip_prefix(stem)?
+        .strip_prefix("-v")?
+        .strip_suffix(".json")?
+        .parse()
+        .ok()
 }
 
 impl Default for Heuristics {
@@ -99,3 +493,147 @@ impl Default for Heuristics {
         Heuristics::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKOCRYPTO: &str = "0x0d0707963952f2fba59dd06f2b425ace40b492fe";
+    const INDODAX: &str = "0x51836a753e344257b361519e948ffcaf5fb8d521";
+    const BINANCE: &str = "0x28c6c06298d514db089934071355e5743bf21d60";
+    const KUCOIN: &str = "0xd6216fc19db775df9774a6e33526131da7d19a2c";
+    const ARBITRUM_BRIDGE: &str = "0x8315177ab297ba92a06054ce80a67ed4dbd7ed3a";
+    const UNISWAP_ROUTER: &str = "0x7a250d5630b4cf539739df2c5dacb4c659f2488d";
+    const WALLET: &str = "0x1111111111111111111111111111111111111111";
+
+    fn metadata(region: &str) -> LabelMetadata {
+        LabelMetadata {
+            region: Some(region.to_string()),
+            source: "manual".to_string(),
+            last_updated: "2025-07-01".to_string(),
+            kind: LabelKind::Cex,
+        }
+    }
+
+    fn label(name: &str, address: &str) -> Label {
+        Label {
+            name: name.to_string(),
+            address: address.to_string(),
+            kind: None,
+            confidence: None,
+        }
+    }
+
+    #[test]
+    fn for_chain_merges_chain_labels_into_the_shared_set() {
+        let mut heuristics = Heuristics::new();
+        heuristics.add_label(metadata("Indonesia"), label("Indodax", INDODAX));
+        heuristics
+            .chain_mut(Chain::Bsc)
+            .add_label(metadata("Indonesia"), label("Tokocrypto", TOKOCRYPTO));
+
+        let bsc = heuristics.for_chain(Chain::Bsc);
+        assert_eq!(bsc.region_of(INDODAX), Some("Indonesia"));
+        assert_eq!(bsc.region_of(TOKOCRYPTO), Some("Indonesia"));
+
+        let ethereum = heuristics.for_chain(Chain::Ethereum);
+        assert_eq!(ethereum.region_of(INDODAX), Some("Indonesia"));
+        assert_eq!(ethereum.region_of(TOKOCRYPTO), None);
+    }
+
+    #[test]
+    fn for_chain_lets_chain_labels_override_shared_ones() {
+        let mut heuristics = Heuristics::new();
+        heuristics.add_label(metadata("Indonesia"), label("Indodax", INDODAX));
+        heuristics.add_label(metadata("Indonesia"), label("Tokocrypto", TOKOCRYPTO));
+        heuristics
+            .chain_mut(Chain::Bsc)
+            .add_label(metadata("Indonesia"), label("Binance", TOKOCRYPTO));
+
+        let bsc = heuristics.for_chain(Chain::Bsc);
+        let entities: Vec<&str> = bsc
+            .labels_of(TOKOCRYPTO)
+            .iter()
+            .map(|label| label.entity.as_str())
+            .collect();
+
+        assert_eq!(entities, ["Binance"]);
+        assert_eq!(bsc.labels_of(INDODAX).len(), 1);
+    }
+
+    fn regional() -> Heuristics {
+        let mut heuristics = Heuristics::new();
+        heuristics.add_label(metadata("Indonesia"), label("Indodax", INDODAX));
+        heuristics.add_label(metadata("indonesia"), label("Tokocrypto", TOKOCRYPTO));
+        heuristics.add_label(metadata("Global"), label("Binance", BINANCE));
+        heuristics.push_cex("KuCoin", KUCOIN);
+        heuristics.push_bridge("Arbitrum", ARBITRUM_BRIDGE);
+        heuristics.add_label(
+            LabelMetadata {
+                kind: LabelKind::Defi,
+                ..metadata("Global")
+            },
+            label("Uniswap V2 Router", UNISWAP_ROUTER),
+        );
+        heuristics
+    }
+
+    #[test]
+    fn categorize_by_region_of_the_labelled_ends() {
+        let heuristics = regional();
+
+        let cases = [
+            (INDODAX, TOKOCRYPTO, TxCategory::Domestic),
+            (WALLET, INDODAX, TxCategory::Domestic),
+            (TOKOCRYPTO, WALLET, TxCategory::Domestic),
+            (INDODAX, BINANCE, TxCategory::Foreign),
+            (WALLET, BINANCE, TxCategory::Foreign),
+            (BINANCE, TOKOCRYPTO, TxCategory::Foreign),
+            (WALLET, KUCOIN, TxCategory::Foreign),
+            (INDODAX, UNISWAP_ROUTER, TxCategory::Domestic),
+            (WALLET, UNISWAP_ROUTER, TxCategory::Unknown),
+            (WALLET, WALLET, TxCategory::Unknown),
+        ];
+
+        for (from, to, expected) in cases {
+            let category = heuristics.categorize(from, Some(to));
+            assert_eq!(category, expected, "{from} -> {to}");
+            assert_eq!(
+                heuristics.is_known_domestic(from, to),
+                expected == TxCategory::Domestic
+            );
+        }
+    }
+
+    #[test]
+    fn only_exchange_labels_have_a_region() {
+        let heuristics = regional();
+
+        assert_eq!(heuristics.region_of(INDODAX), Some("Indonesia"));
+        assert_eq!(heuristics.region_of(KUCOIN), Some(UNTAGGED_CEX_REGION));
+        assert_eq!(heuristics.region_of(UNISWAP_ROUTER), None);
+        assert_eq!(heuristics.labels_of(UNISWAP_ROUTER).len(), 1);
+    }
+
+    #[test]
+    fn categorize_bridges_and_contract_creations() {
+        let heuristics = regional();
+
+        assert_eq!(
+            heuristics.categorize(INDODAX, Some(ARBITRUM_BRIDGE)),
+            TxCategory::Bridge
+        );
+        assert_eq!(heuristics.categorize(INDODAX, None), TxCategory::Unknown);
+    }
+
+    #[test]
+    fn categorize_ignores_address_case() {
+        let heuristics = regional();
+        let upper = format!("0x{}", TOKOCRYPTO[2..].to_ascii_uppercase());
+
+        assert_eq!(
+            heuristics.categorize(&upper, Some(INDODAX)),
+            TxCategory::Domestic
+        );
+    }
+}
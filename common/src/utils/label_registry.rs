@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::LabelKind;
+
+/// Confidence of curated labels that do not state their own.
+pub const CURATED_CONFIDENCE: f32 = 1.0;
+
+/// Confidence of the `unknown` entries older transaction scans added to the
+/// heuristics file on their own.
+pub const LEARNED_CONFIDENCE: f32 = 0.5;
+
+/// One thing known about an address, and where that knowledge came from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AddressLabel {
+    pub entity: String,
+    pub kind: LabelKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    pub confidence: f32,
+    /// List or file the label was taken from.
+    pub source: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_updated: Option<String>,
+}
+
+/// Raw 20-byte address, so lookups neither allocate nor care about case.
+type AddressKey = [u8; 20];
+
+/// Address labels indexed by address.
+///
+/// Addresses are decoded once on insert and every lookup is a single hash
+/// probe, so classifying a block costs a few lookups per transaction no
+/// matter how many labels are loaded. An address may carry several labels;
+/// they are kept strongest first.
+#[derive(Debug, Clone, Default)]
+pub struct LabelRegistry {
+    index: HashMap<AddressKey, Vec<AddressLabel>>,
+}
+
+impl LabelRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `label` to `address`. A label for the same entity and kind from
+    /// the same source is replaced rather than repeated. Returns `false`
+    /// when `address` is not a 20-byte hex address.
+    pub fn insert(&mut self, address: &str, label: AddressLabel) -> bool {
+        let Some(key) = address_key(address) else {
+            return false;
+        };
+
+        let labels = self.index.entry(key).or_default();
+        labels.retain(|known| {
+            !(known.kind == label.kind
+                && known.source == label.source
+                && known.entity.eq_ignore_ascii_case(&label.entity))
+        });
+
+        let at = labels
+            .iter()
+            .position(|known| known.confidence < label.confidence)
+            .unwrap_or(labels.len());
+        labels.insert(at, label);
+
+        true
+    }
+
+    /// Labels of `address`, strongest first.
+    pub fn labels(&self, address: &str) -> &[AddressLabel] {
+        address_key(address)
+            .and_then(|key| self.index.get(&key))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Strongest label of `kind` on `address`.
+    pub fn label(&self, address: &str, kind: LabelKind) -> Option<&AddressLabel> {
+        self.labels(address).iter().find(|label| label.kind == kind)
+    }
+
+    pub fn has(&self, address: &str, kind: LabelKind) -> bool {
+        self.label(address, kind).is_some()
+    }
+
+    /// Region of the strongest exchange label on `address` that names one.
+    /// Other kinds say where a contract is listed, not where its users
+    /// are, so they never place an address.
+    pub fn region(&self, address: &str) -> Option<&str> {
+        self.labels(address)
+            .iter()
+            .filter(|label| label.kind == LabelKind::Cex)
+            .find_map(|label| label.region.as_deref())
+    }
+
+    /// Number of labelled addresses.
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+}
+
+/// Decodes `0x`-prefixed or bare hex into an address key.
+fn address_key(address: &str) -> Option<AddressKey> {
+    let hex = address.trim();
+    let hex = hex
+        .strip_prefix("0x")
+        .or_else(|| hex.strip_prefix("0X"))
+        .unwrap_or(hex)
+        .as_bytes();
+
+    if hex.len() != 40 {
+        return None;
+    }
+
+    let mut key = [0u8; 20];
+    for (byte, pair) in key.iter_mut().zip(hex.chunks_exact(2)) {
+        *byte = (nibble(pair[0])? << 4) | nibble(pair[1])?;
+    }
+
+    Some(key)
+}
+
+fn nibble(digit: u8) -> Option<u8> {
+    match digit {
+        b'0'..=b'9' => Some(digit - b'0'),
+        b'a'..=b'f' => Some(digit - b'a' + 10),
+        b'A'..=b'F' => Some(digit - b'A' + 10),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BINANCE_14: &str = "0x28c6c06298d514db089934071355e5743bf21d60";
+
+    fn label(entity: &str, source: &str, confidence: f32) -> AddressLabel {
+        AddressLabel {
+            entity: entity.to_string(),
+            kind: LabelKind::Cex,
+            region: Some("Global".to_string()),
+            confidence,
+            source: source.to_string(),
+            last_updated: None,
+        }
+    }
+
+    #[test]
+    fn lookups_ignore_case_and_prefix() {
+        let mut registry = LabelRegistry::new();
+        assert!(registry.insert(BINANCE_14, label("Binance", "curated", 1.0)));
+
+        let upper = format!("0X{}", BINANCE_14[2..].to_ascii_uppercase());
+        for address in [
+            BINANCE_14,
+            &upper,
+            &BINANCE_14[2..],
+            " 0x28C6c06298d514Db089934071355E5743bf21d60 ",
+        ] {
+            assert!(registry.has(address, LabelKind::Cex), "{address}");
+        }
+        assert!(!registry.has(BINANCE_14, LabelKind::Bridge));
+        assert_eq!(registry.len(), 1);
+    }
+
+    #[test]
+    fn malformed_addresses_are_refused_and_never_match() {
+        let mut registry = LabelRegistry::new();
+
+        for address in [
+            "",
+            "0x",
+            "0x28c6c06298d514db",
+            "0x28c6c06298d514db089934071355e5743bf21d6g",
+            "0x28c6c06298d514db089934071355e5743bf21d6000",
+        ] {
+            assert!(
+                !registry.insert(address, label("Binance", "curated", 1.0)),
+                "{address}"
+            );
+            assert!(registry.labels(address).is_empty(), "{address}");
+        }
+        assert!(registry.is_empty());
+    }
+
+    #[test]
+    fn labels_are_kept_strongest_first() {
+        let mut registry = LabelRegistry::new();
+        registry.insert(BINANCE_14, label("unknown", "heuristics", 0.5));
+        registry.insert(BINANCE_14, label("Binance", "curated", 1.0));
+        registry.insert(BINANCE_14, label("Binance 14", "explorer", 0.8));
+
+        let entities: Vec<&str> = registry
+            .labels(BINANCE_14)
+            .iter()
+            .map(|label| label.entity.as_str())
+            .collect();
+        assert_eq!(entities, ["Binance", "Binance 14", "unknown"]);
+    }
+
+    #[test]
+    fn same_entity_from_the_same_source_is_replaced() {
+        let mut registry = LabelRegistry::new();
+        registry.insert(BINANCE_14, label("Binance", "curated", 0.9));
+        registry.insert(BINANCE_14, label("BINANCE", "curated", 1.0));
+        registry.insert(BINANCE_14, label("Binance", "explorer", 0.8));
+
+        let labels = registry.labels(BINANCE_14);
+        assert_eq!(labels.len(), 2);
+        assert_eq!(labels[0].entity, "BINANCE");
+        assert_eq!(labels[0].confidence, 1.0);
+    }
+
+    #[test]
+    fn region_comes_from_the_strongest_exchange_label_naming_one() {
+        let mut registry = LabelRegistry::new();
+        let mut untagged = label("Binance", "curated", 1.0);
+        untagged.region = None;
+        let mut tagged = label("Tokocrypto", "indonesia", 0.8);
+        tagged.region = Some("Indonesia".to_string());
+
+        let mut defi = label("Router", "defi", 1.0);
+        defi.kind = LabelKind::Defi;
+
+        registry.insert(BINANCE_14, untagged);
+        registry.insert(BINANCE_14, tagged);
+        registry.insert(BINANCE_14, defi);
+
+        assert_eq!(registry.region(BINANCE_14), Some("Indonesia"));
+        assert_eq!(
+            registry.region("0x0000000000000000000000000000000000000000"),
+            None
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use std::{fs::read_to_string, path::Path};
+
+use serde::{Deserialize, Serialize};
+use serde_json::from_str;
+
+use super::label_registry::{AddressLabel, CURATED_CONFIDENCE};
+use crate::{
+    error::{Error, Result},
+    model::LabelKind,
+};
+
+/// Region of the entities this tool treats as domestic.
+pub const DOMESTIC_REGION: &str = "Indonesia";
+
+/// Curated label files shipped in `data/`, comma-separated.
+pub const DEFAULT_LABEL_FILES: &str = "data/global-cex.json,data/indonesia-cex.json";
+
+/// Region assumed for CEX labels that carry none, matching the old rule
+/// that a known exchange is a foreign one.
+pub const UNTAGGED_CEX_REGION: &str = "Global";
+
+/// A curated label file such as `data/indonesia-cex.json`: entities of one
+/// region, with where the list came from and when it was last checked.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LabelSet {
+    pub metadata: LabelMetadata,
+    pub data: Vec<Label>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LabelMetadata {
+    /// Region of the entities, see [`default_region`] for sets without one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    pub source: String,
+    pub last_updated: String,
+    /// Kind of every entry that does not state its own, `cex` when unset.
+    #[serde(default)]
+    pub kind: LabelKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Label {
+    pub name: String,
+    pub address: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<LabelKind>,
+    /// Between 0 and 1, [`CURATED_CONFIDENCE`] when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+}
+
+impl LabelMetadata {
+    /// Whether labels described by `other` belong in the same set: same
+    /// region, ignoring case, and same source.
+    pub fn same_origin(&self, other: &LabelMetadata) -> bool {
+        let same_region = match (&self.region, &other.region) {
+            (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+            (a, b) => a == b,
+        };
+        same_region && self.source == other.source
+    }
+}
+
+impl LabelSet {
+    pub fn load<P>(path: P) -> Result<LabelSet>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let file = read_to_string(path).map_err(|err| {
+            Error::from_io(err, format!("❌ Failed to read labels {}", path.display()))
+        })?;
+
+        Ok(from_str(&file)?)
+    }
+
+    /// Whether `other` is a newer copy of the same list.
+    pub fn same_origin(&self, other: &LabelSet) -> bool {
+        self.metadata.same_origin(&other.metadata)
+    }
+
+    /// Entries as registry labels, keyed by their address.
+    pub fn address_labels(&self) -> impl Iterator<Item = (&str, AddressLabel)> {
+        self.data.iter().map(|label| {
+            let kind = label.kind.unwrap_or(self.metadata.kind);
+            let address_label = AddressLabel {
+                entity: label.name.clone(),
+                kind,
+                region: self
+                    .metadata
+                    .region
+                    .clone()
+                    .or_else(|| default_region(kind)),
+                confidence: label.confidence.unwrap_or(CURATED_CONFIDENCE),
+                source: self.metadata.source.clone(),
+                last_updated: Some(self.metadata.last_updated.clone()),
+            };
+            (label.address.as_str(), address_label)
+        })
+    }
+}
+
+/// Region given to labels of `kind` that state none: exchanges are assumed
+/// foreign as before, other kinds stay without a region.
+pub fn default_region(kind: LabelKind) -> Option<String> {
+    (kind == LabelKind::Cex).then(|| UNTAGGED_CEX_REGION.to_string())
+}
+
+pub fn is_domestic_region(region: &str) -> bool {
+    region.eq_ignore_ascii_case(DOMESTIC_REGION)
+}
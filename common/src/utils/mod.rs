@@ -1,6 +1,15 @@
+mod chain_path;
 mod heuristics;
+mod label_registry;
+mod label_set;
 mod price_lookup;
 
+pub use chain_path::chain_path;
 pub use heuristics::Heuristics;
+pub use label_registry::{AddressLabel, LabelRegistry, CURATED_CONFIDENCE, LEARNED_CONFIDENCE};
+pub use label_set::{
+    default_region, is_domestic_region, Label, LabelMetadata, LabelSet, DEFAULT_LABEL_FILES,
+    DOMESTIC_REGION, UNTAGGED_CEX_REGION,
+};
 pub use price_lookup::dummy_price_lookup;
 pub use price_lookup::PriceLookupFn;
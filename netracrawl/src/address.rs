@@ -0,0 +1,613 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use common::model::{AddressKind, Chain};
+
+use crate::{
+    error::{Error, Result},
+    ethereum::EthereumClient,
+};
+
+/// EIP-1967 `bytes32(uint256(keccak256("eip1967.proxy.implementation")) - 1)`
+const EIP1967_IMPLEMENTATION_SLOT: &str =
+    "360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc";
+/// EIP-1967 `bytes32(uint256(keccak256("eip1967.proxy.beacon")) - 1)`
+const EIP1967_BEACON_SLOT: &str =
+    "a3f0ad74e5423aebfd80d3ef4346578335a9a72aeaee59ff6cb3582b35133d50";
+/// EIP-1822 `keccak256("PROXIABLE")`
+const EIP1822_PROXIABLE_SLOT: &str =
+    "c5f16f0fcc639fa48a6947836d9850f504798523bf8c9a3a87d5876cf622bcf7";
+/// `proxiableUUID()`, exposed by UUPS implementations, which read the same
+/// slots as their proxy and may delegate during upgrades.
+const PROXIABLE_UUID_SELECTOR: &str = "52d1902d";
+
+const EQ: u8 = 0x14;
+const ISZERO: u8 = 0x15;
+const CALLDATALOAD: u8 = 0x35;
+const SLOAD: u8 = 0x54;
+const JUMPI: u8 = 0x57;
+const PUSH1: u8 = 0x60;
+const PUSH4: u8 = 0x63;
+const PUSH32: u8 = 0x7f;
+const DUP1: u8 = 0x80;
+const SWAP16: u8 = 0x9f;
+const DELEGATECALL: u8 = 0xf4;
+
+/// EIP-1167 runtime code around the 20-byte implementation address.
+const MINIMAL_PROXY_PREFIX: &str = "363d3d373d3d3d363d73";
+const MINIMAL_PROXY_SUFFIX: &str = "5af43d82803e903d91602b57fd5bf3";
+/// EIP-7702 delegation designator, followed by the delegate address.
+const DELEGATION_PREFIX: &str = "ef0100";
+
+/// `masterCopy()`, which the small Safe proxy runtime matches as a `PUSH32`
+/// word before delegating everything else.
+const SAFE_PROXY_SELECTOR: &str = "a619486e";
+/// Safe proxy runtimes are a few hundred bytes; anything larger carrying
+/// the selector is a full contract.
+const SAFE_PROXY_MAX_CODE_HEX: usize = 2_000;
+/// Selectors account contracts dispatch on: `execTransaction(...)` (Safe)
+/// and `validateUserOp` for EntryPoint v0.6 and v0.7.
+const SAFE_EXEC_SELECTOR: &str = "6a761202";
+const VALIDATE_USER_OP_SELECTORS: [&str; 2] = ["3a871cdd", "19822f7c"];
+/// Instructions a dispatcher spends between pushing a selector and
+/// branching on it.
+const DISPATCH_WINDOW: usize = 8;
+
+/// What an account's runtime bytecode reveals about it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodeShape {
+    Empty,
+    /// EIP-7702 delegation to `0`.
+    Delegated(String),
+    /// EIP-1167 minimal proxy to `0`.
+    MinimalProxy(String),
+    /// Proxy reading its implementation from storage slot `0`.
+    SlotProxy(&'static str),
+    BeaconProxy,
+    SafeProxy,
+    SmartAccount,
+    Contract,
+}
+
+impl CodeShape {
+    pub fn parse(code: &str) -> Self {
+        let code = code.trim_start_matches("0x").to_ascii_lowercase();
+
+        if code.is_empty() {
+            return CodeShape::Empty;
+        }
+
+        if let Some(target) = code.strip_prefix(DELEGATION_PREFIX) {
+            if target.len() == 40 {
+                return CodeShape::Delegated(format!("0x{target}"));
+            }
+        }
+
+        if let Some(rest) = code.strip_prefix(MINIMAL_PROXY_PREFIX) {
+            if rest.len() == 40 + MINIMAL_PROXY_SUFFIX.len() && rest.ends_with(MINIMAL_PROXY_SUFFIX)
+            {
+                return CodeShape::MinimalProxy(format!("0x{}", &rest[..40]));
+            }
+        }
+
+        let ops = disassemble(&code);
+        let delegates = ops.iter().any(|(op, _)| *op == DELEGATECALL);
+
+        if delegates
+            && code.len() < SAFE_PROXY_MAX_CODE_HEX
+            && dispatches(&ops, SAFE_PROXY_SELECTOR)
+        {
+            return CodeShape::SafeProxy;
+        }
+
+        // Contracts calling an account (the EntryPoint, Safe modules,
+        // multisend helpers) push the same selectors, so only accounts
+        // dispatching on them count.
+        if dispatches(&ops, SAFE_EXEC_SELECTOR)
+            || VALIDATE_USER_OP_SELECTORS
+                .iter()
+                .any(|selector| dispatches(&ops, selector))
+        {
+            return CodeShape::SmartAccount;
+        }
+
+        // Implementations and tooling mention the proxy slots too; only code
+        // loading one and delegating to what it holds is a proxy.
+        let proxiable = ops
+            .iter()
+            .any(|(op, data)| *op == PUSH4 && *data == PROXIABLE_UUID_SELECTOR);
+
+        if delegates && !proxiable {
+            if loads_slot(&ops, EIP1967_BEACON_SLOT) {
+                return CodeShape::BeaconProxy;
+            }
+
+            for slot in [EIP1967_IMPLEMENTATION_SLOT, EIP1822_PROXIABLE_SLOT] {
+                if loads_slot(&ops, slot) {
+                    return CodeShape::SlotProxy(slot);
+                }
+            }
+        }
+
+        CodeShape::Contract
+    }
+
+    /// Kind without looking behind a proxy.
+    pub fn kind(&self) -> AddressKind {
+        match self {
+            CodeShape::Empty => AddressKind::Eoa,
+            CodeShape::Delegated(_) | CodeShape::SafeProxy | CodeShape::SmartAccount => {
+                AddressKind::SmartAccount
+            }
+            CodeShape::MinimalProxy(_) | CodeShape::SlotProxy(_) | CodeShape::BeaconProxy => {
+                AddressKind::Proxy
+            }
+            CodeShape::Contract => AddressKind::Contract,
+        }
+    }
+
+    /// Whether the kind can change later. Empty addresses may get code
+    /// deployed (counterfactual smart accounts) and EIP-7702 delegations can
+    /// be revoked, so only other shapes are worth persisting.
+    pub fn is_durable(&self) -> bool {
+        !matches!(self, CodeShape::Empty | CodeShape::Delegated(_))
+    }
+}
+
+/// Kinds of addresses by chain, then by lowercase address, as stored in
+/// the cache file. The same address can hold different code on each chain.
+type StoredKinds = BTreeMap<Chain, BTreeMap<String, AddressKind>>;
+
+/// Types addresses by their code over JSON-RPC, looking one hop behind
+/// proxies so a proxied ERC-4337 account reads as a smart account. Results
+/// are cached in memory and, for durable kinds, in an optional JSON file,
+/// both keyed by chain and address.
+pub struct AddressTyper {
+    client: EthereumClient,
+    cache: Mutex<HashMap<(Chain, String), AddressKind>>,
+    path: Option<PathBuf>,
+}
+
+impl AddressTyper {
+    pub fn new(client: EthereumClient) -> Self {
+        Self {
+            client,
+            cache: Mutex::new(HashMap::new()),
+            path: None,
+        }
+    }
+
+    /// Loads and keeps updating the cache file at `path`.
+    pub fn with_cache_file(mut self, path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+
+        if path.exists() {
+            let stored = load_cache(&path)?;
+            self.cache
+                .get_mut()
+                .expect("cache lock")
+                .extend(stored.into_iter().flat_map(|(chain, kinds)| {
+                    kinds
+                        .into_iter()
+                        .map(move |(address, kind)| ((chain, address), kind))
+                }));
+        }
+
+        self.path = Some(path);
+        Ok(self)
+    }
+
+    pub async fn kind(&self, address: &str) -> Result<AddressKind> {
+        let kinds = self.kinds(&[address.to_string()]).await?;
+        Ok(kinds[&address.to_ascii_lowercase()])
+    }
+
+    /// Kinds of `addresses` on the client's chain, keyed by lowercase
+    /// address.
+    pub async fn kinds(&self, addresses: &[String]) -> Result<HashMap<String, AddressKind>> {
+        let chain = self.client.chain();
+        let mut wanted: Vec<String> = addresses.iter().map(|a| a.to_ascii_lowercase()).collect();
+        wanted.sort_unstable();
+        wanted.dedup();
+
+        let missing: Vec<String> = {
+            let cache = self.cache.lock().expect("cache lock");
+            wanted
+                .iter()
+                .filter(|address| !cache.contains_key(&(chain, address.to_string())))
+                .cloned()
+                .collect()
+        };
+
+        if !missing.is_empty() {
+            let resolved = self.resolve(&missing).await?;
+            let durable = resolved.iter().any(|(_, _, durable)| *durable);
+
+            let mut cache = self.cache.lock().expect("cache lock");
+            for (address, kind, _) in &resolved {
+                cache.insert((chain, address.clone()), *kind);
+            }
+            drop(cache);
+
+            if durable {
+                self.save(&resolved)?;
+            }
+        }
+
+        let cache = self.cache.lock().expect("cache lock");
+        Ok(wanted
+            .into_iter()
+            .filter_map(|address| {
+                let kind = *cache.get(&(chain, address.clone()))?;
+                Some((address, kind))
+            })
+            .collect())
+    }
+
+    async fn resolve(&self, addresses: &[String]) -> Result<Vec<(String, AddressKind, bool)>> {
+        let shapes: Vec<CodeShape> = self
+            .client
+            .get_codes(addresses)
+            .await?
+            .iter()
+            .map(|code| CodeShape::parse(code))
+            .collect();
+
+        let slots: Vec<(String, String)> = addresses
+            .iter()
+            .zip(&shapes)
+            .filter_map(|(address, shape)| match shape {
+                CodeShape::SlotProxy(slot) => Some((address.clone(), format!("0x{slot}"))),
+                _ => None,
+            })
+            .collect();
+
+        let mut words = self.client.get_storage_words(&slots).await?.into_iter();
+        let targets: Vec<Option<String>> = shapes
+            .iter()
+            .map(|shape| match shape {
+                CodeShape::MinimalProxy(target) => Some(target.clone()),
+                CodeShape::SlotProxy(_) => words.next().and_then(|word| word_address(&word)),
+                _ => None,
+            })
+            .collect();
+
+        let lookups: Vec<String> = targets.iter().flatten().cloned().collect();
+        let mut implementations = self
+            .client
+            .get_codes(&lookups)
+            .await?
+            .into_iter()
+            .map(|code| CodeShape::parse(&code));
+
+        Ok(addresses
+            .iter()
+            .zip(shapes)
+            .zip(targets)
+            .map(|((address, shape), target)| {
+                let implementation = target.and_then(|_| implementations.next());
+                let kind = match implementation {
+                    Some(inner) if inner.kind() == AddressKind::SmartAccount => {
+                        AddressKind::SmartAccount
+                    }
+                    _ => shape.kind(),
+                };
+                (address.clone(), kind, shape.is_durable())
+            })
+            .collect())
+    }
+
+    /// Adds the durable entries of `resolved` to the cache file.
+    fn save(&self, resolved: &[(String, AddressKind, bool)]) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let mut stored = if path.exists() {
+            load_cache(path).unwrap_or_default()
+        } else {
+            StoredKinds::new()
+        };
+
+        let kinds = stored.entry(self.client.chain()).or_default();
+        for (address, kind, durable) in resolved {
+            if *durable {
+                kinds.insert(address.clone(), *kind);
+            }
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_vec_pretty(&stored)?)?;
+
+        Ok(())
+    }
+}
+
+/// Reads the cache file at `path`.
+fn load_cache(path: &Path) -> Result<StoredKinds> {
+    let text = fs::read_to_string(path)?;
+    serde_json::from_str(&text)
+        .map_err(|e| Error::Serde(e, format!("Invalid address cache {}", path.display())))
+}
+
+/// Opcodes of the hex runtime `code`, each with the hex data it pushes, so
+/// pushed constants are not mistaken for instructions or the other way
+/// round. Stops at the first byte that is not hex.
+fn disassemble(code: &str) -> Vec<(u8, &str)> {
+    let mut ops = Vec::with_capacity(code.len() / 2);
+    let mut at = 0;
+
+    while at + 2 <= code.len() {
+        let Some(op) = code
+            .get(at..at + 2)
+            .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+        else {
+            break;
+        };
+        at += 2;
+
+        let data = match op {
+            PUSH1..=PUSH32 => {
+                let end = (at + 2 * usize::from(op - PUSH1 + 1)).min(code.len());
+                let data = code.get(at..end).unwrap_or_default();
+                at = end;
+                data
+            }
+            _ => "",
+        };
+        ops.push((op, data));
+    }
+
+    ops
+}
+
+/// Whether `ops` compare the calldata selector against `selector` and jump
+/// on the result, as a function dispatcher does. The selector is pushed as
+/// a `PUSH4`, or left-aligned in a `PUSH32` word as the Safe proxy does.
+/// Code calling the function pushes it too, but shifts or stores it next.
+fn dispatches(ops: &[(u8, &str)], selector: &str) -> bool {
+    ops.iter().enumerate().any(|(at, (op, data))| {
+        let pushed = match *op {
+            PUSH4 => *data == selector,
+            PUSH32 => data
+                .strip_prefix(selector)
+                .is_some_and(|rest| rest.len() == 56 && rest.bytes().all(|b| b == b'0')),
+            _ => false,
+        };
+
+        pushed && branches_on_eq(&ops[at + 1..])
+    })
+}
+
+/// Whether `ops` start with an `EQ` followed by a `JUMPI`, with only
+/// operand loading and stack shuffling around them.
+fn branches_on_eq(ops: &[(u8, &str)]) -> bool {
+    let mut compared = false;
+
+    for (op, _) in ops.iter().take(DISPATCH_WINDOW) {
+        match *op {
+            EQ => compared = true,
+            JUMPI => return compared,
+            ISZERO | CALLDATALOAD | PUSH1..=PUSH32 | DUP1..=SWAP16 => {}
+            _ => return false,
+        }
+    }
+
+    false
+}
+
+/// Whether `ops` push `slot` and read storage at it, allowing for stack
+/// shuffling between the two.
+fn loads_slot(ops: &[(u8, &str)], slot: &str) -> bool {
+    ops.iter().enumerate().any(|(at, (op, data))| {
+        *op == PUSH32
+            && *data == slot
+            && ops[at + 1..]
+                .iter()
+                .find(|(next, _)| !(DUP1..=SWAP16).contains(next))
+                .is_some_and(|(next, _)| *next == SLOAD)
+    })
+}
+
+/// Address held in the low 20 bytes of a storage word, `None` when zero.
+fn word_address(word: &str) -> Option<String> {
+    let hex = word.trim_start_matches("0x");
+    let hex = format!("{hex:0>64}");
+    let address = &hex[hex.len() - 40..];
+
+    (address.chars().any(|c| c != '0')).then(|| format!("0x{address}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TARGET: &str = "d9db270c1b5e3bd161e8c8503c55ceabee709552";
+
+    /// Safe v1.3.0 proxy runtime.
+    const SAFE_PROXY: &str = concat!(
+        "608060405273ffffffffffffffffffffffffffffffffffffffff600054167f",
+        "a619486e00000000000000000000000000000000000000000000000000000000",
+        "60003514156050578060005260206000f35b3660008037600080366000845af4",
+        "3d6000803e60008114156070573d6000fd5b3d6000f3fea26469706673582212",
+        "20d1429297349653a4918076d650332de1a1068c5f3e07c5c82360c277770b95",
+        "5264736f6c63430007060033",
+    );
+
+    /// Forwards calldata to the address held at the pushed slot and bubbles
+    /// up the result, like every slot-based proxy's fallback.
+    const DELEGATE_FORWARD: &str = concat!(
+        "73ffffffffffffffffffffffffffffffffffffffff16", // address mask
+        "3660008037600080366000845af4",                 // DELEGATECALL
+        "3d6000803e808015605457",
+        "3d6000f35b3d6000fd",
+    );
+
+    /// Dispatcher and `validateUserOp` call site of the EntryPoint v0.6
+    /// runtime, trimmed to the instructions around each selector.
+    const ENTRY_POINT_V06: &str = concat!(
+        "6080604052",               // PUSH1 0x80 PUSH1 0x40 MSTORE
+        "6004361061012d57",         // CALLDATASIZE < 4: fallback
+        "60003560e01c",             // selector = CALLDATALOAD(0) >> 224
+        "80631fad948c1461013257",   // handleOps
+        "806335567e1a1461015257",   // getNonce
+        "8063b760faf91461017257",   // depositTo
+        "5b600080fd",               // revert
+        "5b604051633a871cdd60e01b", // PUSH4 validateUserOp, SHL 224
+        "8152600060405180830381",   // MSTORE the calldata
+        "6000875af1",               // CALL the account
+        "00",
+    );
+
+    /// Dispatcher of the Safe v1.3.0 singleton, with the binary search
+    /// pivot on `execTransaction` before its own comparison.
+    const SAFE_SINGLETON: &str = concat!(
+        "6080604052",
+        "600436106101dd57",
+        "60003560e01c",
+        "80636a7612021161010257", // GT pivot
+        "80636a7612021461030057", // execTransaction
+        "8063e75235b81461032057", // getThreshold
+        "5b600080fd",
+        "5b6000808451602086018a5af4", // DELEGATECALL operation
+        "00",
+    );
+
+    #[test]
+    fn entry_point_calling_validate_user_op_is_a_contract() {
+        assert_eq!(CodeShape::parse(ENTRY_POINT_V06), CodeShape::Contract);
+    }
+
+    #[test]
+    fn safe_singleton_dispatching_exec_transaction_is_a_smart_account() {
+        assert_eq!(CodeShape::parse(SAFE_SINGLETON), CodeShape::SmartAccount);
+    }
+
+    #[test]
+    fn contract_calling_exec_transaction_is_a_contract() {
+        // PUSH4 execTransaction, SHL 224, MSTORE, CALL
+        let code = "6080604052604051636a76120260e01b81526000806004836000895af100";
+        assert_eq!(CodeShape::parse(code), CodeShape::Contract);
+    }
+
+    fn slot_proxy(slot: &str) -> String {
+        format!("60806040527f{slot}54{DELEGATE_FORWARD}")
+    }
+
+    #[test]
+    fn empty_code_is_an_eoa() {
+        assert_eq!(CodeShape::parse("0x"), CodeShape::Empty);
+        assert_eq!(CodeShape::parse("0x").kind(), AddressKind::Eoa);
+        assert!(!CodeShape::Empty.is_durable());
+    }
+
+    #[test]
+    fn eip7702_designator_is_a_delegated_account() {
+        let shape = CodeShape::parse(&format!("0xef0100{TARGET}"));
+
+        assert_eq!(shape, CodeShape::Delegated(format!("0x{TARGET}")));
+        assert_eq!(shape.kind(), AddressKind::SmartAccount);
+        assert!(!shape.is_durable());
+    }
+
+    #[test]
+    fn eip1167_runtime_is_a_minimal_proxy() {
+        let code = format!("363d3d373d3d3d363d73{TARGET}5af43d82803e903d91602b57fd5bf3");
+        let shape = CodeShape::parse(&format!("0x{}", code.to_ascii_uppercase()));
+
+        assert_eq!(shape, CodeShape::MinimalProxy(format!("0x{TARGET}")));
+        assert_eq!(shape.kind(), AddressKind::Proxy);
+    }
+
+    #[test]
+    fn eip1967_proxy_reads_its_implementation_slot() {
+        assert_eq!(
+            CodeShape::parse(&slot_proxy(EIP1967_IMPLEMENTATION_SLOT)),
+            CodeShape::SlotProxy(EIP1967_IMPLEMENTATION_SLOT)
+        );
+        assert_eq!(
+            CodeShape::parse(&slot_proxy(EIP1967_BEACON_SLOT)),
+            CodeShape::BeaconProxy
+        );
+    }
+
+    #[test]
+    fn eip1822_proxy_reads_the_proxiable_slot() {
+        assert_eq!(
+            CodeShape::parse(&slot_proxy(EIP1822_PROXIABLE_SLOT)),
+            CodeShape::SlotProxy(EIP1822_PROXIABLE_SLOT)
+        );
+    }
+
+    #[test]
+    fn uups_implementation_is_a_contract() {
+        // Dispatches proxiableUUID() and reads the slot while upgrading.
+        let code = format!(
+            "60003560e01c806352d1902d1461004057{}",
+            &slot_proxy(EIP1967_IMPLEMENTATION_SLOT)
+        );
+        assert_eq!(CodeShape::parse(&code), CodeShape::Contract);
+    }
+
+    #[test]
+    fn slot_written_but_never_loaded_is_not_a_proxy() {
+        // PUSH32 slot, SSTORE, then a delegating fallback.
+        let code = format!("7f{EIP1967_IMPLEMENTATION_SLOT}55{DELEGATE_FORWARD}");
+        assert_eq!(CodeShape::parse(&code), CodeShape::Contract);
+    }
+
+    #[test]
+    fn safe_proxy_runtime_is_a_safe_proxy() {
+        let shape = CodeShape::parse(SAFE_PROXY);
+
+        assert_eq!(shape, CodeShape::SafeProxy);
+        assert_eq!(shape.kind(), AddressKind::SmartAccount);
+        assert!(shape.is_durable());
+    }
+
+    #[test]
+    fn erc4337_accounts_dispatching_validate_user_op_are_smart_accounts() {
+        for selector in VALIDATE_USER_OP_SELECTORS {
+            let code = format!("60003560e01c8063{selector}1461004057600080fd");
+            assert_eq!(CodeShape::parse(&code), CodeShape::SmartAccount);
+        }
+    }
+
+    #[test]
+    fn disassemble_keeps_push_data_apart_from_opcodes() {
+        // PUSH2 0xf4f4, STOP, then a PUSH4 cut short.
+        let ops = disassemble("61f4f40063aabb");
+
+        assert_eq!(ops, [(0x61, "f4f4"), (0x00, ""), (PUSH4, "aabb")]);
+        assert!(ops.iter().all(|(op, _)| *op != DELEGATECALL));
+    }
+
+    #[test]
+    fn disassemble_stops_at_non_hex() {
+        assert_eq!(disassemble("6001zz54"), [(PUSH1, "01")]);
+    }
+
+    #[test]
+    fn loads_slot_allows_stack_shuffling_before_sload() {
+        let slot = EIP1967_IMPLEMENTATION_SLOT;
+
+        assert!(loads_slot(&disassemble(&format!("7f{slot}54")), slot));
+        assert!(loads_slot(&disassemble(&format!("7f{slot}809154")), slot));
+        assert!(!loads_slot(&disassemble(&format!("7f{slot}5054")), slot));
+        assert!(!loads_slot(&disassemble(&format!("7f{slot}")), slot));
+    }
+
+    #[test]
+    fn word_address_reads_the_low_20_bytes() {
+        let word = format!("0x000000000000000000000000{TARGET}");
+
+        assert_eq!(word_address(&word), Some(format!("0x{TARGET}")));
+        assert_eq!(word_address("0x0"), None);
+    }
+}
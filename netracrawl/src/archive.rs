@@ -0,0 +1,349 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, BufWriter, Read, Write},
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use common::model::Chain;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::error::{Error, Result};
+
+const INDEX_FILE: &str = "index.jsonl";
+const OBJECTS_DIR: &str = "objects";
+
+/// Raw RPC payload kinds kept in a [`BlockArchive`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveKind {
+    /// `eth_getBlockByNumber` with full transactions.
+    Block,
+    /// `eth_getBlockReceipts`.
+    Receipts,
+    /// `trace_block`.
+    Traces,
+    /// `debug_traceBlockByNumber` with the `callTracer`.
+    CallTraces,
+}
+
+impl ArchiveKind {
+    pub const ALL: [ArchiveKind; 4] = [
+        ArchiveKind::Block,
+        ArchiveKind::Receipts,
+        ArchiveKind::Traces,
+        ArchiveKind::CallTraces,
+    ];
+}
+
+impl fmt::Display for ArchiveKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            ArchiveKind::Block => "block data",
+            ArchiveKind::Receipts => "receipts",
+            ArchiveKind::Traces => "traces",
+            ArchiveKind::CallTraces => "call traces",
+        };
+        write!(f, "{label}")
+    }
+}
+
+/// One line of the append-only index. An entry without `object` evicts the
+/// payload previously recorded for that block number.
+#[derive(Debug, Serialize, Deserialize)]
+struct IndexEntry {
+    kind: ArchiveKind,
+    number: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hash: Option<String>,
+    object: Option<String>,
+}
+
+/// One line of `index.jsonl`: the chain the archive holds, written once when
+/// it is created, or a payload entry.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum IndexLine {
+    Chain { chain: Chain },
+    Entry(IndexEntry),
+}
+
+#[derive(Debug, Default)]
+struct ArchiveIndex {
+    by_number: HashMap<(ArchiveKind, u64), (Option<String>, String)>,
+    by_hash: HashMap<(ArchiveKind, String), String>,
+}
+
+impl ArchiveIndex {
+    fn apply(&mut self, entry: IndexEntry) {
+        let key = (entry.kind, entry.number);
+
+        match entry.object {
+            Some(object) => {
+                if let Some(hash) = &entry.hash {
+                    self.by_hash
+                        .insert((entry.kind, hash.to_ascii_lowercase()), object.clone());
+                }
+                self.by_number.insert(key, (entry.hash, object));
+            }
+            None => {
+                self.by_number.remove(&key);
+            }
+        }
+    }
+}
+
+/// Local archive of raw blocks, receipts and traces.
+///
+/// Payloads are stored gzip-compressed under `objects/`, named by the SHA-256
+/// of their JSON, so identical payloads are written once. `index.jsonl` maps
+/// block numbers and hashes to objects; the latest entry for a number wins,
+/// so re-archiving after a reorg simply supersedes the orphaned payload.
+/// The index also records the chain its blocks belong to, so an archive is
+/// never read or filled as another chain's.
+#[derive(Debug)]
+pub struct BlockArchive {
+    root: PathBuf,
+    index: Mutex<ArchiveIndex>,
+}
+
+impl BlockArchive {
+    /// Opens the archive at `root` for blocks of `chain`, creating it when
+    /// missing. Fails when it already holds another chain's blocks.
+    pub fn open(root: impl Into<PathBuf>, chain: Chain) -> Result<Self> {
+        let root = root.into();
+        fs::create_dir_all(root.join(OBJECTS_DIR))?;
+
+        let mut index = ArchiveIndex::default();
+        let mut archived_chain = None;
+        let index_path = root.join(INDEX_FILE);
+
+        if index_path.exists() {
+            for line in BufReader::new(File::open(&index_path)?).lines() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+
+                let line: IndexLine = serde_json::from_str(&line)
+                    .map_err(|e| Error::Serde(e, format!("Invalid archive index entry: {line}")))?;
+                match line {
+                    IndexLine::Chain { chain } => archived_chain = Some(chain),
+                    IndexLine::Entry(entry) => index.apply(entry),
+                }
+            }
+        }
+
+        let archive = Self {
+            root,
+            index: Mutex::new(index),
+        };
+
+        match archived_chain {
+            Some(archived) if archived != chain => {
+                return Err(Error::ArchiveChainMismatch(
+                    archive.root.display().to_string(),
+                    archived,
+                    chain,
+                ));
+            }
+            Some(_) => {}
+            None => archive.append(&IndexLine::Chain { chain })?,
+        }
+
+        Ok(archive)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Payload of `kind` recorded for block `number`.
+    pub fn get(&self, kind: ArchiveKind, number: u64) -> Result<Option<Value>> {
+        let object = self
+            .lock()
+            .by_number
+            .get(&(kind, number))
+            .map(|(_, object)| object.clone());
+
+        object.map(|object| self.read_object(&object)).transpose()
+    }
+
+    /// Payload of `kind` recorded for the block with `hash`, including blocks
+    /// that were later reorganized out.
+    pub fn get_by_hash(&self, kind: ArchiveKind, hash: &str) -> Result<Option<Value>> {
+        let object = self
+            .lock()
+            .by_hash
+            .get(&(kind, hash.to_ascii_lowercase()))
+            .cloned();
+
+        object.map(|object| self.read_object(&object)).transpose()
+    }
+
+    /// Stores `value` as the `kind` payload of block `number`.
+    pub fn put(&self, kind: ArchiveKind, number: u64, value: &Value) -> Result<()> {
+        let bytes = serde_json::to_vec(value)?;
+        let object = format!("{:x}", Sha256::digest(&bytes));
+
+        let path = self.object_path(&object);
+        if !path.exists() {
+            write_compressed(&path, &bytes)?;
+        }
+
+        let mut index = self.lock();
+        let hash = payload_hash(kind, value).or_else(|| {
+            index
+                .by_number
+                .get(&(ArchiveKind::Block, number))
+                .and_then(|(hash, _)| hash.clone())
+        });
+
+        let entry = IndexEntry {
+            kind,
+            number,
+            hash,
+            object: Some(object),
+        };
+        self.append(&entry)?;
+        index.apply(entry);
+
+        Ok(())
+    }
+
+    /// Forgets every payload recorded for block `number`, e.g. after a reorg.
+    /// Objects stay reachable by hash.
+    pub fn evict(&self, number: u64) -> Result<()> {
+        let mut index = self.lock();
+
+        for kind in ArchiveKind::ALL {
+            if !index.by_number.contains_key(&(kind, number)) {
+                continue;
+            }
+
+            let entry = IndexEntry {
+                kind,
+                number,
+                hash: None,
+                object: None,
+            };
+            self.append(&entry)?;
+            index.apply(entry);
+        }
+
+        Ok(())
+    }
+
+    /// Lowest and highest archived block numbers.
+    pub fn block_range(&self) -> Option<RangeInclusive<u64>> {
+        let index = self.lock();
+        let mut numbers = index
+            .by_number
+            .keys()
+            .filter(|(kind, _)| *kind == ArchiveKind::Block)
+            .map(|(_, number)| *number);
+
+        let first = numbers.next()?;
+        let (min, max) = numbers.fold((first, first), |(min, max), number| {
+            (min.min(number), max.max(number))
+        });
+
+        Some(min..=max)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ArchiveIndex> {
+        self.index
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn append<T>(&self, entry: &T) -> Result<()>
+    where
+        T: Serialize,
+    {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.root.join(INDEX_FILE))?;
+
+        writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        Ok(())
+    }
+
+    fn object_path(&self, object: &str) -> PathBuf {
+        let (prefix, rest) = object.split_at(2);
+        self.root
+            .join(OBJECTS_DIR)
+            .join(prefix)
+            .join(format!("{rest}.json.gz"))
+    }
+
+    fn read_object(&self, object: &str) -> Result<Value> {
+        let mut bytes = vec![];
+        GzDecoder::new(File::open(self.object_path(object))?).read_to_end(&mut bytes)?;
+
+        serde_json::from_slice(&bytes)
+            .map_err(|e| Error::Serde(e, format!("Corrupt archive object {object}")))
+    }
+}
+
+fn write_compressed(path: &Path, bytes: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let tmp_path = path.with_extension("tmp");
+    let mut encoder = GzEncoder::new(
+        BufWriter::new(File::create(&tmp_path)?),
+        Compression::default(),
+    );
+    encoder.write_all(bytes)?;
+    encoder.finish()?.flush()?;
+
+    fs::rename(tmp_path, path)?;
+    Ok(())
+}
+
+/// Block hash carried inside a payload, when its shape has one.
+fn payload_hash(kind: ArchiveKind, value: &Value) -> Option<String> {
+    let hash = match kind {
+        ArchiveKind::Block => value.get("hash"),
+        ArchiveKind::Receipts | ArchiveKind::Traces => value.get(0)?.get("blockHash"),
+        ArchiveKind::CallTraces => None,
+    };
+
+    hash.and_then(Value::as_str).map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn archive_refuses_another_chain() {
+        let root = std::env::temp_dir().join(format!("netra-archive-{}", std::process::id()));
+        let block = serde_json::json!({ "number": "0x1", "hash": "0xabc" });
+
+        let archive = BlockArchive::open(&root, Chain::Bsc).expect("new archive");
+        archive.put(ArchiveKind::Block, 1, &block).expect("put");
+        drop(archive);
+
+        let reopened = BlockArchive::open(&root, Chain::Bsc).expect("same chain");
+        assert_eq!(
+            reopened.get(ArchiveKind::Block, 1).expect("get"),
+            Some(block)
+        );
+        assert!(matches!(
+            BlockArchive::open(&root, Chain::Ethereum),
+            Err(Error::ArchiveChainMismatch(_, Chain::Bsc, Chain::Ethereum))
+        ));
+
+        fs::remove_dir_all(root).expect("cleanup");
+    }
+}
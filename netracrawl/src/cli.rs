@@ -1,4 +1,6 @@
 use clap::{Parser, Subcommand};
+use common::model::Chain;
+use netracrawl::constants::{RPC_BATCH_SIZE, RPC_CONCURRENCY};
 
 #[derive(Parser)]
 #[command(name = "netracrawl")]
@@ -36,4 +38,179 @@ pub enum Command {
         )]
         rpc: String,
     },
+
+    /// Fetch every block in an inclusive range using batched requests
+    FetchRange {
+        #[arg(help = "First block number to scan")]
+        start_block: u64,
+
+        #[arg(help = "Last block number to scan (inclusive)")]
+        end_block: u64,
+
+        #[arg(
+            long,
+            default_value = "https://rpc.ankr.com/eth/6570a5941c65a2a7deffce485080569aa0bf85c6dc4b0f1dc95002557569af40",
+            help = "RPC endpoint to use",
+            env = "ETH_RPC_URL"
+        )]
+        rpc: String,
+
+        #[arg(long, default_value_t = RPC_BATCH_SIZE, help = "Blocks per JSON-RPC batch")]
+        batch_size: usize,
+
+        #[arg(long, default_value_t = RPC_CONCURRENCY, help = "Batches in flight at once")]
+        concurrency: usize,
+    },
+
+    /// Store raw blocks (and optionally receipts and traces) of a range in the local archive
+    Archive {
+        #[arg(help = "First block number to archive")]
+        start_block: u64,
+
+        #[arg(help = "Last block number to archive (inclusive)")]
+        end_block: u64,
+
+        #[arg(
+            long,
+            default_value = "https://rpc.ankr.com/eth/6570a5941c65a2a7deffce485080569aa0bf85c6dc4b0f1dc95002557569af40",
+            help = "RPC endpoint to use",
+            env = "ETH_RPC_URL"
+        )]
+        rpc: String,
+
+        #[arg(
+            long,
+            default_value = "netracrawl/data/archive",
+            env = "NETRA_ARCHIVE_DIR",
+            help = "Archive directory; chains other than Ethereum add their name to it"
+        )]
+        dir: String,
+
+        #[arg(
+            long,
+            default_value_t = Chain::Ethereum,
+            help = "Chain to archive (ethereum, bsc, polygon, arbitrum, base)"
+        )]
+        chain: Chain,
+
+        #[arg(long, help = "Also archive block receipts (needed for ERC-20 flows)")]
+        receipts: bool,
+
+        #[arg(
+            long,
+            help = "Also archive internal call traces (trace_block or debug_traceBlockByNumber)"
+        )]
+        traces: bool,
+
+        #[arg(long, default_value_t = RPC_BATCH_SIZE, help = "Blocks per JSON-RPC batch")]
+        batch_size: usize,
+
+        #[arg(long, default_value_t = RPC_CONCURRENCY, help = "Batches in flight at once")]
+        concurrency: usize,
+    },
+
+    /// Serve recorded fixtures as a local JSON-RPC node and Etherscan API
+    MockServe {
+        #[arg(
+            long,
+            default_value = "netracrawl/data/fixtures",
+            env = "NETRA_FIXTURES_DIR",
+            help = "Fixture directory written by `record`"
+        )]
+        fixtures: String,
+
+        #[arg(long, default_value = "127.0.0.1:8545", help = "Address to listen on")]
+        addr: String,
+
+        #[arg(
+            long,
+            default_value_t = Chain::Ethereum,
+            help = "Chain the fixtures belong to (ethereum, bsc, polygon, arbitrum, base)"
+        )]
+        chain: Chain,
+
+        #[arg(long, help = "Only accept Etherscan requests carrying this API key")]
+        api_key: Option<String>,
+
+        #[arg(long, help = "Answer every n-th request with a rate-limit response")]
+        rate_limit_every: Option<u64>,
+
+        #[arg(long, help = "Fail every n-th request (HTTP 500 / Etherscan NOTOK)")]
+        fail_every: Option<u64>,
+
+        #[arg(
+            long,
+            default_value_t = 0,
+            help = "Delay added to every response, in milliseconds"
+        )]
+        latency_ms: u64,
+    },
+
+    /// Capture live RPC and Etherscan responses into a fixture directory
+    Record {
+        #[arg(
+            long,
+            default_value = "netracrawl/data/fixtures",
+            env = "NETRA_FIXTURES_DIR",
+            help = "Fixture directory to write"
+        )]
+        out: String,
+
+        #[arg(
+            long,
+            default_value = "https://rpc.ankr.com/eth/6570a5941c65a2a7deffce485080569aa0bf85c6dc4b0f1dc95002557569af40",
+            help = "RPC endpoint to use",
+            env = "ETH_RPC_URL"
+        )]
+        rpc: String,
+
+        #[arg(long, help = "First block to record")]
+        start_block: Option<u64>,
+
+        #[arg(
+            long,
+            help = "Last block to record (inclusive); defaults to --start-block for blocks and the latest block for wallet history"
+        )]
+        end_block: Option<u64>,
+
+        #[arg(long, help = "Also record block receipts")]
+        receipts: bool,
+
+        #[arg(
+            long,
+            help = "Also record internal call traces (trace_block or debug_traceBlockByNumber)"
+        )]
+        traces: bool,
+
+        #[arg(long = "tx", help = "Transaction hash to record (repeatable)")]
+        transactions: Vec<String>,
+
+        #[arg(
+            long = "wallet",
+            help = "Wallet whose Etherscan history to record (repeatable)"
+        )]
+        wallets: Vec<String>,
+
+        #[arg(
+            long,
+            env = "ETHERSCAN_API_KEY",
+            help = "Etherscan API key, needed with --wallet"
+        )]
+        etherscan_key: Option<String>,
+
+        #[arg(
+            long,
+            default_value_t = Chain::Ethereum,
+            env = "ETHERSCAN_CHAIN",
+            help = "Chain to query (ethereum, bsc, polygon, arbitrum, base)"
+        )]
+        chain: Chain,
+
+        #[arg(
+            long,
+            env = "ETHERSCAN_BASE_URL",
+            help = "Override the explorer API base URL"
+        )]
+        explorer_url: Option<String>,
+    },
 }
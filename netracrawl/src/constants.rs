@@ -0,0 +1,37 @@
+pub const ETHERSCAN_MAX_RETRIES: u32 = 5;
+pub const ETHERSCAN_BACKOFF_MS: u64 = 1_000;
+pub const ETHERSCAN_MAX_RESULT_WINDOW: usize = 10_000;
+pub const ETHERSCAN_PAGE_SIZE: usize = 1_000;
+pub const ETHERSCAN_BALANCE_BATCH: usize = 20;
+
+pub const RPC_BATCH_SIZE: usize = 50;
+pub const RPC_CONCURRENCY: usize = 4;
+pub const RPC_TIMEOUT_SECS: u64 = 10;
+pub const RPC_MAX_RETRIES: u32 = 3;
+pub const RPC_RETRY_BASE_MS: u64 = 250;
+pub const RPC_FAILURE_THRESHOLD: u32 = 3;
+pub const RPC_COOLDOWN_SECS: u64 = 30;
+pub const RPC_LOG_RANGE: u64 = 10_000;
+
+pub const ARCHIVE_CHUNK_SIZE: u64 = 1_000;
+
+pub const HISTORY_CONFIRMATIONS: u64 = 12;
+
+pub const WS_IDLE_TIMEOUT_SECS: u64 = 60;
+pub const WS_MAX_RECONNECT_SECS: u64 = 60;
+
+pub const MEMPOOL_POLL_MS: u64 = 1_000;
+
+pub const MOCK_MAX_REQUEST_BYTES: usize = 16 * 1024 * 1024;
+
+pub const BSC_RPC_URL: &str = "https://bsc-dataseed.bnbchain.org";
+pub const POLYGON_RPC_URL: &str = "https://polygon-rpc.com";
+pub const ARBITRUM_RPC_URL: &str = "https://arb1.arbitrum.io/rpc";
+pub const BASE_RPC_URL: &str = "https://mainnet.base.org";
+
+pub const ETHERSCAN_API_URL: &str = "https://api.etherscan.io/api";
+pub const ETHERSCAN_V2_API_URL: &str = "https://api.etherscan.io/v2/api";
+pub const BSCSCAN_API_URL: &str = "https://api.bscscan.com/api";
+pub const POLYGONSCAN_API_URL: &str = "https://api.polygonscan.com/api";
+pub const ARBISCAN_API_URL: &str = "https://api.arbiscan.io/api";
+pub const BASESCAN_API_URL: &str = "https://api.basescan.org/api";
@@ -1,4 +1,16 @@
-use netracrawl::{error::Result, ethereum::EthereumClient};
+use std::ops::RangeInclusive;
+
+use common::model::AssetKind;
+use futures::{stream, StreamExt, TryStreamExt};
+use netracrawl::{
+    archive::ArchiveKind,
+    constants::{ARCHIVE_CHUNK_SIZE, ETHERSCAN_PAGE_SIZE, RPC_CONCURRENCY},
+    error::{Error, Result},
+    ethereum::EthereumClient,
+    etherscan::{BlockRange, EtherscanClient},
+    source::FixtureSource,
+};
+use serde_json::{json, Value};
 
 pub async fn scan_latest(rpc: &str) -> Result<()> {
     let client = EthereumClient::new(rpc);
@@ -16,3 +28,176 @@ pub async fn scan_block(block_number: u64, rpc: &str) -> Result<()> {
     println!("Block data: {block:#?}");
     Ok(())
 }
+
+pub async fn fetch_range(
+    start_block: u64,
+    end_block: u64,
+    rpc: &str,
+    batch_size: usize,
+    concurrency: usize,
+) -> Result<()> {
+    let client = EthereumClient::new(rpc)
+        .with_batch_size(batch_size)
+        .with_concurrency(concurrency);
+
+    let blocks = client.get_blocks_in_range(start_block..=end_block).await?;
+
+    for block in &blocks {
+        println!(
+            "Block {} | timestamp: {} | tx_count: {}",
+            block.number,
+            block.timestamp,
+            block.transactions.len()
+        );
+    }
+
+    println!("📦 Fetched {} blocks", blocks.len());
+    Ok(())
+}
+
+pub async fn archive_range(
+    client: &EthereumClient,
+    range: RangeInclusive<u64>,
+    receipts: bool,
+    traces: bool,
+) -> Result<()> {
+    let (start, end) = (*range.start(), *range.end());
+    let mut archived = 0;
+
+    for chunk_start in range.step_by(ARCHIVE_CHUNK_SIZE as usize) {
+        let chunk = chunk_start..=end.min(chunk_start + ARCHIVE_CHUNK_SIZE - 1);
+        archived += client.get_blocks_in_range(chunk.clone()).await?.len();
+
+        if receipts {
+            stream::iter(chunk.clone())
+                .map(|number| client.get_block_receipts(number))
+                .buffer_unordered(RPC_CONCURRENCY)
+                .try_collect::<Vec<_>>()
+                .await?;
+        }
+
+        if traces {
+            stream::iter(chunk.clone())
+                .map(|number| client.get_internal_transfers(number))
+                .buffer_unordered(RPC_CONCURRENCY)
+                .try_collect::<Vec<_>>()
+                .await?;
+        }
+
+        println!("🗄️  Archived blocks {}..={}", chunk.start(), chunk.end());
+    }
+
+    if let Some(archive) = client.archive() {
+        println!(
+            "📦 Archived {archived} blocks ({start}..={end}) in {}",
+            archive.root().display()
+        );
+    }
+
+    Ok(())
+}
+
+/// Records the raw block payloads of `range` into `fixtures`.
+pub async fn record_blocks(
+    client: &EthereumClient,
+    fixtures: &FixtureSource,
+    range: RangeInclusive<u64>,
+    receipts: bool,
+    traces: bool,
+) -> Result<()> {
+    let (start, end) = (*range.start(), *range.end());
+
+    stream::iter(range)
+        .map(|number| record_block(client, fixtures, number, receipts, traces))
+        .buffer_unordered(RPC_CONCURRENCY)
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    println!(
+        "📼 Recorded blocks {start}..={end} into {}",
+        fixtures.root().display()
+    );
+    Ok(())
+}
+
+async fn record_block(
+    client: &EthereumClient,
+    fixtures: &FixtureSource,
+    number: u64,
+    receipts: bool,
+    traces: bool,
+) -> Result<()> {
+    let tag = format!("0x{number:x}");
+
+    let block: Value = client
+        .call("eth_getBlockByNumber", json!([tag, true]))
+        .await?;
+    if block.is_null() {
+        return Err(Error::EmptyBlockResult);
+    }
+    fixtures.write_block(ArchiveKind::Block, number, &block)?;
+
+    if receipts {
+        let receipts: Value = client.call("eth_getBlockReceipts", json!([tag])).await?;
+        fixtures.write_block(ArchiveKind::Receipts, number, &receipts)?;
+    }
+
+    if traces {
+        match client.call::<Value>("trace_block", json!([tag])).await {
+            Ok(traces) => fixtures.write_block(ArchiveKind::Traces, number, &traces)?,
+            Err(Error::Rpc(..)) => {
+                let traces: Value = client
+                    .call(
+                        "debug_traceBlockByNumber",
+                        json!([tag, { "tracer": "callTracer" }]),
+                    )
+                    .await?;
+                fixtures.write_block(ArchiveKind::CallTraces, number, &traces)?;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn record_transactions(
+    client: &EthereumClient,
+    fixtures: &FixtureSource,
+    hashes: &[String],
+) -> Result<()> {
+    for hash in hashes {
+        let tx: Value = client
+            .call("eth_getTransactionByHash", json!([hash]))
+            .await?;
+        if tx.is_null() {
+            return Err(Error::TxNotFound(hash.clone()));
+        }
+
+        fixtures.write_transaction(hash, &tx)?;
+        println!("📼 Recorded transaction {hash}");
+    }
+
+    Ok(())
+}
+
+/// Records every Etherscan account action of each wallet in `range`.
+pub async fn record_wallets(
+    client: &EtherscanClient,
+    fixtures: &FixtureSource,
+    wallets: &[String],
+    range: BlockRange,
+) -> Result<()> {
+    for wallet in wallets {
+        for kind in AssetKind::ALL {
+            let fetched = client
+                .fetch_all_by_kind(kind, wallet, range, ETHERSCAN_PAGE_SIZE)
+                .await?;
+            fixtures.write_account(wallet, kind, &fetched.items)?;
+        }
+
+        println!("📼 Recorded Etherscan history of {wallet}");
+    }
+
+    Ok(())
+}
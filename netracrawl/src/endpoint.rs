@@ -0,0 +1,70 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::constants::{RPC_COOLDOWN_SECS, RPC_FAILURE_THRESHOLD};
+
+#[derive(Debug, Default)]
+struct Health {
+    consecutive_failures: u32,
+    cooldown_until: Option<Instant>,
+}
+
+/// JSON-RPC provider URL with its recent health.
+///
+/// After `RPC_FAILURE_THRESHOLD` consecutive failures the endpoint is put in
+/// cooldown and only tried once every healthy provider has failed.
+#[derive(Debug)]
+pub struct RpcEndpoint {
+    pub url: String,
+    health: Mutex<Health>,
+}
+
+impl RpcEndpoint {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            health: Mutex::new(Health::default()),
+        }
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        let health = self.health.lock().unwrap_or_else(|err| err.into_inner());
+
+        health
+            .cooldown_until
+            .is_none_or(|until| Instant::now() >= until)
+    }
+
+    pub fn mark_success(&self) {
+        let mut health = self.health.lock().unwrap_or_else(|err| err.into_inner());
+        *health = Health::default();
+    }
+
+    pub fn mark_failure(&self) {
+        let mut health = self.health.lock().unwrap_or_else(|err| err.into_inner());
+        health.consecutive_failures += 1;
+
+        if health.consecutive_failures >= RPC_FAILURE_THRESHOLD {
+            health.cooldown_until = Some(Instant::now() + Duration::from_secs(RPC_COOLDOWN_SECS));
+        }
+    }
+}
+
+/// Adds up to 50% random jitter to `delay` so retrying clients spread out.
+pub fn with_jitter(delay: Duration) -> Duration {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.subsec_nanos())
+        .unwrap_or(0);
+
+    let spread = delay.as_millis() as u64 / 2;
+    let jitter = if spread == 0 {
+        0
+    } else {
+        nanos as u64 % spread
+    };
+
+    delay + Duration::from_millis(jitter)
+}
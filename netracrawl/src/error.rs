@@ -1,15 +1,34 @@
+use common::model::Chain;
+
+use crate::constants::ETHERSCAN_MAX_RESULT_WINDOW;
+
 pub type Result<T> = core::result::Result<T, Error>;
 
 #[derive(Debug)]
 pub enum Error {
     Common(common::error::Error),
+    Io(std::io::Error),
     Rpc(i64, String),
     Reqwest(reqwest::Error),
+    WebSocket(Box<tokio_tungstenite::tungstenite::Error>),
     Serde(serde_json::Error, String),
     ParseInt(std::num::ParseIntError),
     EmptyBlockNumber,
     EmptyBlockResult,
     TxNotFound(String),
+    RateLimited(String),
+    InvalidApiKey(String),
+    Etherscan(String),
+    NoEndpoint,
+    QuorumNotReached(u64, usize, usize),
+    InvalidQuorum(usize, usize),
+    InvalidOffset(usize),
+    ChainMismatch(Chain, u64),
+    NotArchived(String, u64),
+    ArchiveChainMismatch(String, Chain, Chain),
+    Offline(String),
+    MissingFixture(String),
+    UnsupportedSource(String, String),
 }
 
 impl std::error::Error for Error {}
@@ -18,8 +37,10 @@ impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Common(err) => write!(f, "{err}"),
+            Self::Io(err) => write!(f, "IO error: {err}"),
             Self::Rpc(code, msg) => write!(f, " RPC error (code {code}): {msg}"),
             Self::Reqwest(err) => write!(f, "Request error: {err}"),
+            Self::WebSocket(err) => write!(f, "WebSocket error: {err}"),
             Self::Serde(err, context) => write!(f, "{context}: {err}"),
             Self::ParseInt(err) => write!(f, "Parsing int error: {err}"),
             Self::EmptyBlockNumber => write!(
@@ -28,6 +49,38 @@ impl std::fmt::Display for Error {
             ),
             Self::EmptyBlockResult => write!(f, "Failed to get block data"),
             Self::TxNotFound(hash) => write!(f, "Transaction not found: {hash}"),
+            Self::RateLimited(msg) => write!(f, "Etherscan rate limit exceeded: {msg}"),
+            Self::InvalidApiKey(msg) => write!(f, "Invalid Etherscan API key: {msg}"),
+            Self::Etherscan(msg) => write!(f, "Etherscan error: {msg}"),
+            Self::NoEndpoint => write!(f, "No RPC endpoint configured"),
+            Self::QuorumNotReached(block, agreeing, required) => write!(
+                f,
+                "Block {block} hash confirmed by {agreeing} provider(s), quorum requires {required}"
+            ),
+            Self::ChainMismatch(chain, id) => write!(
+                f,
+                "RPC endpoint serves chain id {id}, not {chain} ({})",
+                chain.chain_id()
+            ),
+            Self::InvalidQuorum(quorum, endpoints) => write!(
+                f,
+                "Quorum {quorum} must be between 1 and the {endpoints} configured RPC endpoint(s)"
+            ),
+            Self::InvalidOffset(offset) => write!(
+                f,
+                "Page size {offset} must be between 1 and {ETHERSCAN_MAX_RESULT_WINDOW}"
+            ),
+            Self::NotArchived(kind, block) => {
+                write!(f, "Archive has no {kind} for block {block} (offline mode)")
+            }
+            Self::ArchiveChainMismatch(dir, archived, chain) => {
+                write!(f, "Archive {dir} holds {archived} blocks, not {chain}")
+            }
+            Self::Offline(method) => write!(f, "Cannot call {method} in offline mode"),
+            Self::MissingFixture(path) => write!(f, "Missing fixture data: {path}"),
+            Self::UnsupportedSource(source, what) => {
+                write!(f, "Source `{source}` cannot provide {what}")
+            }
         }
     }
 }
@@ -38,12 +91,24 @@ impl From<common::error::Error> for Error {
     }
 }
 
+impl From<std::io::Error> for Error {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
 impl From<reqwest::Error> for Error {
     fn from(value: reqwest::Error) -> Self {
         Self::Reqwest(value)
     }
 }
 
+impl From<tokio_tungstenite::tungstenite::Error> for Error {
+    fn from(value: tokio_tungstenite::tungstenite::Error) -> Self {
+        Self::WebSocket(Box::new(value))
+    }
+}
+
 impl From<serde_json::Error> for Error {
     fn from(value: serde_json::Error) -> Self {
         Self::Serde(value, String::from(""))
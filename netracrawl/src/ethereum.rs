@@ -1,120 +1,1085 @@
-use common::model::{BlockEthereum, TxEthereum};
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    ops::RangeInclusive,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use common::model::{
+    BlockEthereum, Chain, InternalTransfer, LogEthereum, ReceiptEthereum, TokenTransfer,
+    TraceEthereum, TxEthereum, TxTraceEthereum, Wei, TRANSFER_EVENT_TOPIC,
+};
+use futures::{future::join_all, stream, StreamExt, TryStreamExt};
 use reqwest::Client;
+use serde::de::DeserializeOwned;
 use serde_json::{from_value, json, Value};
 
-use crate::error::{Error, Result};
+use tokio::time::sleep;
+
+use crate::{
+    archive::{ArchiveKind, BlockArchive},
+    constants::{
+        RPC_BATCH_SIZE, RPC_CONCURRENCY, RPC_LOG_RANGE, RPC_MAX_RETRIES, RPC_RETRY_BASE_MS,
+        RPC_TIMEOUT_SECS,
+    },
+    endpoint::{with_jitter, RpcEndpoint},
+    error::{Error, Result},
+    etherscan::Closest,
+};
+
+/// `keccak256("symbol()")[..4]`
+const SYMBOL_SELECTOR: &str = "0x95d89b41";
+
+/// `keccak256("balanceOf(address)")[..4]`
+const BALANCE_OF_SELECTOR: &str = "0x70a08231";
 
 pub struct EthereumClient {
     pub client: Client,
-    pub url: String,
+    pub endpoints: Vec<RpcEndpoint>,
+    batch_size: usize,
+    concurrency: usize,
+    max_retries: u32,
+    quorum: usize,
+    archive: Option<BlockArchive>,
+    offline: bool,
+    chain: Chain,
+    next_id: AtomicU64,
 }
 
 impl EthereumClient {
     pub fn new(url: impl Into<String>) -> Self {
+        Self::from_endpoints([url.into()])
+    }
+
+    /// Builds a client that fails over across `urls` in the given order of
+    /// preference.
+    pub fn from_endpoints<I>(urls: I) -> Self
+    where
+        I: IntoIterator<Item = String>,
+    {
         EthereumClient {
-            client: Client::new(),
-            url: url.into(),
+            client: build_client(Duration::from_secs(RPC_TIMEOUT_SECS)),
+            endpoints: urls.into_iter().map(RpcEndpoint::new).collect(),
+            batch_size: RPC_BATCH_SIZE,
+            concurrency: RPC_CONCURRENCY,
+            max_retries: RPC_MAX_RETRIES,
+            quorum: 1,
+            archive: None,
+            offline: false,
+            chain: Chain::Ethereum,
+            next_id: AtomicU64::new(1),
         }
     }
 
-    pub async fn get_latest_block_number(&self) -> Result<u64> {
-        let response = self
-            .client
-            .post(&self.url)
-            .json(&json!({
-                "jsonrpc": "2.0",
-                "method": "eth_blockNumber",
-                "params": [],
-                "id": 1
-            }))
-            .send()
-            .await?
-            .json::<Value>()
-            .await?;
+    /// Chain the endpoints serve, used to label what the client reads.
+    pub fn with_chain(mut self, chain: Chain) -> Self {
+        self.chain = chain;
+        self
+    }
+
+    pub fn chain(&self) -> Chain {
+        self.chain
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client = build_client(timeout);
+        self
+    }
 
-        if let Some(error) = response.get("error") {
-            let code = error.get("code").and_then(Value::as_i64).unwrap_or(0);
-            let message = error
-                .get("message")
-                .and_then(Value::as_str)
-                .unwrap_or("Unknown error");
-            return Err(Error::Rpc(code, message.to_string()));
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries.max(1);
+        self
+    }
+
+    /// Requires `quorum` providers to agree on a block hash in
+    /// [`EthereumClient::verify_block_hash`]. `1` disables the cross-check.
+    /// Fails unless `quorum` is between 1 and the number of endpoints, since
+    /// no block could ever be confirmed otherwise.
+    pub fn with_quorum(mut self, quorum: usize) -> Result<Self> {
+        if quorum == 0 || quorum > self.endpoints.len() {
+            return Err(Error::InvalidQuorum(quorum, self.endpoints.len()));
         }
+        self.quorum = quorum;
+        Ok(self)
+    }
+
+    pub fn quorum(&self) -> usize {
+        self.quorum
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
 
-        let hex = response
-            .get("result")
-            .ok_or(Error::EmptyBlockNumber)?
-            .as_str()
-            .ok_or(Error::EmptyBlockNumber)?;
+    /// Reads blocks, receipts and traces from `archive` before asking the
+    /// node, and archives whatever has to be fetched.
+    pub fn with_archive(mut self, archive: BlockArchive) -> Self {
+        self.archive = Some(archive);
+        self
+    }
+
+    /// Serves everything from the archive and never touches the network. The
+    /// latest block is the highest archived one.
+    pub fn offline(mut self) -> Self {
+        self.offline = true;
+        self
+    }
+
+    pub fn archive(&self) -> Option<&BlockArchive> {
+        self.archive.as_ref()
+    }
+
+    /// Drops archived payloads of a block that was reorganized out, so the
+    /// canonical one is fetched next time.
+    pub fn evict_archived(&self, number: u64) -> Result<()> {
+        match &self.archive {
+            Some(archive) => archive.evict(number),
+            None => Ok(()),
+        }
+    }
+
+    pub async fn get_latest_block_number(&self) -> Result<u64> {
+        if self.offline {
+            return self
+                .archive
+                .as_ref()
+                .and_then(BlockArchive::block_range)
+                .map(|range| *range.end())
+                .ok_or(Error::EmptyBlockNumber);
+        }
+
+        let hex: Option<String> = self.call("eth_blockNumber", json!([])).await?;
+        let hex = hex.ok_or(Error::EmptyBlockNumber)?;
 
         let block_number = u64::from_str_radix(hex.trim_start_matches("0x"), 16)?;
 
         Ok(block_number)
     }
 
+    /// Fails unless the node's `eth_chainId` is that of the chain this
+    /// client was built for, so blocks of one chain are never read as
+    /// another's.
+    pub async fn check_chain(&self) -> Result<()> {
+        let hex: String = self.call("eth_chainId", json!([])).await?;
+        let id = u64::from_str_radix(hex.trim_start_matches("0x"), 16)?;
+
+        if id != self.chain.chain_id() {
+            return Err(Error::ChainMismatch(self.chain, id));
+        }
+
+        Ok(())
+    }
+
     pub async fn get_block_by_number(&self, number: u64) -> Result<BlockEthereum> {
         let hex_block = format!("0x{number:x}");
 
-        let response = self
-            .client
-            .post(&self.url)
-            .json(&json!({
-                "jsonrpc": "2.0",
-                "method": "eth_getBlockByNumber",
-                "params": [hex_block, true],
-                "id": 1
-            }))
-            .send()
-            .await?
-            .json::<Value>()
+        let value = self
+            .archived(ArchiveKind::Block, number, || {
+                self.call("eth_getBlockByNumber", json!([hex_block, true]))
+            })
             .await?;
 
-        if let Some(error) = response.get("error") {
-            let code = error.get("code").and_then(Value::as_i64).unwrap_or(0);
-            let message = error
-                .get("message")
-                .and_then(Value::as_str)
-                .unwrap_or("Unknown error");
-            return Err(Error::Rpc(code, message.to_string()));
+        parse_block(value, &hex_block)
+    }
+
+    /// Canonical hash of block `number`, without fetching its transactions.
+    pub async fn get_block_hash(&self, number: u64) -> Result<String> {
+        if self.offline {
+            return Ok(self.get_block_by_number(number).await?.hash);
         }
 
-        let value = response.get("result").ok_or(Error::EmptyBlockResult)?;
+        let block: Value = self
+            .call(
+                "eth_getBlockByNumber",
+                json!([format!("0x{number:x}"), false]),
+            )
+            .await?;
 
-        from_value(value.clone())
-            .map_err(|e| Error::Serde(e, format!("Failed to parse block: {hex_block}")))
+        block
+            .get("hash")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or(Error::EmptyBlockResult)
     }
 
     pub async fn get_transaction_by_hash(&self, hash: &str) -> Result<TxEthereum> {
+        let tx: Value = self.call("eth_getTransactionByHash", json!([hash])).await?;
+
+        if tx.is_null() {
+            return Err(Error::TxNotFound(hash.to_string()));
+        }
+
+        from_value(tx).map_err(|e| Error::Serde(e, format!("Failed to parse transaction: {hash}")))
+    }
+
+    /// Fetches `hashes` in batched requests, like
+    /// [`EthereumClient::get_blocks_in_range`]. Transactions the node no
+    /// longer knows about (dropped or replaced) come back as `None`.
+    pub async fn get_transactions_by_hash(
+        &self,
+        hashes: &[String],
+    ) -> Result<Vec<Option<TxEthereum>>> {
+        let requests: Vec<_> = hashes
+            .chunks(self.batch_size)
+            .map(|chunk| self.get_transaction_batch(chunk))
+            .collect();
+
+        let batches: Vec<Vec<Option<TxEthereum>>> = stream::iter(requests)
+            .buffered(self.concurrency)
+            .try_collect()
+            .await?;
+
+        Ok(batches.into_iter().flatten().collect())
+    }
+
+    async fn get_transaction_batch(&self, hashes: &[String]) -> Result<Vec<Option<TxEthereum>>> {
+        let calls = hashes
+            .iter()
+            .map(|hash| ("eth_getTransactionByHash", json!([hash])))
+            .collect::<Vec<_>>();
+
+        self.batch_call(&calls)
+            .await?
+            .into_iter()
+            .zip(hashes)
+            .map(|(value, hash)| match value? {
+                Value::Null => Ok(None),
+                tx => from_value(tx)
+                    .map(Some)
+                    .map_err(|e| Error::Serde(e, format!("Failed to parse transaction: {hash}"))),
+            })
+            .collect()
+    }
+
+    /// Installs a filter on the node that collects hashes of transactions
+    /// entering its mempool.
+    pub async fn new_pending_transaction_filter(&self) -> Result<String> {
+        self.call("eth_newPendingTransactionFilter", json!([]))
+            .await
+    }
+
+    /// Returns the pending transaction hashes seen by `filter_id` since the
+    /// last poll.
+    pub async fn get_filter_changes(&self, filter_id: &str) -> Result<Vec<String>> {
+        let hashes: Option<Vec<String>> = self
+            .call("eth_getFilterChanges", json!([filter_id]))
+            .await?;
+
+        Ok(hashes.unwrap_or_default())
+    }
+
+    pub async fn get_block_receipts(&self, number: u64) -> Result<Vec<ReceiptEthereum>> {
+        let hex_block = format!("0x{number:x}");
+
+        let receipts = self
+            .archived(ArchiveKind::Receipts, number, || {
+                self.call("eth_getBlockReceipts", json!([hex_block]))
+            })
+            .await?;
+
+        let receipts: Option<Vec<ReceiptEthereum>> = from_value(receipts)
+            .map_err(|e| Error::Serde(e, format!("Failed to parse receipts of {hex_block}")))?;
+
+        receipts.ok_or(Error::EmptyBlockResult)
+    }
+
+    pub async fn get_logs(
+        &self,
+        range: RangeInclusive<u64>,
+        address: Option<&str>,
+        topics: &[Option<&str>],
+    ) -> Result<Vec<LogEthereum>> {
+        let mut filter = json!({
+            "fromBlock": format!("0x{:x}", range.start()),
+            "toBlock": format!("0x{:x}", range.end()),
+            "topics": topics,
+        });
+
+        if let Some(address) = address {
+            filter["address"] = json!(address);
+        }
+
+        self.call("eth_getLogs", json!([filter])).await
+    }
+
+    /// Decodes the ERC-20 transfers of a block from its receipts, falling back
+    /// to `eth_getLogs` when the node does not serve `eth_getBlockReceipts`.
+    pub async fn get_token_transfers(&self, number: u64) -> Result<Vec<TokenTransfer>> {
+        let logs = match self.get_block_receipts(number).await {
+            Ok(receipts) => return Ok(TokenTransfer::from_receipts(&receipts)),
+            Err(Error::Rpc(..)) => {
+                self.get_logs(number..=number, None, &[Some(TRANSFER_EVENT_TOPIC)])
+                    .await?
+            }
+            Err(err) => return Err(err),
+        };
+
+        Ok(logs.iter().filter_map(TokenTransfer::from_log).collect())
+    }
+
+    /// Decodes ERC-20 transfers across `range`, optionally for one token.
+    pub async fn get_token_transfers_in_range(
+        &self,
+        range: RangeInclusive<u64>,
+        contract: Option<&str>,
+    ) -> Result<Vec<TokenTransfer>> {
+        let logs = self
+            .get_logs(range, contract, &[Some(TRANSFER_EVENT_TOPIC)])
+            .await?;
+
+        Ok(logs.iter().filter_map(TokenTransfer::from_log).collect())
+    }
+
+    pub async fn trace_block(&self, number: u64) -> Result<Vec<TraceEthereum>> {
+        let traces = self
+            .archived(ArchiveKind::Traces, number, || {
+                self.call("trace_block", json!([format!("0x{number:x}")]))
+            })
+            .await?;
+
+        let traces: Option<Vec<TraceEthereum>> = from_value(traces)
+            .map_err(|e| Error::Serde(e, format!("Failed to parse traces of block {number}")))?;
+
+        traces.ok_or(Error::EmptyBlockResult)
+    }
+
+    pub async fn debug_trace_block_by_number(&self, number: u64) -> Result<Vec<TxTraceEthereum>> {
+        let traces = self
+            .archived(ArchiveKind::CallTraces, number, || {
+                self.call(
+                    "debug_traceBlockByNumber",
+                    json!([format!("0x{number:x}"), { "tracer": "callTracer" }]),
+                )
+            })
+            .await?;
+
+        from_value(traces)
+            .map_err(|e| Error::Serde(e, format!("Failed to parse call traces of block {number}")))
+    }
+
+    /// Collects the native value moved by contracts in a block from
+    /// `trace_block`, falling back to `debug_traceBlockByNumber` with the
+    /// `callTracer` on nodes without the `trace` namespace.
+    pub async fn get_internal_transfers(&self, number: u64) -> Result<Vec<InternalTransfer>> {
+        match self.trace_block(number).await {
+            Ok(traces) => return Ok(InternalTransfer::from_traces(&traces)),
+            Err(Error::Rpc(..) | Error::NotArchived(..)) => {}
+            Err(err) => return Err(err),
+        }
+
+        let traces = self.debug_trace_block_by_number(number).await?;
+
+        // Older Geth releases omit `txHash`; traces follow block order.
+        let hashes = if traces.iter().any(|trace| trace.tx_hash.is_none()) {
+            self.get_block_transaction_hashes(number).await?
+        } else {
+            vec![]
+        };
+
+        Ok(InternalTransfer::from_call_traces(&traces, &hashes, number))
+    }
+
+    async fn get_block_transaction_hashes(&self, number: u64) -> Result<Vec<String>> {
+        let block = self.get_block_by_number(number).await?;
+
+        Ok(block.transactions.into_iter().map(|tx| tx.hash).collect())
+    }
+
+    /// ERC-20 transfers sent or received by `wallet`, found by filtering
+    /// `Transfer` logs on the indexed address topics. `range` is scanned in
+    /// windows of `RPC_LOG_RANGE` blocks, `concurrency` at a time.
+    pub async fn get_wallet_token_transfers(
+        &self,
+        wallet: &str,
+        range: RangeInclusive<u64>,
+    ) -> Result<Vec<TokenTransfer>> {
+        let topic = format!(
+            "0x{:0>64}",
+            wallet.trim_start_matches("0x").to_ascii_lowercase()
+        );
+        let (start, end) = (*range.start(), *range.end());
+
+        let windows = (start..=end)
+            .step_by(RPC_LOG_RANGE as usize)
+            .map(|from| from..=end.min(from + RPC_LOG_RANGE - 1));
+
+        let requests: Vec<_> = windows
+            .map(|window| async {
+                let mut logs = self
+                    .get_logs(
+                        window.clone(),
+                        None,
+                        &[Some(TRANSFER_EVENT_TOPIC), Some(&topic)],
+                    )
+                    .await?;
+                logs.extend(
+                    self.get_logs(
+                        window,
+                        None,
+                        &[Some(TRANSFER_EVENT_TOPIC), None, Some(&topic)],
+                    )
+                    .await?,
+                );
+                Ok::<_, Error>(logs)
+            })
+            .collect();
+
+        let batches: Vec<Vec<LogEthereum>> = stream::iter(requests)
+            .buffered(self.concurrency)
+            .try_collect()
+            .await?;
+
+        // Self-transfers match both filters.
+        let mut seen = HashSet::new();
+
+        Ok(batches
+            .iter()
+            .flatten()
+            .filter_map(TokenTransfer::from_log)
+            .filter(|transfer| seen.insert((transfer.hash.clone(), transfer.log_index)))
+            .collect())
+    }
+
+    /// Timestamps of `numbers`, fetched as headers in batches.
+    pub async fn get_block_timestamps(&self, numbers: &[u64]) -> Result<HashMap<u64, u64>> {
+        let mut timestamps = HashMap::with_capacity(numbers.len());
+
+        for chunk in numbers.chunks(self.batch_size) {
+            let calls = chunk
+                .iter()
+                .map(|number| {
+                    (
+                        "eth_getBlockByNumber",
+                        json!([format!("0x{number:x}"), false]),
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            for (header, &number) in self.batch_call(&calls).await?.into_iter().zip(chunk) {
+                let timestamp = header?
+                    .get("timestamp")
+                    .and_then(Value::as_str)
+                    .and_then(|hex| u64::from_str_radix(hex.trim_start_matches("0x"), 16).ok())
+                    .ok_or(Error::EmptyBlockResult)?;
+                timestamps.insert(number, timestamp);
+            }
+        }
+
+        Ok(timestamps)
+    }
+
+    /// Block mined closest to `timestamp` (unix seconds) on the `closest`
+    /// side, found by binary search over block timestamps. `None` when no
+    /// block lies on that side.
+    pub async fn get_block_number_by_time(
+        &self,
+        timestamp: u64,
+        closest: Closest,
+    ) -> Result<Option<u64>> {
+        let latest = self.get_latest_block_number().await?;
+        let (mut low, mut high) = (0, latest);
+
+        while low < high {
+            let mid = match closest {
+                Closest::After => low + (high - low) / 2,
+                Closest::Before => low + (high - low).div_ceil(2),
+            };
+            let mined = self.get_block_timestamp(mid).await?;
+
+            match closest {
+                Closest::After if mined >= timestamp => high = mid,
+                Closest::After => low = mid + 1,
+                Closest::Before if mined <= timestamp => low = mid,
+                Closest::Before => high = mid - 1,
+            }
+        }
+
+        let mined = self.get_block_timestamp(low).await?;
+        let found = match closest {
+            Closest::After => mined >= timestamp,
+            Closest::Before => mined <= timestamp,
+        };
+
+        Ok(found.then_some(low))
+    }
+
+    async fn get_block_timestamp(&self, number: u64) -> Result<u64> {
+        self.get_block_timestamps(&[number])
+            .await?
+            .remove(&number)
+            .ok_or(Error::EmptyBlockResult)
+    }
+
+    /// Runtime bytecode of each of `addresses` at the chain head, in the
+    /// order given; `0x` for accounts without code.
+    pub async fn get_codes(&self, addresses: &[String]) -> Result<Vec<String>> {
+        let mut codes = Vec::with_capacity(addresses.len());
+
+        for chunk in addresses.chunks(self.batch_size) {
+            let calls = chunk
+                .iter()
+                .map(|address| ("eth_getCode", json!([address, "latest"])))
+                .collect::<Vec<_>>();
+
+            for result in self.batch_call(&calls).await? {
+                let code = result?;
+                let code = code.as_str().ok_or(Error::EmptyBlockResult)?;
+                codes.push(code.to_ascii_lowercase());
+            }
+        }
+
+        Ok(codes)
+    }
+
+    /// Storage word at each `(address, slot)` at the chain head, in the
+    /// order given.
+    pub async fn get_storage_words(&self, slots: &[(String, String)]) -> Result<Vec<String>> {
+        let mut words = Vec::with_capacity(slots.len());
+
+        for chunk in slots.chunks(self.batch_size) {
+            let calls = chunk
+                .iter()
+                .map(|(address, slot)| ("eth_getStorageAt", json!([address, slot, "latest"])))
+                .collect::<Vec<_>>();
+
+            for result in self.batch_call(&calls).await? {
+                let word = result?;
+                let word = word.as_str().ok_or(Error::EmptyBlockResult)?;
+                words.push(word.to_ascii_lowercase());
+            }
+        }
+
+        Ok(words)
+    }
+
+    /// Native balance of each of `addresses` at block `at` (the chain head
+    /// when `None`), in the order given.
+    pub async fn get_balances(&self, addresses: &[String], at: Option<u64>) -> Result<Vec<Wei>> {
+        let mut balances = Vec::with_capacity(addresses.len());
+
+        for chunk in addresses.chunks(self.batch_size) {
+            let calls = chunk
+                .iter()
+                .map(|address| ("eth_getBalance", json!([address, block_tag(at)])))
+                .collect::<Vec<_>>();
+
+            for result in self.batch_call(&calls).await? {
+                let balance = result?
+                    .as_str()
+                    .and_then(decode_uint)
+                    .ok_or(Error::EmptyBlockResult)?;
+                balances.push(balance);
+            }
+        }
+
+        Ok(balances)
+    }
+
+    /// Calls `balanceOf(holder)` on each of `tokens` at block `at`, in the
+    /// order given. `None` for contracts that revert or answer with
+    /// something other than a uint.
+    pub async fn get_token_balances(
+        &self,
+        holder: &str,
+        tokens: &[String],
+        at: Option<u64>,
+    ) -> Result<Vec<Option<Wei>>> {
+        let data = format!(
+            "{BALANCE_OF_SELECTOR}{:0>64}",
+            holder.trim_start_matches("0x").to_ascii_lowercase()
+        );
+        let mut balances = Vec::with_capacity(tokens.len());
+
+        for chunk in tokens.chunks(self.batch_size) {
+            let calls = chunk
+                .iter()
+                .map(|token| {
+                    (
+                        "eth_call",
+                        json!([{ "to": token, "data": data }, block_tag(at)]),
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            for result in self.batch_call(&calls).await? {
+                balances.push(
+                    result
+                        .ok()
+                        .as_ref()
+                        .and_then(Value::as_str)
+                        .and_then(decode_uint),
+                );
+            }
+        }
+
+        Ok(balances)
+    }
+
+    /// Calls `symbol()` on each token contract. Contracts that revert or
+    /// return something undecodable are left out.
+    pub async fn get_token_symbols(&self, contracts: &[String]) -> Result<HashMap<String, String>> {
+        let mut symbols = HashMap::with_capacity(contracts.len());
+
+        for chunk in contracts.chunks(self.batch_size) {
+            let calls = chunk
+                .iter()
+                .map(|contract| {
+                    (
+                        "eth_call",
+                        json!([{ "to": contract, "data": SYMBOL_SELECTOR }, "latest"]),
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            for (result, contract) in self.batch_call(&calls).await?.into_iter().zip(chunk) {
+                let symbol = result
+                    .ok()
+                    .as_ref()
+                    .and_then(Value::as_str)
+                    .and_then(decode_abi_string);
+
+                if let Some(symbol) = symbol {
+                    symbols.insert(contract.clone(), symbol);
+                }
+            }
+        }
+
+        Ok(symbols)
+    }
+
+    /// Fetches every block in `range` using JSON-RPC batches of `batch_size`,
+    /// with at most `concurrency` batches in flight. Blocks are returned in
+    /// ascending order.
+    pub async fn get_blocks_in_range(
+        &self,
+        range: RangeInclusive<u64>,
+    ) -> Result<Vec<BlockEthereum>> {
+        let numbers: Vec<u64> = range.collect();
+
+        let requests: Vec<_> = numbers
+            .chunks(self.batch_size)
+            .map(|chunk| self.get_block_batch(chunk))
+            .collect();
+
+        let batches: Vec<Vec<BlockEthereum>> = stream::iter(requests)
+            .buffered(self.concurrency)
+            .try_collect()
+            .await?;
+
+        Ok(batches.into_iter().flatten().collect())
+    }
+
+    async fn get_block_batch(&self, numbers: &[u64]) -> Result<Vec<BlockEthereum>> {
+        let mut values = Vec::with_capacity(numbers.len());
+        let mut missing = vec![];
+
+        for &number in numbers {
+            match self.read_archive(ArchiveKind::Block, number)? {
+                Some(value) => values.push((number, value)),
+                None => missing.push(number),
+            }
+        }
+
+        if let Some(&number) = missing.first().filter(|_| self.offline) {
+            return Err(Error::NotArchived(ArchiveKind::Block.to_string(), number));
+        }
+
+        let calls = missing
+            .iter()
+            .map(|number| {
+                (
+                    "eth_getBlockByNumber",
+                    json!([format!("0x{number:x}"), true]),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        for (value, &number) in self.batch_call(&calls).await?.into_iter().zip(&missing) {
+            let value = value?;
+            self.write_archive(ArchiveKind::Block, number, &value)?;
+            values.push((number, value));
+        }
+
+        values.sort_by_key(|(number, _)| *number);
+
+        values
+            .into_iter()
+            .map(|(number, value)| parse_block(value, &format!("0x{number:x}")))
+            .collect()
+    }
+
+    /// Returns the archived `kind` payload of block `number`, or fetches and
+    /// archives it when missing.
+    async fn archived<F, Fut>(&self, kind: ArchiveKind, number: u64, fetch: F) -> Result<Value>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Value>>,
+    {
+        if let Some(value) = self.read_archive(kind, number)? {
+            return Ok(value);
+        }
+
+        if self.offline {
+            return Err(Error::NotArchived(kind.to_string(), number));
+        }
+
+        let value = fetch().await?;
+        self.write_archive(kind, number, &value)?;
+
+        Ok(value)
+    }
+
+    fn read_archive(&self, kind: ArchiveKind, number: u64) -> Result<Option<Value>> {
+        match &self.archive {
+            Some(archive) => archive.get(kind, number),
+            None => Ok(None),
+        }
+    }
+
+    fn write_archive(&self, kind: ArchiveKind, number: u64, value: &Value) -> Result<()> {
+        match &self.archive {
+            Some(archive) if !value.is_null() => archive.put(kind, number, value),
+            _ => Ok(()),
+        }
+    }
+
+    /// Sends a single JSON-RPC request and returns its decoded `result`.
+    pub async fn call<T>(&self, method: &str, params: Value) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let response = self.send(&self.request_body(method, params)).await?;
+
+        let result = parse_response(response)?;
+
+        from_value(result).map_err(|e| Error::Serde(e, format!("Failed to parse {method} result")))
+    }
+
+    /// Sends `calls` as one JSON-RPC batch. Results come back in the order of
+    /// `calls`, each carrying its own error if the node rejected it.
+    pub async fn batch_call(&self, calls: &[(&str, Value)]) -> Result<Vec<Result<Value>>> {
+        if calls.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let requests: Vec<Value> = calls
+            .iter()
+            .map(|(method, params)| self.request_body(method, params.clone()))
+            .collect();
+
+        let positions: HashMap<u64, usize> = requests
+            .iter()
+            .enumerate()
+            .filter_map(|(index, request)| Some((request["id"].as_u64()?, index)))
+            .collect();
+
+        let response = self.send(&json!(requests)).await?;
+
+        // Providers that refuse batches answer with a single error object.
+        let responses = match response {
+            Value::Array(responses) => responses,
+            other => {
+                parse_response(other)?;
+                return Err(Error::EmptyBlockResult);
+            }
+        };
+
+        let mut results: Vec<Option<Result<Value>>> = (0..calls.len()).map(|_| None).collect();
+
+        for response in responses {
+            let slot = response
+                .get("id")
+                .and_then(Value::as_u64)
+                .and_then(|id| positions.get(&id))
+                .and_then(|&index| results.get_mut(index));
+
+            if let Some(slot) = slot {
+                *slot = Some(parse_response(response));
+            }
+        }
+
+        Ok(results
+            .into_iter()
+            .map(|result| result.unwrap_or(Err(Error::EmptyBlockResult)))
+            .collect())
+    }
+
+    /// Asks every provider for the hash of block `number` and checks that at
+    /// least `quorum` of them report `expected`.
+    pub async fn verify_block_hash(&self, number: u64, expected: &str) -> Result<()> {
+        if self.quorum <= 1 || self.offline {
+            return Ok(());
+        }
+
+        let body = self.request_body(
+            "eth_getBlockByNumber",
+            json!([format!("0x{number:x}"), false]),
+        );
+        let responses = join_all(
+            self.endpoints
+                .iter()
+                .map(|endpoint| self.send_to(endpoint, &body)),
+        )
+        .await;
+
+        let agreeing = responses
+            .into_iter()
+            .filter_map(|response| parse_response(response.ok()?).ok())
+            .filter(|block| {
+                block
+                    .get("hash")
+                    .and_then(Value::as_str)
+                    .is_some_and(|hash| hash.eq_ignore_ascii_case(expected))
+            })
+            .count();
+
+        if agreeing < self.quorum {
+            return Err(Error::QuorumNotReached(number, agreeing, self.quorum));
+        }
+
+        Ok(())
+    }
+
+    /// Posts `body` to the healthiest endpoint, retrying with jittered
+    /// backoff and failing over to the next endpoint on transport errors.
+    async fn send(&self, body: &Value) -> Result<Value> {
+        if self.offline {
+            let request = body.get(0).unwrap_or(body);
+            let method = request["method"].as_str().unwrap_or("RPC");
+            return Err(Error::Offline(method.to_string()));
+        }
+
+        let mut last_error = Error::NoEndpoint;
+
+        for endpoint in self.endpoint_order() {
+            let mut backoff = Duration::from_millis(RPC_RETRY_BASE_MS);
+
+            for attempt in 1..=self.max_retries {
+                match self.send_to(endpoint, body).await {
+                    Ok(response) => {
+                        endpoint.mark_success();
+                        return Ok(response);
+                    }
+                    Err(err) => {
+                        endpoint.mark_failure();
+                        last_error = err;
+
+                        if attempt < self.max_retries {
+                            sleep(with_jitter(backoff)).await;
+                            backoff *= 2;
+                        }
+                    }
+                }
+            }
+
+            if self.endpoints.len() > 1 {
+                eprintln!(
+                    "⚠️  RPC endpoint {} failed ({last_error}), failing over",
+                    endpoint.url
+                );
+            }
+        }
+
+        Err(last_error)
+    }
+
+    async fn send_to(&self, endpoint: &RpcEndpoint, body: &Value) -> Result<Value> {
         let response = self
             .client
-            .post(&self.url)
-            .json(&json!({
-                "jsonrpc": "2.0",
-                "method": "eth_getTransactionByHash",
-                "params": [hash],
-                "id": 1
-            }))
+            .post(&endpoint.url)
+            .json(body)
             .send()
             .await?
+            .error_for_status()?
             .json::<Value>()
             .await?;
 
-        if let Some(error) = response.get("error") {
-            let code = error.get("code").and_then(Value::as_i64).unwrap_or(0);
-            let message = error
-                .get("message")
-                .and_then(Value::as_str)
-                .unwrap_or("Unknown error");
-            return Err(Error::Rpc(code, message.to_string()));
-        }
+        Ok(response)
+    }
 
-        let tx = response.get("result").ok_or(Error::EmptyBlockResult)?;
+    /// Healthy endpoints first, then those cooling down, each in configured order.
+    fn endpoint_order(&self) -> impl Iterator<Item = &RpcEndpoint> {
+        let (healthy, cooling): (Vec<_>, Vec<_>) = self
+            .endpoints
+            .iter()
+            .partition(|endpoint| endpoint.is_healthy());
 
-        if tx.is_null() {
-            return Err(Error::TxNotFound(hash.to_string()));
+        healthy.into_iter().chain(cooling)
+    }
+
+    fn request_body(&self, method: &str, params: Value) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+            "id": self.next_id.fetch_add(1, Ordering::Relaxed),
+        })
+    }
+}
+
+fn block_tag(at: Option<u64>) -> String {
+    match at {
+        Some(number) => format!("0x{number:x}"),
+        None => "latest".to_string(),
+    }
+}
+
+/// Decodes a hex quantity or a single ABI `uint256` word. Amounts beyond
+/// `u128` saturate, which only junk tokens reach.
+fn decode_uint(hex: &str) -> Option<Wei> {
+    let digits = hex.strip_prefix("0x")?.trim_start_matches('0');
+
+    if digits.is_empty() {
+        // A bare `0x` is what calling an account without code returns.
+        return (hex.len() > 2).then_some(0);
+    }
+    if digits.len() > 64 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    if digits.len() > 32 {
+        return Some(Wei::MAX);
+    }
+
+    Wei::from_str_radix(digits, 16).ok()
+}
+
+/// Decodes an ABI-encoded `string` return value, or a `bytes32` one as used
+/// by early tokens such as MKR.
+fn decode_abi_string(hex: &str) -> Option<String> {
+    let data = hex.trim_start_matches("0x");
+
+    let bytes: Vec<u8> = (0..data.len() / 2)
+        .map(|i| u8::from_str_radix(data.get(i * 2..i * 2 + 2)?, 16).ok())
+        .collect::<Option<_>>()?;
+
+    let text = if bytes.len() == 32 {
+        bytes.into_iter().take_while(|&byte| byte != 0).collect()
+    } else {
+        // Offsets and lengths come from the token contract, so any of them
+        // may point past the data or overflow.
+        let word = |index: usize| -> Option<usize> {
+            let chunk = bytes.get(index..index.checked_add(32)?)?;
+            let (high, low) = chunk.split_at(24);
+            if !high.iter().all(|&byte| byte == 0) {
+                return None;
+            }
+            let value = low
+                .iter()
+                .fold(0u64, |acc, &byte| (acc << 8) | u64::from(byte));
+            usize::try_from(value).ok()
+        };
+
+        let offset = word(0)?;
+        let len = word(offset)?;
+        let start = offset.checked_add(32)?;
+        bytes.get(start..start.checked_add(len)?)?.to_vec()
+    };
+
+    String::from_utf8(text)
+        .ok()
+        .filter(|symbol| !symbol.is_empty())
+}
+
+fn build_client(timeout: Duration) -> Client {
+    Client::builder()
+        .timeout(timeout)
+        .build()
+        .unwrap_or_default()
+}
+
+/// Extracts `result` from a JSON-RPC response, mapping `error` to [`Error::Rpc`].
+fn parse_response(mut response: Value) -> Result<Value> {
+    if let Some(error) = response.get("error") {
+        let code = error.get("code").and_then(Value::as_i64).unwrap_or(0);
+        let message = error
+            .get("message")
+            .and_then(Value::as_str)
+            .unwrap_or("Unknown error");
+        return Err(Error::Rpc(code, message.to_string()));
+    }
+
+    response
+        .get_mut("result")
+        .map(Value::take)
+        .ok_or(Error::EmptyBlockResult)
+}
+
+fn parse_block(value: Value, hex_block: &str) -> Result<BlockEthereum> {
+    if value.is_null() {
+        return Err(Error::EmptyBlockResult);
+    }
+
+    from_value(value).map_err(|e| Error::Serde(e, format!("Failed to parse block: {hex_block}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// ABI `string` return value: offset 0x20, length, then the padded text.
+    fn abi_string(text: &str) -> String {
+        let mut data = format!("{:064x}{:064x}", 32, text.len());
+        let mut padded = text.as_bytes().to_vec();
+        padded.resize(text.len().div_ceil(32).max(1) * 32, 0);
+        for byte in padded {
+            data.push_str(&format!("{byte:02x}"));
         }
+        format!("0x{data}")
+    }
 
-        from_value(tx.clone())
-            .map_err(|e| Error::Serde(e, format!("Failed to parse transaction: {hash}")))
+    #[test]
+    fn decode_abi_string_reads_dynamic_strings() {
+        assert_eq!(
+            decode_abi_string(&abi_string("USDT")),
+            Some("USDT".to_string())
+        );
+        assert_eq!(
+            decode_abi_string(&abi_string("A token symbol longer than one word")),
+            Some("A token symbol longer than one word".to_string())
+        );
+    }
+
+    #[test]
+    fn decode_abi_string_reads_bytes32_symbols() {
+        // MKR returns its symbol as a zero-padded bytes32.
+        let mkr = format!("0x4d4b52{}", "0".repeat(58));
+        assert_eq!(decode_abi_string(&mkr), Some("MKR".to_string()));
+    }
+
+    #[test]
+    fn decode_abi_string_rejects_empty_and_malformed_data() {
+        assert_eq!(decode_abi_string("0x"), None);
+        assert_eq!(decode_abi_string(&abi_string("")), None);
+        assert_eq!(decode_abi_string("0xzz"), None);
+    }
+
+    #[test]
+    fn decode_abi_string_rejects_out_of_range_offsets() {
+        let max = "ffffffffffffffff";
+        let word = |low: &str| format!("{low:0>64}");
+
+        let cases = [
+            // Offset near 2^64: reading its length word would overflow.
+            format!("0x{}{}", word(max), word("4")),
+            // Offset just below 2^64 - 32: the string start would overflow.
+            format!("0x{}{}", word("ffffffffffffffe0"), word("4")),
+            // Length near 2^64: the string end would overflow.
+            format!("0x{}{}{}", word("20"), word(max), word("55534454")),
+            // Offset past the data.
+            format!("0x{}{}", word("1000"), word("4")),
+            // Offset with bits above the low 8 bytes.
+            format!("0x{}{}", "1".repeat(64), word("4")),
+        ];
+
+        for data in cases {
+            assert_eq!(decode_abi_string(&data), None, "{data}");
+        }
     }
 }
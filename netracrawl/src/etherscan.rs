@@ -1,89 +1,785 @@
-use common::model::TxResponse;
-use reqwest::Client;
-use serde::Deserialize;
-use serde_json::from_str;
+use std::{collections::HashSet, hash::Hash, time::Duration};
 
-use crate::error::Result;
+use clap::ValueEnum;
+use common::model::{AssetKind, TxResponse, TxRowKey, Wei};
+use reqwest::{Client, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{from_str, from_value, Value};
+use tokio::{
+    sync::Mutex,
+    time::{sleep, sleep_until, Instant},
+};
 
+use crate::{
+    constants::{
+        ETHERSCAN_BACKOFF_MS, ETHERSCAN_BALANCE_BATCH, ETHERSCAN_MAX_RESULT_WINDOW,
+        ETHERSCAN_MAX_RETRIES,
+    },
+    error::{Error, Result},
+    explorer::Explorer,
+};
+
+/// Raw Etherscan response envelope.
+///
+/// `result` is an array on success but a plain string when `status` is `"0"`
+/// (e.g. `"Max rate limit reached"`), so it is kept untyped until inspected.
+/// Actions answering with a single value (e.g. `getblocknobytime`) are read
+/// as a one-element array.
 #[derive(Debug, Deserialize)]
 pub struct EtherscanResponse {
-    pub result: Vec<TxResponse>,
+    pub status: String,
+    pub message: String,
+    pub result: Value,
+}
+
+/// Interpreted outcome of an [`EtherscanResponse`].
+#[derive(Debug)]
+pub enum EtherscanPayload<T> {
+    Data(Vec<T>),
+    Empty,
+    RateLimited(String),
+    InvalidKey(String),
+    Failed(String),
+}
+
+impl EtherscanResponse {
+    pub fn into_payload<T>(self) -> Result<EtherscanPayload<T>>
+    where
+        T: DeserializeOwned,
+    {
+        if self.status == "1" {
+            let result = match self.result {
+                Value::Array(_) => self.result,
+                scalar => Value::Array(vec![scalar]),
+            };
+            let data: Vec<T> = from_value(result)
+                .map_err(|e| Error::Serde(e, "Failed to parse Etherscan result".to_string()))?;
+
+            return Ok(if data.is_empty() {
+                EtherscanPayload::Empty
+            } else {
+                EtherscanPayload::Data(data)
+            });
+        }
+
+        let detail = match &self.result {
+            Value::String(detail) => detail.clone(),
+            Value::Array(items) if items.is_empty() => String::new(),
+            other => other.to_string(),
+        };
+
+        let message = self.message.to_ascii_lowercase();
+        let lower = detail.to_ascii_lowercase();
+
+        let payload = if detail.is_empty()
+            || message.starts_with("no transactions found")
+            || message.starts_with("no records found")
+        {
+            EtherscanPayload::Empty
+        } else if lower.contains("rate limit") {
+            EtherscanPayload::RateLimited(detail)
+        } else if lower.contains("invalid api key") || lower.contains("missing/invalid api key") {
+            EtherscanPayload::InvalidKey(detail)
+        } else {
+            EtherscanPayload::Failed(format!("{}: {detail}", self.message))
+        };
+
+        Ok(payload)
+    }
+}
+
+/// Record returned by a paginated Etherscan `account` action.
+pub trait EtherscanRecord: DeserializeOwned {
+    fn block_number(&self) -> Option<u64>;
+
+    type Key: Eq + Hash;
+
+    /// Identity used to drop records repeated across page boundaries.
+    fn dedup_key(&self) -> Self::Key;
+}
+
+impl EtherscanRecord for TxResponse {
+    type Key = TxRowKey;
+
+    fn block_number(&self) -> Option<u64> {
+        self.block_number.parse().ok()
+    }
+
+    fn dedup_key(&self) -> TxRowKey {
+        self.row_key()
+    }
+}
+
+/// Records collected by a paginated fetch, with the work it took.
+#[derive(Debug)]
+pub struct Paginated<T> {
+    pub items: Vec<T>,
+    pub pages: usize,
+    pub duplicates: usize,
+}
+
+impl<T> Default for Paginated<T> {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            pages: 0,
+            duplicates: 0,
+        }
+    }
+}
+
+impl<T> Paginated<T>
+where
+    T: EtherscanRecord,
+{
+    fn extend(&mut self, seen: &mut HashSet<T::Key>, records: Vec<T>) -> usize {
+        let before = self.items.len();
+
+        for record in records {
+            if seen.insert(record.dedup_key()) {
+                self.items.push(record);
+            } else {
+                self.duplicates += 1;
+            }
+        }
+
+        self.items.len() - before
+    }
+}
+
+/// Etherscan API plan, used to pace requests below the plan's rate limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ApiTier {
+    Free,
+    Standard,
+    Advanced,
+    Professional,
+}
+
+impl ApiTier {
+    pub fn calls_per_second(&self) -> u64 {
+        match self {
+            ApiTier::Free => 5,
+            ApiTier::Standard => 10,
+            ApiTier::Advanced => 20,
+            ApiTier::Professional => 30,
+        }
+    }
+
+    pub fn min_interval(&self) -> Duration {
+        Duration::from_millis(1_000 / self.calls_per_second())
+    }
+}
+
+/// Inclusive block window passed as `startblock`/`endblock`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl BlockRange {
+    pub fn new(start: u64, end: u64) -> Self {
+        Self { start, end }
+    }
+
+    /// Window from `start` with no upper bound, for sources holding a
+    /// fixed set of rows.
+    pub fn from_start(start: u64) -> Self {
+        Self::new(start, u64::MAX)
+    }
+}
+
+impl Default for BlockRange {
+    fn default() -> Self {
+        Self::from_start(0)
+    }
+}
+
+/// Row of a `balancemulti` result.
+#[derive(Debug, Deserialize)]
+struct AccountBalance {
+    account: String,
+    balance: String,
+}
+
+/// Which side of a timestamp to resolve a block on, as passed to
+/// `getblocknobytime`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Closest {
+    /// Last block mined at or before the timestamp.
+    Before,
+    /// First block mined at or after the timestamp.
+    After,
+}
+
+impl Closest {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Closest::Before => "before",
+            Closest::After => "after",
+        }
+    }
 }
 
 pub struct EtherscanClient {
     api_key: String,
     client: Client,
+    explorer: Explorer,
+    tier: ApiTier,
+    last_request: Mutex<Option<Instant>>,
 }
 
 impl EtherscanClient {
-    pub fn new(api_key: impl Into<String>) -> Self {
+    pub fn new(api_key: impl Into<String>, explorer: Explorer) -> Self {
         Self {
             api_key: api_key.into(),
             client: Client::new(),
+            explorer,
+            tier: ApiTier::Free,
+            last_request: Mutex::new(None),
         }
     }
 
+    pub fn with_tier(mut self, tier: ApiTier) -> Self {
+        self.tier = tier;
+        self
+    }
+
+    pub fn explorer(&self) -> &Explorer {
+        &self.explorer
+    }
+
     pub async fn fetch_token_transfer(
         &self,
         wallet: &str,
-        start_block: u64,
+        range: BlockRange,
+        page: usize,
         offset: usize,
     ) -> Result<Vec<TxResponse>> {
-        let url = format!(
-            "https://api.etherscan.io/api?module=account\
-                &action=tokentx\
+        self.fetch_page(action(AssetKind::Erc20), wallet, range, page, offset)
+            .await
+    }
+
+    pub async fn fetch_all_token_transfer(
+        &self,
+        wallet: &str,
+        range: BlockRange,
+        offset: usize,
+    ) -> Result<Paginated<TxResponse>> {
+        self.fetch_all_by_kind(AssetKind::Erc20, wallet, range, offset)
+            .await
+    }
+
+    pub async fn fetch_all_normal_tx(
+        &self,
+        wallet: &str,
+        range: BlockRange,
+        offset: usize,
+    ) -> Result<Paginated<TxResponse>> {
+        self.fetch_all_by_kind(AssetKind::Native, wallet, range, offset)
+            .await
+    }
+
+    pub async fn fetch_all_internal_tx(
+        &self,
+        wallet: &str,
+        range: BlockRange,
+        offset: usize,
+    ) -> Result<Paginated<TxResponse>> {
+        self.fetch_all_by_kind(AssetKind::Internal, wallet, range, offset)
+            .await
+    }
+
+    pub async fn fetch_all_nft_transfer(
+        &self,
+        wallet: &str,
+        range: BlockRange,
+        offset: usize,
+    ) -> Result<Paginated<TxResponse>> {
+        self.fetch_all_by_kind(AssetKind::Erc721, wallet, range, offset)
+            .await
+    }
+
+    pub async fn fetch_all_erc1155_transfer(
+        &self,
+        wallet: &str,
+        range: BlockRange,
+        offset: usize,
+    ) -> Result<Paginated<TxResponse>> {
+        self.fetch_all_by_kind(AssetKind::Erc1155, wallet, range, offset)
+            .await
+    }
+
+    /// Fetches every asset kind for `wallet` and merges them into one
+    /// block-ordered list. Failed (`isError`) transactions are dropped since
+    /// they moved no value.
+    pub async fn fetch_all_transfers(
+        &self,
+        wallet: &str,
+        range: BlockRange,
+        offset: usize,
+    ) -> Result<Vec<TxResponse>> {
+        let mut transfers = Vec::new();
+
+        for kind in AssetKind::ALL {
+            let fetched = self.fetch_all_by_kind(kind, wallet, range, offset).await?;
+            transfers.extend(fetched.items.into_iter().filter(|tx| !tx.is_failed()));
+        }
+
+        transfers.sort_by_key(|tx| (EtherscanRecord::block_number(tx), tx.time_stamp.clone()));
+
+        Ok(transfers)
+    }
+
+    /// Fetches every `kind` record of `wallet` in `range`, failed ones
+    /// included, tagged with `kind`.
+    pub async fn fetch_all_by_kind(
+        &self,
+        kind: AssetKind,
+        wallet: &str,
+        range: BlockRange,
+        offset: usize,
+    ) -> Result<Paginated<TxResponse>> {
+        let mut fetched: Paginated<TxResponse> =
+            self.paginate(action(kind), wallet, range, offset).await?;
+
+        for tx in &mut fetched.items {
+            tx.kind = kind;
+
+            if matches!(kind, AssetKind::Native | AssetKind::Internal) {
+                tx.token_symbol = self.explorer.native_symbol.clone();
+            }
+        }
+
+        Ok(fetched)
+    }
+
+    /// Block mined closest to `timestamp` (unix seconds) on the `closest`
+    /// side, or `None` when the chain has no block there yet.
+    pub async fn get_block_number_by_time(
+        &self,
+        timestamp: u64,
+        closest: Closest,
+    ) -> Result<Option<u64>> {
+        let url = self.explorer.endpoint(&format!(
+            "module=block\
+                &action=getblocknobytime\
+                &timestamp={timestamp}\
+                &closest={}\
+                &apikey={}",
+            closest.as_str(),
+            self.api_key
+        ));
+
+        match self.request::<String>(&url).await {
+            Ok(result) => match result.first() {
+                Some(number) => Ok(Some(number.parse()?)),
+                None => Ok(None),
+            },
+            Err(Error::Etherscan(msg)) if msg.to_ascii_lowercase().contains("no closest block") => {
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Current native balance of each of `addresses`, in the order given,
+    /// via `balancemulti` in batches of [`ETHERSCAN_BALANCE_BATCH`].
+    pub async fn fetch_balances(&self, addresses: &[String]) -> Result<Vec<Wei>> {
+        let mut balances = Vec::with_capacity(addresses.len());
+
+        for chunk in addresses.chunks(ETHERSCAN_BALANCE_BATCH) {
+            let url = self.explorer.endpoint(&format!(
+                "module=account\
+                    &action=balancemulti\
+                    &address={}\
+                    &tag=latest\
+                    &apikey={}",
+                chunk.join(","),
+                self.api_key
+            ));
+
+            let rows: Vec<AccountBalance> = self.request(&url).await?;
+
+            for address in chunk {
+                let row = rows
+                    .iter()
+                    .find(|row| row.account.eq_ignore_ascii_case(address))
+                    .ok_or_else(|| {
+                        Error::Etherscan(format!("No balance returned for {address}"))
+                    })?;
+                balances.push(row.balance.parse()?);
+            }
+        }
+
+        Ok(balances)
+    }
+
+    /// Current `token` balance of `address`, in raw token units.
+    pub async fn fetch_token_balance(&self, token: &str, address: &str) -> Result<Wei> {
+        let url = self.explorer.endpoint(&format!(
+            "module=account\
+                &action=tokenbalance\
+                &contractaddress={token}\
+                &address={address}\
+                &tag=latest\
+                &apikey={}",
+            self.api_key
+        ));
+
+        match self.request::<String>(&url).await?.first() {
+            Some(balance) => Ok(balance.parse()?),
+            None => Ok(0),
+        }
+    }
+
+    async fn fetch_page<T>(
+        &self,
+        action: &str,
+        wallet: &str,
+        range: BlockRange,
+        page: usize,
+        offset: usize,
+    ) -> Result<Vec<T>>
+    where
+        T: DeserializeOwned,
+    {
+        let url = self.explorer.endpoint(&format!(
+            "module=account\
+                &action={action}\
                 &address={wallet}\
-                &startblock={start_block}\
-                &endblock=13000000\
-                &sort=asc\
+                &startblock={}\
+                &endblock={}\
+                &page={page}\
                 &offset={offset}\
+                &sort=asc\
                 &apikey={}",
-            self.api_key
-        );
+            range.start, range.end, self.api_key
+        ));
+
+        self.request(&url).await
+    }
+
+    /// Walks `range` by block number, re-requesting the boundary block of
+    /// each page and dropping records already seen. When a page makes no
+    /// progress (e.g. it is entirely one block), that block is drained with
+    /// page/offset paging before moving on. `offset` must fit in Etherscan's
+    /// result window.
+    async fn paginate<T>(
+        &self,
+        action: &str,
+        wallet: &str,
+        range: BlockRange,
+        offset: usize,
+    ) -> Result<Paginated<T>>
+    where
+        T: EtherscanRecord,
+    {
+        if !(1..=ETHERSCAN_MAX_RESULT_WINDOW).contains(&offset) {
+            return Err(Error::InvalidOffset(offset));
+        }
+
+        let mut result = Paginated::default();
+        let mut seen = HashSet::new();
+        let mut start_block = range.start;
 
-        let response = self.client.get(&url).send().await?;
+        while start_block <= range.end {
+            let window = BlockRange::new(start_block, range.end);
+            let records: Vec<T> = self.fetch_page(action, wallet, window, 1, offset).await?;
+            result.pages += 1;
 
-        let status = response.status();
-        let body = response.text().await?;
+            let is_last_page = records.len() < offset;
+            let last_block = records.last().and_then(EtherscanRecord::block_number);
+            let added = result.extend(&mut seen, records);
 
-        if !status.is_success() {
-            eprintln!("❌ Error parsing response: {body}");
+            if is_last_page {
+                break;
+            }
+
+            match last_block {
+                Some(block) if block > start_block && added > 0 => start_block = block,
+                _ => {
+                    self.drain_block(action, wallet, start_block, offset, &mut seen, &mut result)
+                        .await?;
+                    start_block += 1;
+                }
+            }
         }
 
-        let etherscan: EtherscanResponse = from_str(&body)?;
-        Ok(etherscan.result)
+        eprintln!(
+            "📦 Fetched {} {action} records across {} pages ({} duplicates skipped)",
+            result.items.len(),
+            result.pages,
+            result.duplicates
+        );
+
+        Ok(result)
     }
 
-    pub async fn fetch_all_token_transfer(
+    async fn drain_block<T>(
         &self,
+        action: &str,
         wallet: &str,
-        mut start_block: u64,
+        block: u64,
         offset: usize,
-    ) -> Result<Vec<TxResponse>> {
-        let mut all_txs: Vec<TxResponse> = Vec::new();
+        seen: &mut HashSet<T::Key>,
+        result: &mut Paginated<T>,
+    ) -> Result<()>
+    where
+        T: EtherscanRecord,
+    {
+        let window = BlockRange::new(block, block);
+        let max_page = ETHERSCAN_MAX_RESULT_WINDOW / offset;
 
-        loop {
-            let txs: Vec<TxResponse> = self
-                .fetch_token_transfer(wallet, start_block, offset)
+        for page in 1..=max_page {
+            let records: Vec<T> = self
+                .fetch_page(action, wallet, window, page, offset)
                 .await?;
+            result.pages += 1;
 
-            if txs.is_empty() {
-                break;
+            let is_last_page = records.len() < offset;
+            result.extend(seen, records);
+
+            if is_last_page {
+                return Ok(());
             }
+        }
 
-            println!(
-                "📦 Fetched {} txs starting from block {start_block}",
-                txs.len()
+        eprintln!(
+            "⚠️  Block {block} holds more than {ETHERSCAN_MAX_RESULT_WINDOW} {action} records for {wallet}, the rest is truncated"
+        );
+
+        Ok(())
+    }
+
+    /// Sends a GET request, pacing it to the API tier and retrying with
+    /// exponential backoff while Etherscan reports a rate limit.
+    async fn request<T>(&self, url: &str) -> Result<Vec<T>>
+    where
+        T: DeserializeOwned,
+    {
+        let mut backoff = Duration::from_millis(ETHERSCAN_BACKOFF_MS);
+        let mut attempt = 0;
+
+        loop {
+            self.throttle().await;
+
+            let response = self.client.get(url).send().await?;
+            let status = response.status();
+            let body = response.text().await?;
+
+            let payload = if status == StatusCode::TOO_MANY_REQUESTS {
+                EtherscanPayload::RateLimited(body)
+            } else {
+                if !status.is_success() {
+                    eprintln!("❌ Error parsing response: {body}");
+                }
+
+                from_str::<EtherscanResponse>(&body)
+                    .map_err(|e| Error::Serde(e, "Failed to parse Etherscan response".to_string()))?
+                    .into_payload()?
+            };
+
+            match payload {
+                EtherscanPayload::Data(data) => return Ok(data),
+                EtherscanPayload::Empty => return Ok(Vec::new()),
+                EtherscanPayload::InvalidKey(msg) => return Err(Error::InvalidApiKey(msg)),
+                EtherscanPayload::Failed(msg) => return Err(Error::Etherscan(msg)),
+                EtherscanPayload::RateLimited(msg) => {
+                    if attempt >= ETHERSCAN_MAX_RETRIES {
+                        return Err(Error::RateLimited(msg));
+                    }
+
+                    eprintln!(
+                        "⏳ Rate limited ({msg}), retrying in {}ms",
+                        backoff.as_millis()
+                    );
+
+                    sleep(backoff).await;
+                    backoff *= 2;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    async fn throttle(&self) {
+        let mut last_request = self.last_request.lock().await;
+
+        if let Some(last) = *last_request {
+            sleep_until(last + self.tier.min_interval()).await;
+        }
+
+        *last_request = Some(Instant::now());
+    }
+}
+
+pub(crate) fn action(kind: AssetKind) -> &'static str {
+    match kind {
+        AssetKind::Native => "txlist",
+        AssetKind::Internal => "txlistinternal",
+        AssetKind::Erc20 => "tokentx",
+        AssetKind::Erc721 => "tokennfttx",
+        AssetKind::Erc1155 => "token1155tx",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::model::Chain;
+    use serde_json::json;
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::{mock::MockServer, source::FixtureSource};
+
+    const WALLET: &str = "0x1111111111111111111111111111111111111111";
+    const HASH: &str = "0x5c504ed432cb51138bcf09aa5e8a410dd4a1e204ef84bfed1be16dfba1b22060";
+
+    fn payload(body: Value) -> EtherscanPayload<Value> {
+        from_value::<EtherscanResponse>(body)
+            .expect("envelope")
+            .into_payload()
+            .expect("payload")
+    }
+
+    #[test]
+    fn ok_envelope_yields_its_rows() {
+        let rows = payload(json!({
+            "status": "1",
+            "message": "OK",
+            "result": [{ "hash": "0x01" }, { "hash": "0x02" }],
+        }));
+        assert!(matches!(rows, EtherscanPayload::Data(rows) if rows.len() == 2));
+
+        let scalar = payload(json!({ "status": "1", "message": "OK", "result": "17000000" }));
+        assert!(matches!(scalar, EtherscanPayload::Data(rows) if rows == [json!("17000000")]));
+
+        let empty = payload(json!({ "status": "1", "message": "OK", "result": [] }));
+        assert!(matches!(empty, EtherscanPayload::Empty));
+    }
+
+    #[test]
+    fn no_records_envelope_is_empty() {
+        for message in ["No transactions found", "No records found"] {
+            let body = json!({ "status": "0", "message": message, "result": [] });
+            assert!(
+                matches!(payload(body), EtherscanPayload::Empty),
+                "{message}"
             );
+        }
+    }
 
-            start_block = txs
-                .last()
-                .and_then(|tx| tx.block_number.parse::<u64>().ok())
-                .unwrap_or(start_block + 1);
+    #[test]
+    fn notok_envelope_is_classified_by_its_detail() {
+        let rate_limited = payload(json!({
+            "status": "0",
+            "message": "NOTOK",
+            "result": "Max rate limit reached, please use API Key for higher rate limit",
+        }));
+        assert!(matches!(rate_limited, EtherscanPayload::RateLimited(_)));
 
-            all_txs.extend(txs);
+        for detail in ["Invalid API Key", "Missing/Invalid API Key"] {
+            let body = json!({ "status": "0", "message": "NOTOK", "result": detail });
+            assert!(
+                matches!(payload(body), EtherscanPayload::InvalidKey(key) if key == detail),
+                "{detail}"
+            );
         }
 
-        Ok(all_txs)
+        let failed = payload(json!({
+            "status": "0",
+            "message": "NOTOK",
+            "result": "Error! Invalid address format",
+        }));
+        assert!(matches!(
+            failed,
+            EtherscanPayload::Failed(msg) if msg == "NOTOK: Error! Invalid address format"
+        ));
+    }
+
+    /// Sets the field telling apart the rows of one transaction.
+    type Vary = fn(&mut TxResponse, usize);
+
+    /// Three rows of one transaction in block 100, told apart by `vary`,
+    /// and one more row in block 101.
+    fn rows(vary: Vary) -> Vec<TxResponse> {
+        (0..4)
+            .map(|i| {
+                let mut tx = TxResponse {
+                    block_number: if i < 3 { "100" } else { "101" }.to_string(),
+                    from: WALLET.to_string(),
+                    time_stamp: "1700000000".to_string(),
+                    hash: if i < 3 {
+                        HASH.to_string()
+                    } else {
+                        format!("0x{i:064x}")
+                    },
+                    ..TxResponse::default()
+                };
+                vary(&mut tx, i);
+                tx
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn rows_sharing_a_hash_survive_dedup_and_block_draining() {
+        let root = std::env::temp_dir().join(format!("netra-paginate-{}", std::process::id()));
+        let fixtures = FixtureSource::new(&root);
+
+        let cases: [(AssetKind, Vary); 3] = [
+            (AssetKind::Erc20, |tx, i| tx.log_index = Some(i.to_string())),
+            (AssetKind::Internal, |tx, i| {
+                tx.trace_id = Some(format!("0_{i}"))
+            }),
+            (AssetKind::Erc1155, |tx, i| {
+                tx.token_id = Some(i.to_string())
+            }),
+        ];
+        for (kind, vary) in cases {
+            fixtures
+                .write_account(WALLET, kind, &rows(vary))
+                .expect("fixture");
+        }
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+        let addr = listener.local_addr().expect("addr");
+        tokio::spawn(MockServer::new(FixtureSource::new(&root)).serve(listener));
+
+        let explorer = Explorer::for_chain(Chain::Ethereum).with_base_url(format!("http://{addr}"));
+        let client = EtherscanClient::new("key", explorer).with_tier(ApiTier::Professional);
+
+        for (kind, _) in cases {
+            // Two rows per page: block 100 fills the first page on its own,
+            // so it is drained with page/offset paging.
+            let fetched = client
+                .fetch_all_by_kind(kind, WALLET, BlockRange::new(0, 200), 2)
+                .await
+                .expect("fetch");
+
+            let keys: HashSet<TxRowKey> = fetched.items.iter().map(TxResponse::row_key).collect();
+            assert_eq!(fetched.items.len(), 4, "{kind:?}");
+            assert_eq!(keys.len(), 4, "{kind:?}");
+            assert!(fetched.duplicates > 0, "{kind:?}");
+        }
+
+        std::fs::remove_dir_all(root).expect("cleanup");
+    }
+
+    #[tokio::test]
+    async fn paginate_rejects_pages_outside_the_result_window() {
+        let client = EtherscanClient::new("key", Explorer::for_chain(Chain::Ethereum));
+
+        for offset in [0, ETHERSCAN_MAX_RESULT_WINDOW + 1] {
+            let result = client
+                .fetch_all_by_kind(AssetKind::Native, WALLET, BlockRange::default(), offset)
+                .await;
+            assert!(
+                matches!(result, Err(Error::InvalidOffset(rejected)) if rejected == offset),
+                "{offset}"
+            );
+        }
     }
 }
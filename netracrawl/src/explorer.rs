@@ -0,0 +1,78 @@
+use common::model::Chain;
+use sha2::{Digest, Sha256};
+
+use crate::constants::{
+    ARBISCAN_API_URL, BASESCAN_API_URL, BSCSCAN_API_URL, ETHERSCAN_API_URL, ETHERSCAN_V2_API_URL,
+    POLYGONSCAN_API_URL,
+};
+
+/// Etherscan-compatible explorer API that an `EtherscanClient` talks to.
+#[derive(Debug, Clone)]
+pub struct Explorer {
+    pub chain: Chain,
+    pub base_url: String,
+    pub chain_id: u64,
+    pub native_symbol: String,
+    /// Whether the endpoint expects a `chainid` query parameter (Etherscan v2).
+    pub multichain: bool,
+}
+
+impl Explorer {
+    /// Chain-specific explorer (Etherscan, BscScan, Polygonscan, Arbiscan,
+    /// Basescan).
+    pub fn for_chain(chain: Chain) -> Self {
+        let base_url = match chain {
+            Chain::Ethereum => ETHERSCAN_API_URL,
+            Chain::Bsc => BSCSCAN_API_URL,
+            Chain::Polygon => POLYGONSCAN_API_URL,
+            Chain::Arbitrum => ARBISCAN_API_URL,
+            Chain::Base => BASESCAN_API_URL,
+        };
+
+        Self {
+            chain,
+            base_url: base_url.to_string(),
+            chain_id: chain.chain_id(),
+            native_symbol: chain.native_symbol().to_string(),
+            multichain: false,
+        }
+    }
+
+    /// Etherscan v2 multichain endpoint, selecting the chain via `chainid`.
+    pub fn multichain(chain: Chain) -> Self {
+        Self {
+            base_url: ETHERSCAN_V2_API_URL.to_string(),
+            multichain: true,
+            ..Self::for_chain(chain)
+        }
+    }
+
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Name of this explorer in local caches: `etherscan` plus a short hash
+    /// of the endpoint, so rows cached from one explorer are never served
+    /// for another.
+    pub fn cache_label(&self) -> String {
+        let digest = format!("{:x}", Sha256::digest(self.endpoint("").as_bytes()));
+        format!("etherscan-{}", &digest[..12])
+    }
+
+    pub fn endpoint(&self, query: &str) -> String {
+        let base_url = self.base_url.trim_end_matches('/');
+
+        if self.multichain {
+            format!("{base_url}?chainid={}&{query}", self.chain_id)
+        } else {
+            format!("{base_url}?{query}")
+        }
+    }
+}
+
+impl Default for Explorer {
+    fn default() -> Self {
+        Explorer::for_chain(Chain::Ethereum)
+    }
+}
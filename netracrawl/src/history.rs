@@ -0,0 +1,376 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use common::model::{AssetKind, Chain, TxResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{Error, Result},
+    etherscan::BlockRange,
+};
+
+/// Which part of a wallet's history a sync fetched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryScope {
+    /// Every asset kind.
+    All,
+    /// ERC-20 transfers only.
+    Erc20,
+}
+
+impl HistoryScope {
+    pub fn includes(&self, kind: AssetKind) -> bool {
+        match self {
+            HistoryScope::All => true,
+            HistoryScope::Erc20 => kind == AssetKind::Erc20,
+        }
+    }
+}
+
+/// Locally stored transfers of one wallet, with the block spans each scope
+/// has been synced over.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WalletHistory {
+    pub wallet: String,
+    /// Disjoint spans sorted by start block.
+    pub synced: BTreeMap<HistoryScope, Vec<BlockRange>>,
+    pub transfers: Vec<TxResponse>,
+}
+
+impl WalletHistory {
+    pub fn new(wallet: &str) -> Self {
+        Self {
+            wallet: wallet.to_ascii_lowercase(),
+            ..Self::default()
+        }
+    }
+
+    /// Block windows of `range` still to fetch for `scope`, one per gap
+    /// between the synced spans. A span's boundary block is fetched again
+    /// since an explorer may have indexed it only partially;
+    /// [`WalletHistory::merge`] drops the repeats.
+    pub fn missing(&self, scope: HistoryScope, range: BlockRange) -> Vec<BlockRange> {
+        let mut windows = vec![];
+        let mut from = range.start;
+
+        for span in self.spans(scope) {
+            if span.end < from {
+                continue;
+            }
+            if span.start > range.end {
+                break;
+            }
+            if span.start > from {
+                windows.push(BlockRange::new(from, span.start));
+            }
+            if span.end >= range.end {
+                return windows;
+            }
+            from = span.end;
+        }
+
+        windows.push(BlockRange::new(from, range.end));
+        windows
+    }
+
+    /// Spans synced for `scope`, including those synced for every kind.
+    fn spans(&self, scope: HistoryScope) -> Vec<BlockRange> {
+        let mut spans = self
+            .synced
+            .get(&HistoryScope::All)
+            .cloned()
+            .unwrap_or_default();
+        if scope != HistoryScope::All {
+            spans.extend(self.synced.get(&scope).into_iter().flatten());
+        }
+        coalesce(spans)
+    }
+
+    /// Adds `fetched` rows of `scope` synced over `window`, skipping rows
+    /// already stored. Returns how many were new. The window is recorded as
+    /// synced up to `settled` at most, the last block deep enough under the
+    /// head not to be reorganized. Stored rows of unsynced blocks in the
+    /// window are replaced by the fetched ones, since those blocks may have
+    /// changed since.
+    pub fn merge(
+        &mut self,
+        scope: HistoryScope,
+        window: BlockRange,
+        fetched: Vec<TxResponse>,
+        settled: u64,
+    ) -> usize {
+        let spans = self.spans(scope);
+        self.transfers.retain(|tx| {
+            let Ok(number) = tx.block_number.parse::<u64>() else {
+                return true;
+            };
+            !scope.includes(tx.kind)
+                || number <= window.start
+                || number > window.end
+                || spans
+                    .iter()
+                    .any(|span| span.start <= number && number <= span.end)
+        });
+
+        let mut seen: HashSet<_> = self.transfers.iter().map(TxResponse::row_key).collect();
+        let before = self.transfers.len();
+
+        for tx in fetched {
+            if seen.insert(tx.row_key()) {
+                self.transfers.push(tx);
+            }
+        }
+
+        self.transfers
+            .sort_by_key(|tx| (tx.block_number.parse::<u64>().ok(), tx.time_stamp.clone()));
+
+        if window.start <= settled {
+            let spans = self.synced.entry(scope).or_default();
+            spans.push(BlockRange::new(window.start, window.end.min(settled)));
+            *spans = coalesce(std::mem::take(spans));
+        }
+
+        self.transfers.len() - before
+    }
+
+    /// Stored rows of `scope` in `range`.
+    pub fn transfers(&self, scope: HistoryScope, range: BlockRange) -> Vec<TxResponse> {
+        self.transfers
+            .iter()
+            .filter(|tx| scope.includes(tx.kind))
+            .filter(|tx| {
+                tx.block_number
+                    .parse::<u64>()
+                    .is_ok_and(|number| number >= range.start && number <= range.end)
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+/// Directory of [`WalletHistory`] files, one per wallet, grouped by data
+/// source and chain so histories from sources that see different subsets of
+/// transfers are never mixed.
+#[derive(Debug, Clone)]
+pub struct HistoryStore {
+    root: PathBuf,
+}
+
+impl HistoryStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn path(&self, source: &str, chain: Chain, wallet: &str) -> PathBuf {
+        self.root
+            .join(source)
+            .join(chain.to_string())
+            .join(format!("{}.json", wallet.to_ascii_lowercase()))
+    }
+
+    pub fn load(&self, source: &str, chain: Chain, wallet: &str) -> Result<WalletHistory> {
+        let path = self.path(source, chain, wallet);
+        if !path.exists() {
+            return Ok(WalletHistory::new(wallet));
+        }
+
+        let text = fs::read_to_string(&path)?;
+        serde_json::from_str(&text)
+            .map_err(|e| Error::Serde(e, format!("Invalid wallet history {}", path.display())))
+    }
+
+    pub fn save(&self, source: &str, chain: Chain, history: &WalletHistory) -> Result<()> {
+        let path = self.path(source, chain, &history.wallet);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, serde_json::to_vec(history)?)?;
+        fs::rename(tmp_path, path)?;
+
+        Ok(())
+    }
+}
+
+/// `spans` sorted by start block, with overlapping or adjacent spans joined.
+fn coalesce(mut spans: Vec<BlockRange>) -> Vec<BlockRange> {
+    spans.sort_by_key(|span| span.start);
+
+    let mut joined: Vec<BlockRange> = Vec::with_capacity(spans.len());
+    for span in spans {
+        match joined.last_mut() {
+            Some(last) if span.start <= last.end.saturating_add(1) => {
+                last.end = last.end.max(span.end);
+            }
+            _ => joined.push(span),
+        }
+    }
+    joined
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WALLET: &str = "0x1111111111111111111111111111111111111111";
+
+    fn row(hash: &str, block: u64, kind: AssetKind) -> TxResponse {
+        TxResponse {
+            block_number: block.to_string(),
+            hash: hash.to_string(),
+            kind,
+            ..TxResponse::default()
+        }
+    }
+
+    fn range(start: u64, end: u64) -> BlockRange {
+        BlockRange::new(start, end)
+    }
+
+    #[test]
+    fn missing_is_the_whole_range_before_any_sync() {
+        let history = WalletHistory::new(WALLET);
+        assert_eq!(
+            history.missing(HistoryScope::All, range(10, 20)),
+            [range(10, 20)]
+        );
+    }
+
+    #[test]
+    fn disjoint_syncs_leave_the_gap_between_them_missing() {
+        let mut history = WalletHistory::new(WALLET);
+        history.merge(HistoryScope::All, range(100, 200), vec![], u64::MAX);
+        history.merge(HistoryScope::All, range(400, 500), vec![], u64::MAX);
+
+        assert_eq!(
+            history.synced[&HistoryScope::All],
+            [range(100, 200), range(400, 500)]
+        );
+        assert_eq!(
+            history.missing(HistoryScope::All, range(0, 600)),
+            [range(0, 100), range(200, 400), range(500, 600)]
+        );
+        assert!(history
+            .missing(HistoryScope::All, range(120, 180))
+            .is_empty());
+    }
+
+    #[test]
+    fn overlapping_and_adjacent_spans_are_joined() {
+        let mut history = WalletHistory::new(WALLET);
+        history.merge(HistoryScope::All, range(100, 200), vec![], u64::MAX);
+        history.merge(HistoryScope::All, range(150, 300), vec![], u64::MAX);
+        history.merge(HistoryScope::All, range(301, 400), vec![], u64::MAX);
+
+        assert_eq!(history.synced[&HistoryScope::All], [range(100, 400)]);
+    }
+
+    #[test]
+    fn erc20_scope_reuses_spans_synced_for_every_kind() {
+        let mut history = WalletHistory::new(WALLET);
+        history.merge(HistoryScope::All, range(100, 200), vec![], u64::MAX);
+        history.merge(HistoryScope::Erc20, range(200, 300), vec![], u64::MAX);
+
+        assert!(history
+            .missing(HistoryScope::Erc20, range(100, 300))
+            .is_empty());
+        assert_eq!(
+            history.missing(HistoryScope::All, range(100, 300)),
+            [range(200, 300)]
+        );
+    }
+
+    #[test]
+    fn window_past_the_settled_block_is_synced_up_to_it() {
+        let mut history = WalletHistory::new(WALLET);
+        let fetched = vec![
+            row("0xa", 120, AssetKind::Native),
+            row("0xb", 150, AssetKind::Erc20),
+        ];
+        // Past the old 99_999_999 "latest" sentinel, as on Arbitrum.
+        history.merge(HistoryScope::All, range(100, 350_000_000), fetched, 138);
+
+        assert_eq!(history.synced[&HistoryScope::All], [range(100, 138)]);
+        assert_eq!(
+            history.missing(HistoryScope::All, range(100, 200)),
+            [range(138, 200)]
+        );
+    }
+
+    #[test]
+    fn unsettled_rows_are_replaced_when_fetched_again() {
+        let mut history = WalletHistory::new(WALLET);
+        let fetched = vec![
+            row("0xa", 120, AssetKind::Native),
+            row("0xb", 150, AssetKind::Erc20),
+        ];
+        history.merge(HistoryScope::All, range(100, 160), fetched, 138);
+
+        // Block 150 was reorganized: 0xb is gone and 0xc took its place.
+        let refetched = vec![row("0xc", 151, AssetKind::Erc20)];
+        history.merge(HistoryScope::All, range(138, 200), refetched, 188);
+
+        let hashes: Vec<&str> = history
+            .transfers
+            .iter()
+            .map(|tx| tx.hash.as_str())
+            .collect();
+        assert_eq!(hashes, ["0xa", "0xc"]);
+        assert_eq!(history.synced[&HistoryScope::All], [range(100, 188)]);
+    }
+
+    #[test]
+    fn window_above_the_settled_block_is_not_synced() {
+        let mut history = WalletHistory::new(WALLET);
+        history.merge(HistoryScope::All, range(200, 300), vec![], 150);
+
+        assert!(history.synced.is_empty());
+    }
+
+    #[test]
+    fn merge_skips_rows_already_stored() {
+        let mut history = WalletHistory::new(WALLET);
+        let first = vec![
+            row("0xa", 120, AssetKind::Native),
+            row("0xa", 120, AssetKind::Internal),
+        ];
+        let again = vec![
+            row("0xA", 120, AssetKind::Native),
+            row("0xc", 130, AssetKind::Erc20),
+        ];
+
+        assert_eq!(
+            history.merge(HistoryScope::All, range(100, 125), first, u64::MAX),
+            2
+        );
+        assert_eq!(
+            history.merge(HistoryScope::All, range(120, 140), again, u64::MAX),
+            1
+        );
+        assert_eq!(
+            history.transfers(HistoryScope::Erc20, range(0, 200)).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn synced_spans_round_trip_through_json() {
+        let mut history = WalletHistory::new(WALLET);
+        history.merge(HistoryScope::All, range(100, 200), vec![], u64::MAX);
+        history.merge(HistoryScope::All, range(400, 500), vec![], u64::MAX);
+
+        let json = serde_json::to_string(&history).expect("serialize");
+        let loaded: WalletHistory = serde_json::from_str(&json).expect("deserialize");
+
+        assert_eq!(loaded.synced, history.synced);
+    }
+}
@@ -1,3 +1,13 @@
+pub mod address;
+pub mod archive;
+pub mod constants;
+pub mod endpoint;
 pub mod error;
 pub mod ethereum;
 pub mod etherscan;
+pub mod explorer;
+pub mod history;
+pub mod mempool;
+pub mod mock;
+pub mod source;
+pub mod websocket;
@@ -1,9 +1,21 @@
 mod cli;
 mod controller;
 
+use std::time::Duration;
+
 use clap::Parser;
 use cli::{Cli, Command};
-use netracrawl::error::Result;
+use common::utils::chain_path;
+use netracrawl::{
+    archive::BlockArchive,
+    error::{Error, Result},
+    ethereum::EthereumClient,
+    etherscan::{BlockRange, EtherscanClient},
+    explorer::Explorer,
+    mock::{FaultPlan, MockServer},
+    source::{FixtureSource, TransferSource},
+};
+use tokio::net::TcpListener;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -14,6 +26,104 @@ async fn main() -> Result<()> {
         Command::ScanBlock { block_number, rpc } => {
             controller::scan_block(block_number, &rpc).await?
         }
+        Command::FetchRange {
+            start_block,
+            end_block,
+            rpc,
+            batch_size,
+            concurrency,
+        } => controller::fetch_range(start_block, end_block, &rpc, batch_size, concurrency).await?,
+        Command::Archive {
+            start_block,
+            end_block,
+            rpc,
+            dir,
+            chain,
+            receipts,
+            traces,
+            batch_size,
+            concurrency,
+        } => {
+            let client = EthereumClient::new(rpc)
+                .with_chain(chain)
+                .with_archive(BlockArchive::open(chain_path(dir, chain), chain)?)
+                .with_batch_size(batch_size)
+                .with_concurrency(concurrency);
+            client.check_chain().await?;
+
+            controller::archive_range(&client, start_block..=end_block, receipts, traces).await?
+        }
+        Command::MockServe {
+            fixtures,
+            addr,
+            chain,
+            api_key,
+            rate_limit_every,
+            fail_every,
+            latency_ms,
+        } => {
+            let mut server = MockServer::new(FixtureSource::new(&fixtures).with_chain(chain))
+                .with_faults(FaultPlan {
+                    rate_limit_every,
+                    fail_every,
+                    latency: Duration::from_millis(latency_ms),
+                });
+
+            if let Some(key) = api_key {
+                server = server.with_api_key(key);
+            }
+
+            let listener = TcpListener::bind(&addr).await?;
+            println!("🧪 Serving fixtures from {fixtures} on http://{addr}");
+            server.serve(listener).await?
+        }
+        Command::Record {
+            out,
+            rpc,
+            start_block,
+            end_block,
+            receipts,
+            traces,
+            transactions,
+            wallets,
+            etherscan_key,
+            chain,
+            explorer_url,
+        } => {
+            let fixtures = FixtureSource::new(out).with_chain(chain);
+            let client = EthereumClient::new(rpc).with_chain(chain);
+            if start_block.is_some() || !transactions.is_empty() {
+                client.check_chain().await?;
+            }
+
+            if let Some(start) = start_block {
+                let end = end_block.unwrap_or(start);
+                controller::record_blocks(&client, &fixtures, start..=end, receipts, traces)
+                    .await?;
+            }
+
+            controller::record_transactions(&client, &fixtures, &transactions).await?;
+
+            if !wallets.is_empty() {
+                let key = etherscan_key.ok_or_else(|| {
+                    Error::InvalidApiKey("missing, set --etherscan-key or ETHERSCAN_API_KEY".into())
+                })?;
+
+                let mut explorer = Explorer::for_chain(chain);
+                if let Some(url) = explorer_url {
+                    explorer = explorer.with_base_url(url);
+                }
+
+                let client = EtherscanClient::new(key, explorer);
+                let end = match end_block {
+                    Some(end) => end,
+                    None => client.head_block().await?,
+                };
+                let range = BlockRange::new(start_block.unwrap_or(0), end);
+
+                controller::record_wallets(&client, &fixtures, &wallets, range).await?;
+            }
+        }
     }
 
     Ok(())
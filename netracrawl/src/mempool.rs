@@ -0,0 +1,101 @@
+use std::time::Duration;
+
+use common::model::TxEthereum;
+use tokio::time::{sleep_until, Instant};
+
+use crate::{error::Result, ethereum::EthereumClient};
+
+/// Source of transactions entering the node's mempool.
+///
+/// Polls an `eth_newPendingTransactionFilter` filter and resolves the new
+/// hashes into transactions. The filter is reinstalled whenever polling fails,
+/// since nodes expire idle filters and a failover lands on a node that never
+/// saw it.
+pub struct PendingStream {
+    filter_id: Option<String>,
+    poll_interval: Duration,
+    last_polled: Option<Instant>,
+    queued: Vec<String>,
+    /// When fetching the queued hashes last failed, to back off before
+    /// retrying them.
+    failed_at: Option<Instant>,
+}
+
+impl PendingStream {
+    pub fn new(poll_interval: Duration) -> Self {
+        Self {
+            filter_id: None,
+            poll_interval,
+            last_polled: None,
+            queued: Vec::new(),
+            failed_at: None,
+        }
+    }
+
+    /// Waits for the next non-empty batch of pending transactions. Hashes that
+    /// were already mined or dropped by the time they are fetched are skipped.
+    ///
+    /// Safe to retry after an error: polled hashes stay queued until they
+    /// are fetched. Not fully cancel-safe: hashes queued before the call was
+    /// dropped are kept, but a poll dropped while its `eth_getFilterChanges`
+    /// request is in flight loses the hashes the node already handed out,
+    /// since the node does not return them again.
+    pub async fn next_batch(&mut self, client: &EthereumClient) -> Result<Vec<TxEthereum>> {
+        while self.queued.is_empty() {
+            self.poll(client).await?;
+        }
+
+        if let Some(failed_at) = self.failed_at {
+            sleep_until(failed_at + self.poll_interval).await;
+        }
+
+        let fetched = match client.get_transactions_by_hash(&self.queued).await {
+            Ok(fetched) => fetched,
+            Err(err) => {
+                self.failed_at = Some(Instant::now());
+                return Err(err);
+            }
+        };
+        self.failed_at = None;
+        self.queued.clear();
+
+        Ok(fetched
+            .into_iter()
+            .flatten()
+            .filter(|tx| tx.block_number.is_none())
+            .collect())
+    }
+
+    async fn poll(&mut self, client: &EthereumClient) -> Result<()> {
+        if let Some(last) = self.last_polled {
+            sleep_until(last + self.poll_interval).await;
+        }
+
+        let changes = self.poll_changes(client).await;
+        self.last_polled = Some(Instant::now());
+
+        // Queued before any further await, so a caller dropping the next
+        // one still finds these hashes on the next call.
+        self.queued.extend(changes?);
+        Ok(())
+    }
+
+    async fn poll_changes(&mut self, client: &EthereumClient) -> Result<Vec<String>> {
+        let filter_id = match &self.filter_id {
+            Some(filter_id) => filter_id.clone(),
+            None => {
+                let filter_id = client.new_pending_transaction_filter().await?;
+                println!("🕳️  Installed pending transaction filter {filter_id}");
+                self.filter_id.insert(filter_id).clone()
+            }
+        };
+
+        let changes = client.get_filter_changes(&filter_id).await;
+
+        if changes.is_err() {
+            self.filter_id = None;
+        }
+
+        changes
+    }
+}
@@ -0,0 +1,594 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use common::model::AssetKind;
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    time::sleep,
+};
+
+use crate::{
+    archive::ArchiveKind,
+    constants::{ETHERSCAN_MAX_RESULT_WINDOW, MOCK_MAX_REQUEST_BYTES},
+    error::Result,
+    etherscan::{action, Closest},
+    source::{BlockSource, FixtureSource},
+};
+
+/// Failures the mock server injects, so retry, backoff and failover paths can
+/// be exercised without a flaky provider.
+#[derive(Debug, Clone, Default)]
+pub struct FaultPlan {
+    /// Every n-th request is answered with a rate-limit response.
+    pub rate_limit_every: Option<u64>,
+    /// Every n-th request fails (HTTP 500 for JSON-RPC, `NOTOK` for
+    /// Etherscan).
+    pub fail_every: Option<u64>,
+    /// Delay added before every response.
+    pub latency: Duration,
+}
+
+enum Fault {
+    RateLimit,
+    Fail,
+}
+
+/// HTTP response as sent back by the mock.
+struct Reply {
+    status: u16,
+    body: Value,
+}
+
+impl Reply {
+    fn ok(body: Value) -> Self {
+        Self { status: 200, body }
+    }
+}
+
+/// Serves a [`FixtureSource`] directory over HTTP, answering JSON-RPC `POST`s
+/// like a node and `GET`s with an Etherscan `module=account` (or
+/// `getblocknobytime`) query like the explorer API, in the shapes `EthereumClient` and `EtherscanClient` parse.
+pub struct MockServer {
+    fixtures: FixtureSource,
+    faults: FaultPlan,
+    api_key: Option<String>,
+    requests: AtomicU64,
+}
+
+impl MockServer {
+    pub fn new(fixtures: FixtureSource) -> Self {
+        Self {
+            fixtures,
+            faults: FaultPlan::default(),
+            api_key: None,
+            requests: AtomicU64::new(0),
+        }
+    }
+
+    pub fn with_faults(mut self, faults: FaultPlan) -> Self {
+        self.faults = faults;
+        self
+    }
+
+    /// Rejects Etherscan requests whose `apikey` differs from `api_key`.
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Accepts connections on `listener` until the process exits.
+    pub async fn serve(self, listener: TcpListener) -> Result<()> {
+        let server = Arc::new(self);
+
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let server = server.clone();
+
+            tokio::spawn(async move {
+                if let Err(err) = server.handle_connection(stream).await {
+                    eprintln!("⚠️  Mock connection error: {err}");
+                }
+            });
+        }
+    }
+
+    async fn handle_connection(&self, mut stream: TcpStream) -> Result<()> {
+        let Some((method, target, body)) = read_request(&mut stream).await? else {
+            return Ok(());
+        };
+
+        let count = self.requests.fetch_add(1, Ordering::Relaxed) + 1;
+        let fault = self.fault_for(count);
+        let note = match fault {
+            Some(Fault::RateLimit) => " (rate limited)",
+            Some(Fault::Fail) => " (injected failure)",
+            None => "",
+        };
+
+        if !self.faults.latency.is_zero() {
+            sleep(self.faults.latency).await;
+        }
+
+        let reply = match method.as_str() {
+            "POST" => self.rpc_reply(&body, fault),
+            "GET" => self.etherscan_reply(&target, fault),
+            _ => Reply {
+                status: 405,
+                body: json!({ "error": format!("Unsupported method {method}") }),
+            },
+        };
+
+        println!("🧪 #{count} {method} {target} -> {}{note}", reply.status);
+        write_response(&mut stream, reply).await
+    }
+
+    fn fault_for(&self, count: u64) -> Option<Fault> {
+        let hits = |every: Option<u64>| every.is_some_and(|every| count.is_multiple_of(every));
+
+        if hits(self.faults.rate_limit_every) {
+            Some(Fault::RateLimit)
+        } else if hits(self.faults.fail_every) {
+            Some(Fault::Fail)
+        } else {
+            None
+        }
+    }
+
+    // =========================
+    //        JSON-RPC
+    // =========================
+
+    fn rpc_reply(&self, body: &[u8], fault: Option<Fault>) -> Reply {
+        match fault {
+            Some(Fault::RateLimit) => {
+                return Reply {
+                    status: 429,
+                    body: rpc_error(&Value::Null, -32005, "Mock rate limit exceeded"),
+                }
+            }
+            Some(Fault::Fail) => {
+                return Reply {
+                    status: 500,
+                    body: rpc_error(&Value::Null, -32603, "Mock injected failure"),
+                }
+            }
+            None => {}
+        }
+
+        let request: Value = match serde_json::from_slice(body) {
+            Ok(request) => request,
+            Err(err) => {
+                return Reply {
+                    status: 400,
+                    body: rpc_error(&Value::Null, -32700, &format!("Parse error: {err}")),
+                }
+            }
+        };
+
+        let body = match request {
+            Value::Array(calls) => {
+                Value::Array(calls.iter().map(|call| self.rpc_call(call)).collect())
+            }
+            call => self.rpc_call(&call),
+        };
+
+        Reply::ok(body)
+    }
+
+    fn rpc_call(&self, call: &Value) -> Value {
+        let id = call.get("id").cloned().unwrap_or(Value::Null);
+        let method = call
+            .get("method")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let params = call.get("params").cloned().unwrap_or(json!([]));
+
+        match self.dispatch(method, &params) {
+            Ok(Some(result)) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Ok(None) => rpc_error(
+                &id,
+                -32601,
+                &format!("the method {method} does not exist/is not available"),
+            ),
+            Err(err) => rpc_error(&id, -32000, &err.to_string()),
+        }
+    }
+
+    /// Result of `method`, or `None` when the fixtures cannot answer it.
+    fn dispatch(&self, method: &str, params: &Value) -> Result<Option<Value>> {
+        let fixtures = &self.fixtures;
+
+        let result = match method {
+            "eth_chainId" => json!(format!("0x{:x}", fixtures.chain().chain_id())),
+            "eth_blockNumber" => match self.latest_block() {
+                Some(number) => json!(format!("0x{number:x}")),
+                None => return Ok(None),
+            },
+            "eth_getBlockByNumber" => {
+                let Some(number) = self.block_param(&params[0]) else {
+                    return Ok(Some(Value::Null));
+                };
+                let full = params[1].as_bool().unwrap_or(false);
+
+                match fixtures.read_block(ArchiveKind::Block, number)? {
+                    Some(block) if full => block,
+                    Some(block) => with_transaction_hashes(block),
+                    None => Value::Null,
+                }
+            }
+            "eth_getBlockReceipts" => self.block_payload(ArchiveKind::Receipts, &params[0])?,
+            "trace_block" => match self.block_payload(ArchiveKind::Traces, &params[0])? {
+                Value::Null => return Ok(None),
+                traces => traces,
+            },
+            "debug_traceBlockByNumber" => {
+                match self.block_payload(ArchiveKind::CallTraces, &params[0])? {
+                    Value::Null => return Ok(None),
+                    traces => traces,
+                }
+            }
+            "eth_getTransactionByHash" => {
+                let hash = params[0].as_str().unwrap_or_default();
+                self.find_transaction(hash)?.unwrap_or(Value::Null)
+            }
+            "eth_getLogs" => Value::Array(self.logs(&params[0])?),
+            "eth_newPendingTransactionFilter" => json!("0x1"),
+            "eth_getFilterChanges" => json!([]),
+            _ => return Ok(None),
+        };
+
+        Ok(Some(result))
+    }
+
+    fn latest_block(&self) -> Option<u64> {
+        self.fixtures.stored_range().map(|range| *range.end())
+    }
+
+    fn block_param(&self, param: &Value) -> Option<u64> {
+        match param.as_str()? {
+            "latest" | "safe" | "finalized" | "pending" => self.latest_block(),
+            "earliest" => self.fixtures.stored_range().map(|range| *range.start()),
+            hex => parse_hex(hex),
+        }
+    }
+
+    fn block_payload(&self, kind: ArchiveKind, param: &Value) -> Result<Value> {
+        let Some(number) = self.block_param(param) else {
+            return Ok(Value::Null);
+        };
+
+        Ok(self
+            .fixtures
+            .read_block(kind, number)?
+            .unwrap_or(Value::Null))
+    }
+
+    /// Recorded transaction, falling back to the recorded blocks.
+    fn find_transaction(&self, hash: &str) -> Result<Option<Value>> {
+        if let Some(tx) = self.fixtures.read_transaction(hash)? {
+            return Ok(Some(tx));
+        }
+
+        let Some(range) = self.fixtures.stored_range() else {
+            return Ok(None);
+        };
+
+        for number in range {
+            let Some(block) = self.fixtures.read_block(ArchiveKind::Block, number)? else {
+                continue;
+            };
+
+            let found = block["transactions"].as_array().and_then(|txs| {
+                txs.iter()
+                    .find(|tx| {
+                        tx["hash"]
+                            .as_str()
+                            .is_some_and(|h| h.eq_ignore_ascii_case(hash))
+                    })
+                    .cloned()
+            });
+
+            if found.is_some() {
+                return Ok(found);
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// `eth_getLogs` answered from recorded receipts.
+    fn logs(&self, filter: &Value) -> Result<Vec<Value>> {
+        let Some(latest) = self.latest_block() else {
+            return Ok(vec![]);
+        };
+
+        let from = self.block_param(&filter["fromBlock"]).unwrap_or(latest);
+        let to = self.block_param(&filter["toBlock"]).unwrap_or(latest);
+
+        let addresses: Vec<String> = match &filter["address"] {
+            Value::String(address) => vec![address.to_ascii_lowercase()],
+            Value::Array(addresses) => addresses
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_ascii_lowercase)
+                .collect(),
+            _ => vec![],
+        };
+        let topics = filter["topics"].as_array().cloned().unwrap_or_default();
+
+        let mut logs = vec![];
+
+        for number in from..=to.min(latest) {
+            let Some(receipts) = self.fixtures.read_block(ArchiveKind::Receipts, number)? else {
+                continue;
+            };
+
+            let receipt_logs = receipts
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|receipt| receipt["logs"].as_array())
+                .flatten();
+
+            for log in receipt_logs {
+                let address = log["address"].as_str().unwrap_or_default();
+                let address_matches = addresses.is_empty()
+                    || addresses.iter().any(|a| a.eq_ignore_ascii_case(address));
+
+                if address_matches && topics_match(&topics, log) {
+                    logs.push(log.clone());
+                }
+            }
+        }
+
+        Ok(logs)
+    }
+
+    // =========================
+    //        Etherscan
+    // =========================
+
+    fn etherscan_reply(&self, target: &str, fault: Option<Fault>) -> Reply {
+        match fault {
+            Some(Fault::RateLimit) => {
+                return Reply::ok(notok(
+                    "Max rate limit reached, please use API Key for higher rate limit",
+                ))
+            }
+            Some(Fault::Fail) => return Reply::ok(notok("Error! Mock injected failure")),
+            None => {}
+        }
+
+        let query = target
+            .split_once('?')
+            .map(|(_, query)| query)
+            .unwrap_or_default();
+        let param = |name: &str| {
+            query
+                .split('&')
+                .filter_map(|pair| pair.split_once('='))
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        };
+
+        if let Some(expected) = &self.api_key {
+            if param("apikey").as_deref() != Some(expected) {
+                return Reply::ok(notok("Invalid API Key"));
+            }
+        }
+
+        match param("module").as_deref() {
+            Some("account") => {}
+            Some("block") if param("action").as_deref() == Some("getblocknobytime") => {
+                return self.block_by_time(param("timestamp"), param("closest"));
+            }
+            _ => return Reply::ok(notok("Error! Missing Or invalid Module name")),
+        }
+
+        let requested = param("action").unwrap_or_default();
+        let Some(kind) = AssetKind::ALL
+            .into_iter()
+            .find(|kind| action(*kind) == requested)
+        else {
+            return Reply::ok(notok("Error! Missing Or invalid Action name"));
+        };
+
+        let Some(address) = param("address") else {
+            return Reply::ok(notok("Error! Missing address"));
+        };
+
+        let number = |name: &str, default: u64| {
+            param(name)
+                .and_then(|value| value.parse::<u64>().ok())
+                .unwrap_or(default)
+        };
+        let (start, end) = (number("startblock", 0), number("endblock", u64::MAX));
+        let page = number("page", 1).max(1) as usize;
+        let offset = number("offset", 10_000).max(1) as usize;
+
+        if page * offset > ETHERSCAN_MAX_RESULT_WINDOW {
+            return Reply::ok(notok(
+                "Result window is too large, PageNo x Offset size must be less than or equal to 10000",
+            ));
+        }
+
+        let mut rows = match self.fixtures.read_account(&address, kind) {
+            Ok(rows) => rows,
+            Err(err) => return Reply::ok(notok(&err.to_string())),
+        };
+
+        let row_block = |row: &Value| {
+            row["blockNumber"]
+                .as_str()
+                .and_then(|number| number.parse::<u64>().ok())
+                .unwrap_or(0)
+        };
+
+        rows.retain(|row| (start..=end).contains(&row_block(row)));
+        rows.sort_by_key(row_block);
+
+        let rows: Vec<Value> = rows
+            .into_iter()
+            .skip((page - 1) * offset)
+            .take(offset)
+            .collect();
+
+        if rows.is_empty() {
+            return Reply::ok(json!({
+                "status": "0",
+                "message": "No transactions found",
+                "result": [],
+            }));
+        }
+
+        Reply::ok(json!({ "status": "1", "message": "OK", "result": rows }))
+    }
+
+    fn block_by_time(&self, timestamp: Option<String>, closest: Option<String>) -> Reply {
+        let Some(timestamp) = timestamp.and_then(|value| value.parse::<u64>().ok()) else {
+            return Reply::ok(notok("Error! Invalid timestamp"));
+        };
+        let closest = match closest.as_deref() {
+            Some("before") => Closest::Before,
+            Some("after") => Closest::After,
+            _ => return Reply::ok(notok("Error! Invalid closest value")),
+        };
+
+        match self.fixtures.nearest_block(timestamp, closest) {
+            Ok(Some(number)) => Reply::ok(json!({
+                "status": "1",
+                "message": "OK",
+                "result": number.to_string(),
+            })),
+            Ok(None) => Reply::ok(notok("Error! No closest block found")),
+            Err(err) => Reply::ok(notok(&err.to_string())),
+        }
+    }
+}
+
+fn rpc_error(id: &Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+fn notok(result: &str) -> Value {
+    json!({ "status": "0", "message": "NOTOK", "result": result })
+}
+
+fn parse_hex(hex: &str) -> Option<u64> {
+    u64::from_str_radix(hex.trim_start_matches("0x"), 16).ok()
+}
+
+fn with_transaction_hashes(mut block: Value) -> Value {
+    if let Some(transactions) = block["transactions"].as_array_mut() {
+        for tx in transactions.iter_mut() {
+            if let Some(hash) = tx.get("hash").cloned() {
+                *tx = hash;
+            }
+        }
+    }
+
+    block
+}
+
+/// Position-wise topic filter: `null` matches anything, an array matches any
+/// of its entries.
+fn topics_match(filter: &[Value], log: &Value) -> bool {
+    let topics = log["topics"].as_array().cloned().unwrap_or_default();
+
+    filter.iter().enumerate().all(|(index, wanted)| {
+        let Some(topic) = topics.get(index).and_then(Value::as_str) else {
+            return wanted.is_null();
+        };
+
+        match wanted {
+            Value::Null => true,
+            Value::String(wanted) => wanted.eq_ignore_ascii_case(topic),
+            Value::Array(options) => options
+                .iter()
+                .filter_map(Value::as_str)
+                .any(|wanted| wanted.eq_ignore_ascii_case(topic)),
+            _ => false,
+        }
+    })
+}
+
+/// Reads one HTTP/1.1 request: method, target and body.
+async fn read_request(stream: &mut TcpStream) -> Result<Option<(String, String, Vec<u8>)>> {
+    let mut buffer = Vec::with_capacity(4096);
+    let mut chunk = [0u8; 4096];
+
+    let header_end = loop {
+        if let Some(end) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            break end + 4;
+        }
+
+        if buffer.len() > MOCK_MAX_REQUEST_BYTES {
+            return Ok(None);
+        }
+
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            return Ok(None);
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..header_end]).into_owned();
+    let mut lines = head.lines();
+
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_string();
+    let target = request_line.next().unwrap_or("/").to_string();
+
+    let content_length = lines
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+        .unwrap_or(0)
+        .min(MOCK_MAX_REQUEST_BYTES);
+
+    let mut body = buffer.split_off(header_end);
+    while body.len() < content_length {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            break;
+        }
+        body.extend_from_slice(&chunk[..read]);
+    }
+
+    Ok(Some((method, target, body)))
+}
+
+async fn write_response(stream: &mut TcpStream, reply: Reply) -> Result<()> {
+    let body = serde_json::to_vec(&reply.body)?;
+    let reason = match reply.status {
+        200 => "OK",
+        400 => "Bad Request",
+        405 => "Method Not Allowed",
+        429 => "Too Many Requests",
+        _ => "Internal Server Error",
+    };
+
+    let head = format!(
+        "HTTP/1.1 {} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        reply.status,
+        body.len()
+    );
+
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(&body).await?;
+    stream.shutdown().await?;
+
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use common::model::{AssetKind, Chain, TxResponse};
+
+use crate::{
+    constants::HISTORY_CONFIRMATIONS,
+    error::Result,
+    etherscan::{BlockRange, Closest},
+    history::{HistoryScope, HistoryStore},
+};
+
+use super::{into_history, TransferSource};
+
+/// Wraps a remote [`TransferSource`] with a [`HistoryStore`], so each run
+/// only requests the blocks a wallet has not been synced over yet. Blocks
+/// within [`HISTORY_CONFIRMATIONS`] of the head are fetched again each run.
+pub struct CachedSource {
+    inner: Box<dyn TransferSource>,
+    store: HistoryStore,
+    label: String,
+    /// Head of the inner source, looked up once per run.
+    head: Mutex<Option<u64>>,
+}
+
+impl CachedSource {
+    /// `label` names the inner source in the store layout; sources that
+    /// see different transfers must use different labels.
+    pub fn new(inner: Box<dyn TransferSource>, store: HistoryStore, label: &str) -> Self {
+        Self {
+            inner,
+            store,
+            label: label.to_string(),
+            head: Mutex::new(None),
+        }
+    }
+
+    async fn head(&self) -> Result<u64> {
+        if let Some(head) = *self.head.lock().expect("head lock") {
+            return Ok(head);
+        }
+
+        let head = self.inner.head_block().await?;
+        *self.head.lock().expect("head lock") = Some(head);
+        Ok(head)
+    }
+
+    async fn sync(
+        &self,
+        wallet: &str,
+        scope: HistoryScope,
+        range: BlockRange,
+    ) -> Result<Vec<TxResponse>> {
+        let chain = self.inner.chain();
+        let mut history = self.store.load(&self.label, chain, wallet)?;
+        let windows = history.missing(scope, range);
+
+        if windows.is_empty() {
+            println!("🗃️  {wallet}: served from local history");
+        } else {
+            let settled = self.head().await?.saturating_sub(HISTORY_CONFIRMATIONS);
+            let mut new = 0;
+            for window in windows {
+                let fetched = match scope {
+                    HistoryScope::All => self.inner.wallet_transfers(wallet, window).await?,
+                    HistoryScope::Erc20 => {
+                        self.inner.wallet_token_transfers(wallet, window).await?
+                    }
+                };
+                new += history.merge(scope, window, fetched, settled);
+            }
+
+            self.store.save(&self.label, chain, &history)?;
+            println!(
+                "🗃️  {wallet}: {new} new transfers, {} stored",
+                history.transfers.len()
+            );
+        }
+
+        Ok(into_history(history.transfers(scope, range)))
+    }
+}
+
+#[async_trait]
+impl TransferSource for CachedSource {
+    fn chain(&self) -> Chain {
+        self.inner.chain()
+    }
+
+    fn asset_kinds(&self) -> &'static [AssetKind] {
+        self.inner.asset_kinds()
+    }
+
+    async fn wallet_transfers(&self, wallet: &str, range: BlockRange) -> Result<Vec<TxResponse>> {
+        self.sync(wallet, HistoryScope::All, range).await
+    }
+
+    async fn wallet_token_transfers(
+        &self,
+        wallet: &str,
+        range: BlockRange,
+    ) -> Result<Vec<TxResponse>> {
+        self.sync(wallet, HistoryScope::Erc20, range).await
+    }
+
+    async fn block_by_time(&self, timestamp: u64, closest: Closest) -> Result<Option<u64>> {
+        self.inner.block_by_time(timestamp, closest).await
+    }
+
+    async fn head_block(&self) -> Result<u64> {
+        self.head().await
+    }
+}
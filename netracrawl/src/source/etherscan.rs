@@ -0,0 +1,78 @@
+use async_trait::async_trait;
+use common::model::{Chain, TxResponse, Wei};
+
+use crate::{
+    constants::ETHERSCAN_PAGE_SIZE,
+    error::{Error, Result},
+    etherscan::{BlockRange, Closest, EtherscanClient},
+};
+
+use super::{BalanceSource, TransferSource};
+
+#[async_trait]
+impl TransferSource for EtherscanClient {
+    fn chain(&self) -> Chain {
+        self.explorer().chain
+    }
+
+    async fn wallet_transfers(&self, wallet: &str, range: BlockRange) -> Result<Vec<TxResponse>> {
+        self.fetch_all_transfers(wallet, range, ETHERSCAN_PAGE_SIZE)
+            .await
+    }
+
+    async fn wallet_token_transfers(
+        &self,
+        wallet: &str,
+        range: BlockRange,
+    ) -> Result<Vec<TxResponse>> {
+        Ok(self
+            .fetch_all_token_transfer(wallet, range, ETHERSCAN_PAGE_SIZE)
+            .await?
+            .items)
+    }
+
+    async fn block_by_time(&self, timestamp: u64, closest: Closest) -> Result<Option<u64>> {
+        self.get_block_number_by_time(timestamp, closest).await
+    }
+}
+
+/// Only current balances: the free API has no balance history.
+#[async_trait]
+impl BalanceSource for EtherscanClient {
+    async fn native_balances(&self, wallets: &[String], at: Option<u64>) -> Result<Vec<Wei>> {
+        if at.is_some() {
+            return Err(historical());
+        }
+
+        self.fetch_balances(wallets).await
+    }
+
+    async fn token_balances(
+        &self,
+        wallet: &str,
+        tokens: &[String],
+        at: Option<u64>,
+    ) -> Result<Vec<Option<Wei>>> {
+        if at.is_some() {
+            return Err(historical());
+        }
+
+        let mut balances = Vec::with_capacity(tokens.len());
+        for token in tokens {
+            match self.fetch_token_balance(token, wallet).await {
+                Ok(balance) => balances.push(Some(balance)),
+                Err(Error::Etherscan(_)) => balances.push(None),
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(balances)
+    }
+}
+
+fn historical() -> Error {
+    Error::UnsupportedSource(
+        "etherscan".to_string(),
+        "balances at past blocks".to_string(),
+    )
+}
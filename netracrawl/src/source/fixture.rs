@@ -0,0 +1,292 @@
+use std::{
+    fs,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+
+use async_trait::async_trait;
+use common::model::{
+    AssetKind, BlockEthereum, Chain, InternalTransfer, TokenTransfer, TxEthereum, TxResponse,
+};
+use serde_json::{from_value, Value};
+
+use crate::{
+    archive::ArchiveKind,
+    error::{Error, Result},
+    etherscan::{action, BlockRange, Closest},
+};
+
+use super::{
+    in_range, into_history, involves, stored_block, stored_block_by_time,
+    stored_internal_transfers, stored_latest, stored_token_transfers, stored_transaction,
+    BlockSource, RawBlockStore, TransferSource,
+};
+
+/// Recorded API responses laid out as plain JSON files:
+///
+/// ```text
+/// account/<wallet>/<action>.json   Etherscan rows (`txlist`, `tokentx`, ...)
+/// blocks/<number>.json             eth_getBlockByNumber with full transactions
+/// receipts/<number>.json           eth_getBlockReceipts
+/// traces/<number>.json             trace_block
+/// call_traces/<number>.json        debug_traceBlockByNumber (callTracer)
+/// transactions/<hash>.json         eth_getTransactionByHash
+/// ```
+///
+/// Account files may hold either the bare `result` array or the whole
+/// Etherscan envelope. Wallets and hashes are lowercase.
+#[derive(Debug, Clone)]
+pub struct FixtureSource {
+    root: PathBuf,
+    chain: Chain,
+}
+
+impl FixtureSource {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            chain: Chain::Ethereum,
+        }
+    }
+
+    pub fn with_chain(mut self, chain: Chain) -> Self {
+        self.chain = chain;
+        self
+    }
+
+    pub fn chain(&self) -> Chain {
+        self.chain
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Path of the file holding `kind` for block `number`.
+    pub fn block_path(&self, kind: ArchiveKind, number: u64) -> PathBuf {
+        self.root
+            .join(block_dir(kind))
+            .join(format!("{number}.json"))
+    }
+
+    pub fn transaction_path(&self, hash: &str) -> PathBuf {
+        self.root
+            .join("transactions")
+            .join(format!("{}.json", hash.to_ascii_lowercase()))
+    }
+
+    pub fn account_path(&self, wallet: &str, kind: AssetKind) -> PathBuf {
+        self.root
+            .join("account")
+            .join(wallet.to_ascii_lowercase())
+            .join(format!("{}.json", action(kind)))
+    }
+
+    /// Recorded `kind` payload of block `number`.
+    pub fn read_block(&self, kind: ArchiveKind, number: u64) -> Result<Option<Value>> {
+        read_json(&self.block_path(kind, number))
+    }
+
+    pub fn read_transaction(&self, hash: &str) -> Result<Option<Value>> {
+        read_json(&self.transaction_path(hash))
+    }
+
+    /// Recorded Etherscan rows of `wallet` for `kind`, as stored.
+    pub fn read_account(&self, wallet: &str, kind: AssetKind) -> Result<Vec<Value>> {
+        let path = self.account_path(wallet, kind);
+        let Some(mut value) = read_json(&path)? else {
+            return Ok(vec![]);
+        };
+
+        if let Some(result) = value.get_mut("result") {
+            value = result.take();
+        }
+
+        from_value(value)
+            .map_err(|e| Error::Serde(e, format!("Invalid fixture {}", path.display())))
+    }
+
+    pub fn write_block(&self, kind: ArchiveKind, number: u64, value: &Value) -> Result<()> {
+        write_json(&self.block_path(kind, number), value)
+    }
+
+    pub fn write_transaction(&self, hash: &str, value: &Value) -> Result<()> {
+        write_json(&self.transaction_path(hash), value)
+    }
+
+    pub fn write_account(&self, wallet: &str, kind: AssetKind, rows: &[TxResponse]) -> Result<()> {
+        write_json(
+            &self.account_path(wallet, kind),
+            &serde_json::to_value(rows)?,
+        )
+    }
+
+    /// Recorded block closest to `timestamp` on the `closest` side, judged
+    /// from the recorded blocks and every recorded account row.
+    pub fn nearest_block(&self, timestamp: u64, closest: Closest) -> Result<Option<u64>> {
+        let mut rows = vec![];
+        for wallet in json_stems(&self.root.join("account")) {
+            for kind in AssetKind::ALL {
+                rows.extend(self.account_rows(&wallet, kind, BlockRange::default())?);
+            }
+        }
+
+        stored_block_by_time(self, &rows, timestamp, closest)
+    }
+
+    fn account_rows(
+        &self,
+        wallet: &str,
+        kind: AssetKind,
+        range: BlockRange,
+    ) -> Result<Vec<TxResponse>> {
+        let rows: Vec<TxResponse> = from_value(Value::Array(self.read_account(wallet, kind)?))
+            .map_err(|e| {
+                let path = self.account_path(wallet, kind);
+                Error::Serde(e, format!("Invalid fixture {}", path.display()))
+            })?;
+
+        Ok(rows
+            .into_iter()
+            .filter(|tx| in_range(tx, range) && involves(tx, wallet))
+            .map(|mut tx| {
+                tx.kind = kind;
+                if matches!(kind, AssetKind::Native | AssetKind::Internal) {
+                    tx.token_symbol = self.chain.native_symbol().to_string();
+                }
+                tx
+            })
+            .collect())
+    }
+}
+
+impl RawBlockStore for FixtureSource {
+    fn label(&self) -> String {
+        self.root.display().to_string()
+    }
+
+    fn raw(&self, kind: ArchiveKind, number: u64) -> Result<Option<Value>> {
+        self.read_block(kind, number)
+    }
+
+    fn raw_transaction(&self, hash: &str) -> Result<Option<Value>> {
+        self.read_transaction(hash)
+    }
+
+    fn block_numbers(&self) -> Vec<u64> {
+        json_stems(&self.root.join(block_dir(ArchiveKind::Block)))
+            .iter()
+            .filter_map(|stem| stem.parse().ok())
+            .collect()
+    }
+}
+
+#[async_trait]
+impl TransferSource for FixtureSource {
+    fn chain(&self) -> Chain {
+        self.chain
+    }
+
+    async fn wallet_transfers(&self, wallet: &str, range: BlockRange) -> Result<Vec<TxResponse>> {
+        let mut transfers = vec![];
+        for kind in AssetKind::ALL {
+            transfers.extend(self.account_rows(wallet, kind, range)?);
+        }
+        Ok(into_history(transfers))
+    }
+
+    async fn wallet_token_transfers(
+        &self,
+        wallet: &str,
+        range: BlockRange,
+    ) -> Result<Vec<TxResponse>> {
+        Ok(into_history(self.account_rows(
+            wallet,
+            AssetKind::Erc20,
+            range,
+        )?))
+    }
+
+    async fn block_by_time(&self, timestamp: u64, closest: Closest) -> Result<Option<u64>> {
+        self.nearest_block(timestamp, closest)
+    }
+}
+
+#[async_trait]
+impl BlockSource for FixtureSource {
+    fn chain(&self) -> Chain {
+        self.chain
+    }
+
+    async fn latest_block_number(&self) -> Result<u64> {
+        stored_latest(self)
+    }
+
+    async fn block(&self, number: u64) -> Result<BlockEthereum> {
+        stored_block(self, number)
+    }
+
+    async fn token_transfers(&self, number: u64) -> Result<Vec<TokenTransfer>> {
+        stored_token_transfers(self, number)
+    }
+
+    async fn internal_transfers(&self, number: u64) -> Result<Vec<InternalTransfer>> {
+        stored_internal_transfers(self, number)
+    }
+
+    async fn transaction(&self, hash: &str) -> Result<TxEthereum> {
+        stored_transaction(self, hash)
+    }
+
+    fn stored_range(&self) -> Option<RangeInclusive<u64>> {
+        RawBlockStore::block_range(self)
+    }
+}
+
+/// Directory name for `kind` under the fixture root.
+fn block_dir(kind: ArchiveKind) -> &'static str {
+    match kind {
+        ArchiveKind::Block => "blocks",
+        ArchiveKind::Receipts => "receipts",
+        ArchiveKind::Traces => "traces",
+        ArchiveKind::CallTraces => "call_traces",
+    }
+}
+
+/// File stems of the `.json` files in `dir`, or directory names when the
+/// entries are directories.
+fn json_stems(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return vec![];
+    };
+
+    entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if !path.is_dir() && path.extension()? != "json" {
+                return None;
+            }
+            Some(path.file_stem()?.to_str()?.to_string())
+        })
+        .collect()
+}
+
+fn write_json(path: &Path, value: &Value) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::write(path, serde_json::to_vec_pretty(value)?)?;
+    Ok(())
+}
+
+fn read_json(path: &Path) -> Result<Option<Value>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let text = fs::read_to_string(path)?;
+    serde_json::from_str(&text)
+        .map(Some)
+        .map_err(|e| Error::Serde(e, format!("Invalid fixture {}", path.display())))
+}
@@ -0,0 +1,227 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufRead, BufReader},
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+
+use async_trait::async_trait;
+use common::model::{
+    AssetKind, BlockEthereum, Chain, InternalTransfer, TokenTransfer, TxEthereum, TxResponse,
+};
+use serde_json::{from_value, Value};
+
+use crate::{
+    archive::ArchiveKind,
+    error::{Error, Result},
+    etherscan::{BlockRange, Closest},
+};
+
+use super::{
+    in_range, into_history, involves, stored_block, stored_block_by_time,
+    stored_internal_transfers, stored_latest, stored_token_transfers, stored_transaction,
+    BlockSource, RawBlockStore, TransferSource,
+};
+
+/// A JSON Lines dump holding any mix of:
+///
+/// - blocks, as returned by `eth_getBlockByNumber` with full transactions,
+///   optionally carrying their `receipts`, `traces` (`trace_block`) and
+///   `callTraces` (`debug_traceBlockByNumber`) under extra keys;
+/// - transfer rows in Etherscan's shape, with an optional `kind`
+///   (`native`, `internal`, `erc20`, ...; defaults to `erc20`).
+///
+/// The whole file is loaded on open.
+#[derive(Debug)]
+pub struct JsonlSource {
+    path: PathBuf,
+    chain: Chain,
+    blocks: HashMap<(ArchiveKind, u64), Value>,
+    transactions: HashMap<String, Value>,
+    transfers: Vec<TxResponse>,
+}
+
+impl JsonlSource {
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let mut source = Self {
+            path,
+            chain: Chain::Ethereum,
+            blocks: HashMap::new(),
+            transactions: HashMap::new(),
+            transfers: vec![],
+        };
+
+        let reader = BufReader::new(File::open(&source.path)?);
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let value: Value = serde_json::from_str(&line).map_err(|e| {
+                Error::Serde(
+                    e,
+                    format!("Invalid line {} of {}", index + 1, source.label()),
+                )
+            })?;
+            source.load(value, index + 1)?;
+        }
+
+        Ok(source)
+    }
+
+    pub fn with_chain(mut self, chain: Chain) -> Self {
+        self.chain = chain;
+        self
+    }
+
+    pub fn chain(&self) -> Chain {
+        self.chain
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn load(&mut self, mut value: Value, line: usize) -> Result<()> {
+        if value.get("transactions").is_none() {
+            let mut tx: TxResponse = from_value(value).map_err(|e| {
+                Error::Serde(
+                    e,
+                    format!("Invalid transfer on line {line} of {}", self.label()),
+                )
+            })?;
+
+            if matches!(tx.kind, AssetKind::Native | AssetKind::Internal)
+                && tx.token_symbol.is_empty()
+            {
+                tx.token_symbol = self.chain.native_symbol().to_string();
+            }
+
+            self.transfers.push(tx);
+            return Ok(());
+        }
+
+        let number = value
+            .get("number")
+            .and_then(Value::as_str)
+            .and_then(|hex| u64::from_str_radix(hex.trim_start_matches("0x"), 16).ok())
+            .ok_or(Error::EmptyBlockNumber)?;
+
+        let Some(block) = value.as_object_mut() else {
+            return Ok(());
+        };
+
+        for (key, kind) in [
+            ("receipts", ArchiveKind::Receipts),
+            ("traces", ArchiveKind::Traces),
+            ("callTraces", ArchiveKind::CallTraces),
+        ] {
+            if let Some(payload) = block.remove(key) {
+                self.blocks.insert((kind, number), payload);
+            }
+        }
+
+        if let Some(transactions) = block.get("transactions").and_then(Value::as_array) {
+            for tx in transactions {
+                if let Some(hash) = tx.get("hash").and_then(Value::as_str) {
+                    self.transactions
+                        .insert(hash.to_ascii_lowercase(), tx.clone());
+                }
+            }
+        }
+
+        self.blocks.insert((ArchiveKind::Block, number), value);
+        Ok(())
+    }
+
+    fn rows(&self, wallet: &str, range: BlockRange, kind: Option<AssetKind>) -> Vec<TxResponse> {
+        let rows = self
+            .transfers
+            .iter()
+            .filter(|tx| kind.is_none_or(|kind| tx.kind == kind))
+            .filter(|tx| in_range(tx, range) && involves(tx, wallet))
+            .cloned()
+            .collect();
+
+        into_history(rows)
+    }
+}
+
+impl RawBlockStore for JsonlSource {
+    fn label(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn raw(&self, kind: ArchiveKind, number: u64) -> Result<Option<Value>> {
+        Ok(self.blocks.get(&(kind, number)).cloned())
+    }
+
+    fn raw_transaction(&self, hash: &str) -> Result<Option<Value>> {
+        Ok(self.transactions.get(&hash.to_ascii_lowercase()).cloned())
+    }
+
+    fn block_numbers(&self) -> Vec<u64> {
+        self.blocks
+            .keys()
+            .filter(|(kind, _)| *kind == ArchiveKind::Block)
+            .map(|(_, number)| *number)
+            .collect()
+    }
+}
+
+#[async_trait]
+impl TransferSource for JsonlSource {
+    fn chain(&self) -> Chain {
+        self.chain
+    }
+
+    async fn wallet_transfers(&self, wallet: &str, range: BlockRange) -> Result<Vec<TxResponse>> {
+        Ok(self.rows(wallet, range, None))
+    }
+
+    async fn wallet_token_transfers(
+        &self,
+        wallet: &str,
+        range: BlockRange,
+    ) -> Result<Vec<TxResponse>> {
+        Ok(self.rows(wallet, range, Some(AssetKind::Erc20)))
+    }
+
+    async fn block_by_time(&self, timestamp: u64, closest: Closest) -> Result<Option<u64>> {
+        stored_block_by_time(self, &self.transfers, timestamp, closest)
+    }
+}
+
+#[async_trait]
+impl BlockSource for JsonlSource {
+    fn chain(&self) -> Chain {
+        self.chain
+    }
+
+    async fn latest_block_number(&self) -> Result<u64> {
+        stored_latest(self)
+    }
+
+    async fn block(&self, number: u64) -> Result<BlockEthereum> {
+        stored_block(self, number)
+    }
+
+    async fn token_transfers(&self, number: u64) -> Result<Vec<TokenTransfer>> {
+        stored_token_transfers(self, number)
+    }
+
+    async fn internal_transfers(&self, number: u64) -> Result<Vec<InternalTransfer>> {
+        stored_internal_transfers(self, number)
+    }
+
+    async fn transaction(&self, hash: &str) -> Result<TxEthereum> {
+        stored_transaction(self, hash)
+    }
+
+    fn stored_range(&self) -> Option<RangeInclusive<u64>> {
+        RawBlockStore::block_range(self)
+    }
+}
@@ -0,0 +1,415 @@
+mod cached;
+mod etherscan;
+mod fixture;
+mod jsonl;
+mod rpc;
+
+use std::{
+    fmt,
+    ops::RangeInclusive,
+    path::PathBuf,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use common::model::{
+    AssetKind, BlockEthereum, Chain, InternalTransfer, ReceiptEthereum, TokenTransfer,
+    TraceEthereum, TxEthereum, TxResponse, TxTraceEthereum, Wei,
+};
+use serde::de::DeserializeOwned;
+use serde_json::{from_value, Value};
+
+use crate::{
+    archive::{ArchiveKind, BlockArchive},
+    error::{Error, Result},
+    ethereum::EthereumClient,
+    etherscan::{BlockRange, Closest},
+};
+
+pub use cached::CachedSource;
+pub use fixture::FixtureSource;
+pub use jsonl::JsonlSource;
+
+/// Where a command reads its data from, as given to `--source`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceKind {
+    /// Etherscan-compatible explorer API.
+    Etherscan,
+    /// JSON-RPC node(s).
+    Rpc,
+    /// Local block archive, read offline.
+    Archive(PathBuf),
+    /// Directory of recorded responses, see [`FixtureSource`].
+    Fixtures(PathBuf),
+    /// JSON Lines dump, see [`JsonlSource`].
+    Jsonl(PathBuf),
+}
+
+impl FromStr for SourceKind {
+    type Err = String;
+
+    fn from_str(value: &str) -> core::result::Result<Self, Self::Err> {
+        let (kind, path) = match value.split_once(':') {
+            Some((kind, path)) => (kind, Some(PathBuf::from(path))),
+            None => (value, None),
+        };
+
+        match (kind.to_ascii_lowercase().as_str(), path) {
+            ("etherscan", None) => Ok(SourceKind::Etherscan),
+            ("rpc", None) => Ok(SourceKind::Rpc),
+            ("archive", Some(path)) => Ok(SourceKind::Archive(path)),
+            ("fixtures", Some(path)) => Ok(SourceKind::Fixtures(path)),
+            ("jsonl", Some(path)) => Ok(SourceKind::Jsonl(path)),
+            _ => Err(format!(
+                "Unknown source `{value}` (expected etherscan, rpc, archive:<dir>, fixtures:<dir> or jsonl:<file>)"
+            )),
+        }
+    }
+}
+
+impl SourceKind {
+    /// Opens a file-backed block source holding blocks of `chain`. Returns
+    /// `None` for `rpc`, which callers build from their own endpoint options.
+    pub fn open_blocks(&self, chain: Chain) -> Result<Option<Box<dyn BlockSource>>> {
+        match self {
+            SourceKind::Etherscan => Err(self.unsupported("blocks")),
+            SourceKind::Rpc => Ok(None),
+            SourceKind::Archive(dir) => {
+                let client = EthereumClient::from_endpoints([])
+                    .with_chain(chain)
+                    .with_archive(BlockArchive::open(dir, chain)?)
+                    .offline();
+                Ok(Some(Box::new(client)))
+            }
+            SourceKind::Fixtures(dir) => {
+                Ok(Some(Box::new(FixtureSource::new(dir).with_chain(chain))))
+            }
+            SourceKind::Jsonl(path) => {
+                Ok(Some(Box::new(JsonlSource::open(path)?.with_chain(chain))))
+            }
+        }
+    }
+
+    /// Opens a balance source. Only the remote sources hold balances, and
+    /// callers build those from their own options, so this returns `None`
+    /// for them and fails for the file-backed ones.
+    pub fn open_balances(&self) -> Result<Option<Box<dyn BalanceSource>>> {
+        match self {
+            SourceKind::Etherscan | SourceKind::Rpc => Ok(None),
+            _ => Err(self.unsupported("balances")),
+        }
+    }
+
+    /// Opens a file-backed transfer source for `chain`. Returns `None` for
+    /// `etherscan` and `rpc`, which callers build from their own options.
+    pub fn open_transfers(&self, chain: Chain) -> Result<Option<Box<dyn TransferSource>>> {
+        match self {
+            SourceKind::Etherscan | SourceKind::Rpc => Ok(None),
+            SourceKind::Archive(_) => Err(self.unsupported("wallet transfer history")),
+            SourceKind::Fixtures(dir) => {
+                Ok(Some(Box::new(FixtureSource::new(dir).with_chain(chain))))
+            }
+            SourceKind::Jsonl(path) => {
+                Ok(Some(Box::new(JsonlSource::open(path)?.with_chain(chain))))
+            }
+        }
+    }
+
+    pub fn unsupported(&self, what: &str) -> Error {
+        Error::UnsupportedSource(self.to_string(), what.to_string())
+    }
+}
+
+impl fmt::Display for SourceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SourceKind::Etherscan => write!(f, "etherscan"),
+            SourceKind::Rpc => write!(f, "rpc"),
+            SourceKind::Archive(path) => write!(f, "archive:{}", path.display()),
+            SourceKind::Fixtures(path) => write!(f, "fixtures:{}", path.display()),
+            SourceKind::Jsonl(path) => write!(f, "jsonl:{}", path.display()),
+        }
+    }
+}
+
+/// Per-wallet transfer history, in Etherscan's row shape.
+#[async_trait]
+pub trait TransferSource: Send + Sync {
+    fn chain(&self) -> Chain;
+
+    /// Asset kinds [`TransferSource::wallet_transfers`] can return. Sources
+    /// without a per-address index see fewer than all of them.
+    fn asset_kinds(&self) -> &'static [AssetKind] {
+        &AssetKind::ALL
+    }
+
+    /// Every transfer of `wallet` in `range` across all asset kinds, oldest
+    /// first, without failed transactions.
+    async fn wallet_transfers(&self, wallet: &str, range: BlockRange) -> Result<Vec<TxResponse>>;
+
+    /// ERC-20 transfers of `wallet` in `range`, oldest first.
+    async fn wallet_token_transfers(
+        &self,
+        wallet: &str,
+        range: BlockRange,
+    ) -> Result<Vec<TxResponse>>;
+
+    /// Block mined closest to `timestamp` (unix seconds) on the `closest`
+    /// side, or `None` when the source knows no block there.
+    async fn block_by_time(&self, timestamp: u64, closest: Closest) -> Result<Option<u64>>;
+
+    /// Newest block the source can return transfers up to: the chain head,
+    /// or the newest block held in files. Defaults to the last block mined
+    /// by now.
+    async fn head_block(&self) -> Result<u64> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+
+        self.block_by_time(now, Closest::Before)
+            .await?
+            .ok_or(Error::EmptyBlockNumber)
+    }
+}
+
+/// Native and ERC-20 balances, at the chain head or at a past block.
+#[async_trait]
+pub trait BalanceSource: Send + Sync {
+    /// Native balance of each of `wallets` at block `at` (the chain head
+    /// when `None`), in wei, in the order given.
+    async fn native_balances(&self, wallets: &[String], at: Option<u64>) -> Result<Vec<Wei>>;
+
+    /// Balance of `wallet` in each of `tokens` at block `at`, in raw token
+    /// units, in the order given. `None` where the token did not answer.
+    async fn token_balances(
+        &self,
+        wallet: &str,
+        tokens: &[String],
+        at: Option<u64>,
+    ) -> Result<Vec<Option<Wei>>>;
+}
+
+/// Blocks and the per-block data derived from them.
+#[async_trait]
+pub trait BlockSource: Send + Sync {
+    fn chain(&self) -> Chain;
+
+    async fn latest_block_number(&self) -> Result<u64>;
+
+    async fn block(&self, number: u64) -> Result<BlockEthereum>;
+
+    /// Blocks in `range`, in ascending order.
+    async fn blocks(&self, range: RangeInclusive<u64>) -> Result<Vec<BlockEthereum>> {
+        let mut blocks = Vec::new();
+        for number in range {
+            blocks.push(self.block(number).await?);
+        }
+        Ok(blocks)
+    }
+
+    /// Canonical hash of block `number`.
+    async fn block_hash(&self, number: u64) -> Result<String> {
+        Ok(self.block(number).await?.hash)
+    }
+
+    /// Cross-checks a block hash with other providers, where the source has
+    /// any.
+    async fn verify_block_hash(&self, _number: u64, _expected: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn token_transfers(&self, number: u64) -> Result<Vec<TokenTransfer>>;
+
+    async fn internal_transfers(&self, number: u64) -> Result<Vec<InternalTransfer>>;
+
+    async fn transaction(&self, hash: &str) -> Result<TxEthereum>;
+
+    /// Looks up `hashes`, with `None` for transactions the source does not
+    /// know.
+    async fn transactions(&self, hashes: &[String]) -> Result<Vec<Option<TxEthereum>>> {
+        let mut transactions = Vec::with_capacity(hashes.len());
+
+        for hash in hashes {
+            match self.transaction(hash).await {
+                Ok(tx) => transactions.push(Some(tx)),
+                Err(Error::TxNotFound(_)) => transactions.push(None),
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(transactions)
+    }
+
+    /// Drops any cached copy of a block that was reorganized out.
+    fn forget(&self, _number: u64) -> Result<()> {
+        Ok(())
+    }
+
+    /// Lowest and highest block held locally, for file-backed sources.
+    fn stored_range(&self) -> Option<RangeInclusive<u64>> {
+        None
+    }
+}
+
+/// Raw JSON-RPC payloads kept in local files, keyed like the block archive.
+/// Shared by the file-backed sources to decode blocks the way the RPC client
+/// does.
+trait RawBlockStore {
+    /// Label used in error messages.
+    fn label(&self) -> String;
+
+    fn raw(&self, kind: ArchiveKind, number: u64) -> Result<Option<Value>>;
+
+    fn raw_transaction(&self, hash: &str) -> Result<Option<Value>>;
+
+    /// Numbers of the stored blocks, in no particular order.
+    fn block_numbers(&self) -> Vec<u64>;
+
+    fn block_range(&self) -> Option<RangeInclusive<u64>> {
+        let numbers = self.block_numbers();
+        Some(*numbers.iter().min()?..=*numbers.iter().max()?)
+    }
+}
+
+fn stored<T, S>(store: &S, kind: ArchiveKind, number: u64) -> Result<Option<T>>
+where
+    T: DeserializeOwned,
+    S: RawBlockStore + ?Sized,
+{
+    store
+        .raw(kind, number)?
+        .map(|value| {
+            from_value(value).map_err(|e| {
+                Error::Serde(
+                    e,
+                    format!("Invalid {kind} of block {number} in {}", store.label()),
+                )
+            })
+        })
+        .transpose()
+}
+
+fn stored_block<S>(store: &S, number: u64) -> Result<BlockEthereum>
+where
+    S: RawBlockStore + ?Sized,
+{
+    stored(store, ArchiveKind::Block, number)?
+        .ok_or_else(|| Error::MissingFixture(format!("block {number} in {}", store.label())))
+}
+
+fn stored_latest<S>(store: &S) -> Result<u64>
+where
+    S: RawBlockStore + ?Sized,
+{
+    store
+        .block_range()
+        .map(|range| *range.end())
+        .ok_or(Error::EmptyBlockNumber)
+}
+
+fn stored_token_transfers<S>(store: &S, number: u64) -> Result<Vec<TokenTransfer>>
+where
+    S: RawBlockStore + ?Sized,
+{
+    let receipts: Vec<ReceiptEthereum> =
+        stored(store, ArchiveKind::Receipts, number)?.ok_or_else(|| {
+            Error::MissingFixture(format!("receipts of block {number} in {}", store.label()))
+        })?;
+
+    Ok(TokenTransfer::from_receipts(&receipts))
+}
+
+fn stored_internal_transfers<S>(store: &S, number: u64) -> Result<Vec<InternalTransfer>>
+where
+    S: RawBlockStore + ?Sized,
+{
+    if let Some(traces) = stored::<Vec<TraceEthereum>, _>(store, ArchiveKind::Traces, number)? {
+        return Ok(InternalTransfer::from_traces(&traces));
+    }
+
+    let traces: Vec<TxTraceEthereum> =
+        stored(store, ArchiveKind::CallTraces, number)?.ok_or_else(|| {
+            Error::MissingFixture(format!("traces of block {number} in {}", store.label()))
+        })?;
+
+    let hashes: Vec<String> = stored_block(store, number)?
+        .transactions
+        .into_iter()
+        .map(|tx| tx.hash)
+        .collect();
+
+    Ok(InternalTransfer::from_call_traces(&traces, &hashes, number))
+}
+
+fn stored_transaction<S>(store: &S, hash: &str) -> Result<TxEthereum>
+where
+    S: RawBlockStore + ?Sized,
+{
+    let value = store
+        .raw_transaction(hash)?
+        .ok_or_else(|| Error::TxNotFound(hash.to_string()))?;
+
+    from_value(value).map_err(|e| Error::Serde(e, format!("Failed to parse transaction: {hash}")))
+}
+
+/// Resolves a timestamp against the blocks and transfer rows a file-backed
+/// source holds. Only blocks with data are known, so the answer is the
+/// nearest such block rather than the chain's.
+fn stored_block_by_time<S>(
+    store: &S,
+    rows: &[TxResponse],
+    timestamp: u64,
+    closest: Closest,
+) -> Result<Option<u64>>
+where
+    S: RawBlockStore + ?Sized,
+{
+    let mut points = Vec::new();
+
+    for number in store.block_numbers() {
+        let block = stored_block(store, number)?;
+        if let Ok(mined) = u64::from_str_radix(block.timestamp.trim_start_matches("0x"), 16) {
+            points.push((number, mined));
+        }
+    }
+
+    points.extend(
+        rows.iter()
+            .filter_map(|tx| Some((tx.block_number.parse().ok()?, tx.time_stamp.parse().ok()?))),
+    );
+
+    let found = match closest {
+        Closest::Before => points
+            .into_iter()
+            .filter(|(_, mined)| *mined <= timestamp)
+            .map(|(number, _)| number)
+            .max(),
+        Closest::After => points
+            .into_iter()
+            .filter(|(_, mined)| *mined >= timestamp)
+            .map(|(number, _)| number)
+            .min(),
+    };
+
+    Ok(found)
+}
+
+/// Drops failed rows and orders them like [`EtherscanClient::fetch_all_transfers`].
+///
+/// [`EtherscanClient::fetch_all_transfers`]: crate::etherscan::EtherscanClient::fetch_all_transfers
+fn into_history(mut transfers: Vec<TxResponse>) -> Vec<TxResponse> {
+    transfers.retain(|tx| !tx.is_failed());
+    transfers.sort_by_key(|tx| (tx.block_number.parse::<u64>().ok(), tx.time_stamp.clone()));
+    transfers
+}
+
+fn in_range(tx: &TxResponse, range: BlockRange) -> bool {
+    tx.block_number
+        .parse::<u64>()
+        .is_ok_and(|number| number >= range.start && number <= range.end)
+}
+
+fn involves(tx: &TxResponse, wallet: &str) -> bool {
+    tx.from.eq_ignore_ascii_case(wallet) || tx.to.eq_ignore_ascii_case(wallet)
+}
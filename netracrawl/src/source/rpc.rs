@@ -0,0 +1,161 @@
+use std::ops::RangeInclusive;
+
+use async_trait::async_trait;
+use common::model::{
+    AssetKind, BlockEthereum, Chain, InternalTransfer, TokenTransfer, TxEthereum, TxResponse, Wei,
+};
+
+use crate::{
+    archive::BlockArchive,
+    error::Result,
+    ethereum::EthereumClient,
+    etherscan::{BlockRange, Closest},
+};
+
+use super::{into_history, BalanceSource, BlockSource, TransferSource};
+
+#[async_trait]
+impl BlockSource for EthereumClient {
+    fn chain(&self) -> Chain {
+        EthereumClient::chain(self)
+    }
+
+    async fn latest_block_number(&self) -> Result<u64> {
+        self.get_latest_block_number().await
+    }
+
+    async fn block(&self, number: u64) -> Result<BlockEthereum> {
+        self.get_block_by_number(number).await
+    }
+
+    async fn blocks(&self, range: RangeInclusive<u64>) -> Result<Vec<BlockEthereum>> {
+        self.get_blocks_in_range(range).await
+    }
+
+    async fn block_hash(&self, number: u64) -> Result<String> {
+        self.get_block_hash(number).await
+    }
+
+    async fn verify_block_hash(&self, number: u64, expected: &str) -> Result<()> {
+        EthereumClient::verify_block_hash(self, number, expected).await
+    }
+
+    async fn token_transfers(&self, number: u64) -> Result<Vec<TokenTransfer>> {
+        self.get_token_transfers(number).await
+    }
+
+    async fn internal_transfers(&self, number: u64) -> Result<Vec<InternalTransfer>> {
+        self.get_internal_transfers(number).await
+    }
+
+    async fn transaction(&self, hash: &str) -> Result<TxEthereum> {
+        self.get_transaction_by_hash(hash).await
+    }
+
+    async fn transactions(&self, hashes: &[String]) -> Result<Vec<Option<TxEthereum>>> {
+        self.get_transactions_by_hash(hashes).await
+    }
+
+    fn forget(&self, number: u64) -> Result<()> {
+        self.evict_archived(number)
+    }
+
+    fn stored_range(&self) -> Option<RangeInclusive<u64>> {
+        self.archive().and_then(BlockArchive::block_range)
+    }
+}
+
+#[async_trait]
+impl BalanceSource for EthereumClient {
+    async fn native_balances(&self, wallets: &[String], at: Option<u64>) -> Result<Vec<Wei>> {
+        self.get_balances(wallets, at).await
+    }
+
+    async fn token_balances(
+        &self,
+        wallet: &str,
+        tokens: &[String],
+        at: Option<u64>,
+    ) -> Result<Vec<Option<Wei>>> {
+        self.get_token_balances(wallet, tokens, at).await
+    }
+}
+
+/// A node has no per-address index, so wallet history over JSON-RPC is
+/// limited to ERC-20 `Transfer` logs, which index both parties.
+#[async_trait]
+impl TransferSource for EthereumClient {
+    fn chain(&self) -> Chain {
+        EthereumClient::chain(self)
+    }
+
+    fn asset_kinds(&self) -> &'static [AssetKind] {
+        &[AssetKind::Erc20]
+    }
+
+    async fn head_block(&self) -> Result<u64> {
+        self.get_latest_block_number().await
+    }
+
+    async fn wallet_transfers(&self, wallet: &str, range: BlockRange) -> Result<Vec<TxResponse>> {
+        self.wallet_token_transfers(wallet, range).await
+    }
+
+    async fn wallet_token_transfers(
+        &self,
+        wallet: &str,
+        range: BlockRange,
+    ) -> Result<Vec<TxResponse>> {
+        let end = range.end.min(self.get_latest_block_number().await?);
+        if range.start > end {
+            return Ok(vec![]);
+        }
+
+        let transfers = self
+            .get_wallet_token_transfers(wallet, range.start..=end)
+            .await?;
+
+        let mut numbers: Vec<u64> = transfers.iter().map(|t| t.block_number).collect();
+        numbers.sort_unstable();
+        numbers.dedup();
+
+        let mut contracts: Vec<String> = transfers
+            .iter()
+            .map(|t| t.contract_address.clone())
+            .collect();
+        contracts.sort_unstable();
+        contracts.dedup();
+
+        let timestamps = self.get_block_timestamps(&numbers).await?;
+        let symbols = self.get_token_symbols(&contracts).await?;
+
+        let rows = transfers
+            .into_iter()
+            .map(|transfer| TxResponse {
+                block_number: transfer.block_number.to_string(),
+                time_stamp: timestamps
+                    .get(&transfer.block_number)
+                    .map(u64::to_string)
+                    .unwrap_or_default(),
+                token_symbol: symbols
+                    .get(&transfer.contract_address)
+                    .cloned()
+                    .unwrap_or_default(),
+                from: transfer.from,
+                to: transfer.to,
+                value: transfer.value.to_string(),
+                contract_address: transfer.contract_address,
+                hash: transfer.hash,
+                log_index: Some(transfer.log_index.to_string()),
+                kind: AssetKind::Erc20,
+                ..Default::default()
+            })
+            .collect();
+
+        Ok(into_history(rows))
+    }
+
+    async fn block_by_time(&self, timestamp: u64, closest: Closest) -> Result<Option<u64>> {
+        self.get_block_number_by_time(timestamp, closest).await
+    }
+}
@@ -0,0 +1,204 @@
+use std::time::Duration;
+
+use futures::{future::BoxFuture, FutureExt, SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio::{
+    net::TcpStream,
+    time::{sleep_until, timeout, timeout_at, Instant},
+};
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+use crate::{
+    constants::{WS_IDLE_TIMEOUT_SECS, WS_MAX_RECONNECT_SECS},
+    error::{Error, Result},
+    source::BlockSource,
+};
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Source of new chain heads.
+///
+/// Prefers an `eth_subscribe("newHeads")` WebSocket subscription and
+/// reconnects with exponential backoff when it drops or goes quiet. While the
+/// socket is down, heads are polled with `eth_blockNumber` instead.
+///
+/// [`HeadWatcher::next_head`] is cancel safe: the idle deadline and any
+/// subscription handshake in flight live on the watcher, so a caller racing
+/// it against other streams neither resets the one nor aborts the other.
+pub struct HeadWatcher {
+    ws_url: Option<String>,
+    socket: Option<Socket>,
+    connecting: Option<BoxFuture<'static, Result<Socket>>>,
+    last_head: Instant,
+    poll_interval: Duration,
+    reconnect_delay: Duration,
+    reconnect_at: Instant,
+    last_polled: Option<Instant>,
+}
+
+impl HeadWatcher {
+    pub fn new(ws_url: Option<String>, poll_interval: Duration) -> Self {
+        Self {
+            ws_url,
+            socket: None,
+            connecting: None,
+            last_head: Instant::now(),
+            poll_interval,
+            reconnect_delay: Duration::from_secs(1),
+            reconnect_at: Instant::now(),
+            last_polled: None,
+        }
+    }
+
+    /// Waits for the next head and returns its block number. Heads may skip
+    /// numbers (e.g. across a reconnect), so callers should fill the gap.
+    pub async fn next_head(&mut self, source: &dyn BlockSource) -> Result<u64> {
+        loop {
+            if self.socket.is_none() && Instant::now() >= self.reconnect_at {
+                self.reconnect().await;
+            }
+
+            let Some(socket) = self.socket.as_mut() else {
+                return self.poll(source).await;
+            };
+
+            // Measured from the last head rather than per call, so being
+            // cancelled by the caller does not restart the idle timeout.
+            let idle_deadline = self.last_head + Duration::from_secs(WS_IDLE_TIMEOUT_SECS);
+
+            match timeout_at(idle_deadline, socket.next()).await {
+                Ok(Some(Ok(Message::Text(text)))) => {
+                    if let Some(number) = parse_head(&text) {
+                        self.last_head = Instant::now();
+                        return Ok(number);
+                    }
+                }
+                Ok(Some(Ok(Message::Ping(payload)))) => {
+                    let _ = socket.send(Message::Pong(payload)).await;
+                }
+                Ok(Some(Ok(Message::Close(_)))) | Ok(None) => {
+                    self.disconnect("connection closed");
+                }
+                Ok(Some(Ok(_))) => {}
+                Ok(Some(Err(err))) => self.disconnect(&err.to_string()),
+                Err(_) => self.disconnect("no new heads received"),
+            }
+        }
+    }
+
+    async fn reconnect(&mut self) {
+        let Some(url) = self.ws_url.clone() else {
+            return;
+        };
+
+        let connecting = self
+            .connecting
+            .get_or_insert_with(|| subscribe(url.clone()).boxed());
+        let subscribed = connecting.await;
+        self.connecting = None;
+
+        match subscribed {
+            Ok(socket) => {
+                println!("🔌 Subscribed to newHeads via {url}");
+                self.socket = Some(socket);
+                self.last_head = Instant::now();
+                self.reconnect_delay = Duration::from_secs(1);
+            }
+            Err(err) => {
+                eprintln!(
+                    "⚠️  WebSocket subscription failed ({err}), polling for {}s",
+                    self.reconnect_delay.as_secs()
+                );
+                self.schedule_reconnect();
+            }
+        }
+    }
+
+    fn disconnect(&mut self, reason: &str) {
+        eprintln!("⚠️  WebSocket dropped ({reason}), reconnecting");
+        self.socket = None;
+        self.schedule_reconnect();
+    }
+
+    fn schedule_reconnect(&mut self) {
+        self.reconnect_at = Instant::now() + self.reconnect_delay;
+        self.reconnect_delay =
+            (self.reconnect_delay * 2).min(Duration::from_secs(WS_MAX_RECONNECT_SECS));
+    }
+
+    async fn poll(&mut self, source: &dyn BlockSource) -> Result<u64> {
+        if let Some(last) = self.last_polled {
+            let mut next_poll = last + self.poll_interval;
+
+            if self.ws_url.is_some() {
+                next_poll = next_poll.min(self.reconnect_at);
+            }
+
+            sleep_until(next_poll).await;
+        }
+
+        // Only stamped once the request completes, so a poll cancelled
+        // mid-flight is retried straight away.
+        let head = source.latest_block_number().await;
+        self.last_polled = Some(Instant::now());
+        head
+    }
+}
+
+async fn subscribe(url: String) -> Result<Socket> {
+    let (mut socket, _) = connect_async(url).await?;
+
+    let request = json!({
+        "jsonrpc": "2.0",
+        "method": "eth_subscribe",
+        "params": ["newHeads"],
+        "id": 1
+    });
+
+    socket.send(Message::text(request.to_string())).await?;
+
+    // The first reply confirms the subscription id or carries an error.
+    loop {
+        let reply = timeout(Duration::from_secs(WS_IDLE_TIMEOUT_SECS), socket.next())
+            .await
+            .map_err(|_| Error::Rpc(0, "eth_subscribe timed out".to_string()))?;
+
+        match reply {
+            Some(Ok(Message::Text(text))) => {
+                let response: Value = serde_json::from_str(&text)?;
+
+                if let Some(error) = response.get("error") {
+                    let code = error.get("code").and_then(Value::as_i64).unwrap_or(0);
+                    let message = error
+                        .get("message")
+                        .and_then(Value::as_str)
+                        .unwrap_or("Unknown error");
+                    return Err(Error::Rpc(code, message.to_string()));
+                }
+
+                if response.get("result").is_some() {
+                    return Ok(socket);
+                }
+            }
+            Some(Ok(_)) => {}
+            Some(Err(err)) => return Err(err.into()),
+            None => {
+                return Err(Error::Rpc(
+                    0,
+                    "WebSocket closed during subscribe".to_string(),
+                ));
+            }
+        }
+    }
+}
+
+fn parse_head(text: &str) -> Option<u64> {
+    let message: Value = serde_json::from_str(text).ok()?;
+
+    if message.get("method")?.as_str()? != "eth_subscription" {
+        return None;
+    }
+
+    let number = message.pointer("/params/result/number")?.as_str()?;
+    u64::from_str_radix(number.trim_start_matches("0x"), 16).ok()
+}
@@ -1,23 +1,37 @@
 use std::collections::HashMap;
 
-use common::model::FlowSummary;
+use common::model::{Chain, FlowStatus, FlowSummary, TxCategory, Wei};
 use netrascan::classification::TxClassified;
 
-pub fn summarize_block(classified: &[TxClassified]) -> FlowSummary {
-    let totals = classified.iter().fold(HashMap::new(), |mut acc, tx| {
-        *acc.entry(tx.category).or_default() += tx.value;
-        acc
-    });
+pub fn summarize_block(
+    chain: Chain,
+    block_number: u64,
+    block_hash: &str,
+    timestamp: u64,
+    classified: &[TxClassified],
+) -> FlowSummary {
+    let mut totals: HashMap<TxCategory, Wei> = HashMap::new();
+    let mut token_totals: HashMap<String, HashMap<TxCategory, Wei>> = HashMap::new();
 
-    let (block_number, timestamp) = classified
-        .first()
-        .map(|tx| (tx.block_number, tx.timestamp))
-        .unwrap_or((0, 0));
+    for tx in classified {
+        let bucket = match &tx.token {
+            Some(token) => token_totals.entry(token.clone()).or_default(),
+            None => &mut totals,
+        };
+
+        let total = bucket.entry(tx.category).or_default();
+        *total = total.saturating_add(tx.value);
+    }
 
     FlowSummary {
+        chain,
         block_number,
+        block_hash: block_hash.to_string(),
         timestamp,
         category_totals: totals,
+        token_totals,
         tx_count: classified.len(),
+        status: FlowStatus::Confirmed,
+        provenance: None,
     }
 }
@@ -0,0 +1,64 @@
+use std::{
+    fs::{create_dir_all, read_to_string, rename, File},
+    io::Write,
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{from_str, to_string_pretty};
+
+use crate::error::{Error, Result};
+
+/// Last block whose flows were written, persisted so `watch` can resume.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub block_number: u64,
+    pub block_hash: String,
+}
+
+impl Checkpoint {
+    pub fn new(block_number: u64, block_hash: impl Into<String>) -> Self {
+        Self {
+            block_number,
+            block_hash: block_hash.into(),
+        }
+    }
+
+    pub fn load<P>(path: P) -> Result<Option<Checkpoint>>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let json = read_to_string(path)
+            .map_err(|err| Error::Io(err, "❌ Failed to read checkpoint".to_string()))?;
+
+        Ok(Some(from_str(&json)?))
+    }
+
+    /// Writes through a temporary file so a crash never leaves a torn checkpoint.
+    pub fn save<P>(&self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+
+        if let Some(dir) = path.parent() {
+            create_dir_all(dir)?;
+        }
+
+        let tmp = path.with_extension("json.tmp");
+        let mut file = File::create(&tmp)?;
+        file.write_all(to_string_pretty(self)?.as_bytes())?;
+        file.sync_all()?;
+
+        rename(&tmp, path)
+            .map_err(|err| Error::Io(err, "❌ Failed to write checkpoint".to_string()))?;
+
+        Ok(())
+    }
+}
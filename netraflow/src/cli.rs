@@ -1,14 +1,19 @@
+use std::str::FromStr;
+
 use clap::{Parser, Subcommand};
+use common::{model::Chain, utils::DEFAULT_LABEL_FILES};
+use netracrawl::{constants::RPC_TIMEOUT_SECS, source::SourceKind};
 
-/// 📊 `netraflow`: Real-time Ethereum capital flow tracker
+/// 📊 `netraflow`: Real-time EVM capital flow tracker
 ///
-/// Monitors on-chain transaction data and aggregates capital movement into
-/// categories like Domestic, Foreign, Bridge, and Unknown.
+/// Monitors on-chain transaction data on Ethereum, BSC and EVM L2s and
+/// aggregates capital movement into categories like Domestic, Foreign,
+/// Bridge, and Unknown.
 #[derive(Parser)]
 #[command(name = "netraflow")]
 #[command(author = "Garunetra Labs")]
 #[command(version = "0.1.0")]
-#[command(about = "Real-time Ethereum capital flow aggregator")]
+#[command(about = "Real-time EVM capital flow aggregator")]
 pub struct Cli {
     #[command(subcommand)]
     pub command: Command,
@@ -16,15 +21,68 @@ pub struct Cli {
 
 #[derive(Subcommand)]
 pub enum Command {
-    /// Start real-time monitoring and aggregation of Ethereum transaction flows
+    /// Start real-time monitoring and aggregation of transaction flows on one
+    /// or more EVM chains
     Watch {
+        #[arg(
+            long,
+            default_value = "rpc",
+            env = "NETRA_SOURCE",
+            help = "Where to read blocks from: rpc, archive:<dir>, fixtures:<dir> or jsonl:<file> (several chains need rpc)"
+        )]
+        source: SourceKind,
+
+        #[arg(
+            long = "chain",
+            default_value = "ethereum",
+            value_delimiter = ',',
+            env = "NETRA_CHAINS",
+            help = "Chains to follow at once, comma-separated (ethereum, bsc, polygon, arbitrum, base); each writes its own report and checkpoint"
+        )]
+        chains: Vec<Chain>,
+
         #[arg(
             long,
             default_value = "https://rpc.ankr.com/eth/6570a5941c65a2a7deffce485080569aa0bf85c6dc4b0f1dc95002557569af40",
-            help = "Ethereum RPC endpoint (e.g. Ankr, Alchemy, etc.)",
+            value_delimiter = ',',
+            help = "Ethereum RPC endpoints in failover order, comma-separated (e.g. Ankr, Alchemy, etc.)",
             env = "ETH_RPC_URL"
         )]
-        rpc: String,
+        rpc: Vec<String>,
+
+        #[arg(
+            long,
+            value_name = "CHAIN=URL",
+            help = "RPC endpoint for one chain, repeatable for failover; chains without one use --rpc (ethereum) or a public endpoint"
+        )]
+        chain_rpc: Vec<ChainUrl>,
+
+        #[arg(
+            long,
+            help = "WebSocket RPC endpoint for newHeads subscriptions on Ethereum; polls when unset or down",
+            env = "ETH_WS_URL"
+        )]
+        ws: Option<String>,
+
+        #[arg(
+            long,
+            value_name = "CHAIN=URL",
+            help = "WebSocket endpoint for newHeads subscriptions on one chain"
+        )]
+        chain_ws: Vec<ChainUrl>,
+
+        #[arg(
+            long,
+            help = "Number of providers that must agree on a block hash before it is recorded, up to the number of endpoints (rpc source only; default 1)"
+        )]
+        quorum: Option<usize>,
+
+        #[arg(
+            long,
+            default_value_t = RPC_TIMEOUT_SECS,
+            help = "Per-request RPC timeout in seconds"
+        )]
+        rpc_timeout: u64,
 
         #[arg(
             long,
@@ -33,12 +91,133 @@ pub enum Command {
         )]
         heuristics: String,
 
+        #[arg(
+            long,
+            value_delimiter = ',',
+            default_value = DEFAULT_LABEL_FILES,
+            help = "Region-tagged CEX label files deciding Domestic vs Foreign, comma-separated"
+        )]
+        labels: Vec<String>,
+
         #[arg(
             long,
             default_value = "netraflow/data/flow.jsonl",
-            help = "Path to store aggregated flow reports"
+            help = "Path to store aggregated flow reports; chains other than ethereum write next to it (flow-bsc.jsonl)"
+        )]
+        out: String,
+
+        #[arg(
+            long,
+            default_value = "netraflow/data/checkpoint.json",
+            help = "Path to the last processed block checkpoint used to resume and backfill, suffixed per chain like --out"
+        )]
+        checkpoint: String,
+
+        #[arg(
+            long,
+            help = "Block to start from when no checkpoint exists (defaults to the chain head, single chain only)"
+        )]
+        start_block: Option<u64>,
+
+        #[arg(
+            long,
+            default_value_t = 0,
+            help = "Blocks to wait below the head before a block's flows are recorded as final"
+        )]
+        confirmations: u64,
+
+        #[arg(
+            long,
+            help = "Skip ERC-20 Transfer log decoding and only aggregate native value"
+        )]
+        native_only: bool,
+
+        #[arg(
+            long,
+            help = "Also stream mempool transactions and raise provisional alerts before they are mined (rpc source only)"
+        )]
+        pending: bool,
+
+        #[arg(
+            long,
+            help = "Trace internal calls (trace_block, or debug_traceBlockByNumber) so ETH moved by contracts is aggregated; needs a tracing node"
+        )]
+        trace_internal: bool,
+
+        #[arg(
+            long,
+            env = "NETRA_ARCHIVE_DIR",
+            help = "Local block archive to read first and fill with fetched blocks, receipts and traces"
+        )]
+        archive: Option<String>,
+    },
+
+    /// Re-classify archived blocks offline, e.g. after changing heuristics
+    Replay {
+        #[arg(
+            long,
+            default_value = "archive:netracrawl/data/archive",
+            env = "NETRA_SOURCE",
+            help = "Stored blocks to replay: archive:<dir> (filled by `watch --archive` or `netracrawl archive`; chains other than Ethereum read <dir>-<chain>), fixtures:<dir> or jsonl:<file>"
+        )]
+        source: SourceKind,
+
+        #[arg(
+            long,
+            default_value_t = Chain::Ethereum,
+            help = "Chain the stored blocks belong to"
+        )]
+        chain: Chain,
+
+        #[arg(
+            long,
+            default_value = "netrascan/data/heuristics/heuristics.json",
+            help = "Path to heuristic file (JSON format)"
+        )]
+        heuristics: String,
+
+        #[arg(
+            long,
+            value_delimiter = ',',
+            default_value = DEFAULT_LABEL_FILES,
+            help = "Region-tagged CEX label files deciding Domestic vs Foreign, comma-separated"
+        )]
+        labels: Vec<String>,
+
+        #[arg(
+            long,
+            default_value = "netraflow/data/replay.jsonl",
+            help = "Path to store re-aggregated flow reports"
         )]
         out: String,
+
+        #[arg(
+            long,
+            default_value = "netraflow/data/replay-checkpoint.json",
+            help = "Path to the replay checkpoint, so an interrupted replay resumes"
+        )]
+        checkpoint: String,
+
+        #[arg(
+            long,
+            help = "First block to replay (defaults to the oldest stored block)"
+        )]
+        start_block: Option<u64>,
+
+        #[arg(
+            long,
+            help = "Last block to replay (defaults to the newest stored block)"
+        )]
+        end_block: Option<u64>,
+
+        #[arg(
+            long,
+            help = "Skip ERC-20 Transfer log decoding and only aggregate native value"
+        )]
+        native_only: bool,
+
+        #[arg(long, help = "Include stored internal call traces")]
+        trace_internal: bool,
     },
 
     /// Show latest aggregated capital flow summary
@@ -50,6 +229,13 @@ pub enum Command {
         )]
         input: String,
 
+        #[arg(
+            long,
+            default_value_t = Chain::Ethereum,
+            help = "Chain whose report to read, next to --input"
+        )]
+        chain: Chain,
+
         #[arg(long, default_value_t = 10, help = "Number of latest blocks to show")]
         latest: usize,
     },
@@ -64,3 +250,25 @@ pub enum Command {
         dir: String,
     },
 }
+
+/// Endpoint for a single chain, given as `CHAIN=URL`.
+#[derive(Debug, Clone)]
+pub struct ChainUrl {
+    pub chain: Chain,
+    pub url: String,
+}
+
+impl FromStr for ChainUrl {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (chain, url) = value
+            .split_once('=')
+            .ok_or_else(|| format!("Expected CHAIN=URL, got `{value}`"))?;
+
+        Ok(ChainUrl {
+            chain: chain.trim().parse()?,
+            url: url.trim().to_string(),
+        })
+    }
+}
@@ -1,2 +1,10 @@
 pub const REQUEST_PERIOD: u64 = 12;
 pub const ETH_THRESHOLDS: u128 = 100_000_000_000_000_000_000; // 100 ETH in WEI
+pub const BNB_THRESHOLDS: u128 = 500_000_000_000_000_000_000; // 500 BNB in WEI
+pub const POL_THRESHOLDS: u128 = 1_000_000_000_000_000_000_000_000; // 1M POL in WEI
+pub const MIN_POLL_MS: u64 = 1_000;
+pub const MONITOR_WINDOW: usize = 10;
+pub const BACKFILL_CHUNK_SIZE: u64 = 100;
+pub const REORG_HISTORY: usize = 64;
+pub const PENDING_TIMEOUT_SECS: u64 = 180;
+pub const TOOL_VERSION: &str = concat!("netraflow ", env!("CARGO_PKG_VERSION"));
@@ -1,94 +1,210 @@
 use std::{
-    fs::{create_dir_all, File},
-    io::{BufRead, BufReader},
-    path::Path,
+    fs::{create_dir_all, remove_file},
+    path::{Path, PathBuf},
+    sync::Arc,
     time::Duration,
 };
 
-use common::{
-    model::{FlowSummary, TxCategory},
-    utils::Heuristics,
+use common::{model::TxEthereum, utils::Heuristics};
+use netracrawl::{
+    constants::MEMPOOL_POLL_MS, ethereum::EthereumClient, mempool::PendingStream,
+    source::BlockSource, websocket::HeadWatcher,
 };
-use netracrawl::ethereum::EthereumClient;
-use netrascan::classification::classify_block;
-use serde_json::from_str;
 
 use crate::{
-    aggregator::summarize_block,
-    constants::REQUEST_PERIOD,
+    checkpoint::Checkpoint,
+    constants::{MIN_POLL_MS, PENDING_TIMEOUT_SECS, REQUEST_PERIOD},
     error::{Error, Result},
-    monitor::FlowMonitor,
-    storage::save_summary,
+    storage::{read_latest_summaries, rotate_report},
+    watcher::FlowWatcher,
 };
 
-pub async fn run<P>(rpc: &str, heuristics_path: P, out_path: P) -> Result<()>
+pub struct WatchOptions {
+    /// Data source as given on the command line, recorded in every summary.
+    pub source: String,
+    pub ws_url: Option<String>,
+    pub label_files: Vec<PathBuf>,
+    pub checkpoint_path: PathBuf,
+    pub start_block: Option<u64>,
+    pub confirmations: u64,
+    pub native_only: bool,
+    pub trace_internal: bool,
+}
+
+enum WatchEvent {
+    Head(netracrawl::error::Result<u64>),
+    Pending(netracrawl::error::Result<Vec<TxEthereum>>),
+}
+
+/// Follows the head of `source`. When `mempool` is given, its pending
+/// transactions are streamed too and raise provisional alerts.
+pub async fn run<P>(
+    source: Arc<dyn BlockSource>,
+    mempool: Option<Arc<EthereumClient>>,
+    heuristics_path: P,
+    out_path: P,
+    options: WatchOptions,
+) -> Result<()>
 where
     P: AsRef<Path>,
 {
-    let mut latest_seen = 0;
-
-    if !heuristics_path.as_ref().exists() {
-        return Err(Error::HeuristicsNotFound(
-            heuristics_path.as_ref().to_string_lossy().into_owned(),
+    let mut watcher = build_watcher(source, heuristics_path, out_path, &options)?;
+
+    let mut pending = None;
+    if let Some(client) = mempool {
+        watcher = watcher.with_pending_tracking(Duration::from_secs(PENDING_TIMEOUT_SECS));
+        pending = Some((
+            client,
+            PendingStream::new(Duration::from_millis(MEMPOOL_POLL_MS)),
         ));
     }
 
-    let heuristics = Heuristics::load(heuristics_path)?;
-    let mut flow_monitor = FlowMonitor::new(10);
-
-    loop {
-        let client = EthereumClient::new(rpc);
-        let block_number = client.get_latest_block_number().await?;
-
-        if block_number > latest_seen {
-            let block = client.get_block_by_number(block_number).await?;
-            let classified = classify_block(block, &heuristics);
-
-            let summary = summarize_block(&classified);
-            save_summary(&summary, &out_path)?;
+    watcher.resume(options.start_block)?;
 
-            let (delta, alerts) = flow_monitor.push(summary);
+    let chain = watcher.chain();
+    let poll_interval = Duration::from_millis(chain.block_time_ms().max(MIN_POLL_MS));
+    let mut heads = HeadWatcher::new(options.ws_url, poll_interval);
 
-            if let Some(delta) = delta {
-                println!("Δ Flow @ block {}: {:?}", delta.block_number, delta.deltas);
+    loop {
+        // Whichever source is not ready resumes waiting on the next
+        // iteration. Heads are never lost; a mempool poll dropped mid-request
+        // may miss some pending hashes, which are then only seen once mined.
+        let event = match pending.as_mut() {
+            Some((client, stream)) => tokio::select! {
+                head = heads.next_head(watcher.source()) => WatchEvent::Head(head),
+                batch = stream.next_batch(client) => WatchEvent::Pending(batch),
+            },
+            None => WatchEvent::Head(heads.next_head(watcher.source()).await),
+        };
+
+        match event {
+            WatchEvent::Head(Ok(head)) => {
+                // Every block up to the head is processed in order, so blocks
+                // skipped between notifications, polls or restarts are still
+                // recorded once.
+                if let Err(err) = watcher.catch_up(head).await {
+                    eprintln!("⚠️  [{chain}] Failed to catch up to block {head}: {err}");
+                }
             }
-
-            for alert in alerts {
-                alert.report();
+            WatchEvent::Head(Err(err)) => {
+                eprintln!("⚠️  [{chain}] Failed to fetch latest block number: {err}");
+                tokio::time::sleep(Duration::from_secs(REQUEST_PERIOD)).await;
+                continue;
             }
-
-            if let Some(avg) = flow_monitor.avg_flow(&TxCategory::Foreign) {
-                println!("📊 Avg Foreign flow: {:.2} ETH", avg as f64 / 1e18);
+            WatchEvent::Pending(Ok(transactions)) => watcher.observe_pending(transactions),
+            WatchEvent::Pending(Err(err)) => {
+                eprintln!("⚠️  [{chain}] Failed to poll pending transactions: {err}");
             }
+        }
+
+        if let Err(err) = watcher.sweep_pending().await {
+            eprintln!("⚠️  [{chain}] Failed to reconcile provisional alerts: {err}");
+        }
+    }
+}
 
-            if let Some(block) = flow_monitor.latest_block() {
-                println!("🔁 Monitoring up to block {block}");
+/// Re-classifies stored blocks up to `end_block` (or the newest stored
+/// block) with the current heuristics. `label` names the source in errors.
+pub async fn replay<P>(
+    source: Arc<dyn BlockSource>,
+    label: &str,
+    heuristics_path: P,
+    out_path: P,
+    options: WatchOptions,
+    end_block: Option<u64>,
+) -> Result<()>
+where
+    P: AsRef<Path>,
+{
+    let stored = source.stored_range();
+    let start_block = options
+        .start_block
+        .or(stored.as_ref().map(|range| *range.start()));
+    let end_block = end_block.or(stored.as_ref().map(|range| *range.end()));
+
+    let (Some(start_block), Some(end_block)) = (start_block, end_block) else {
+        return Err(Error::EmptyArchive(label.to_string()));
+    };
+
+    // A checkpoint only resumes an interrupted replay. An explicit start
+    // block, or a checkpoint already at the end, starts a fresh report.
+    let fresh = match Checkpoint::load(&options.checkpoint_path)? {
+        Some(checkpoint) => match options.start_block {
+            Some(start) => {
+                if start <= checkpoint.block_number {
+                    eprintln!(
+                        "⚠️  --start-block {start} is at or before the replay checkpoint {}, replaying from {start} again",
+                        checkpoint.block_number
+                    );
+                }
+                true
             }
+            None => checkpoint.block_number >= end_block,
+        },
+        None => true,
+    };
+
+    if fresh {
+        if options.checkpoint_path.exists() {
+            remove_file(&options.checkpoint_path)?;
+        }
+        if let Some(previous) = rotate_report(out_path.as_ref())? {
+            println!(
+                "🗂️  Moved the previous replay report to {}",
+                previous.display()
+            );
+        }
+    }
 
-            flow_monitor.print_summary();
+    let mut watcher = build_watcher(source, heuristics_path, out_path.as_ref(), &options)?;
+    watcher.resume(Some(start_block))?;
+    watcher.catch_up(end_block).await?;
 
-            println!("\n======================================================================\n");
+    println!(
+        "✅ Replayed stored blocks up to {end_block} into {}",
+        out_path.as_ref().display()
+    );
 
-            latest_seen = block_number;
-        }
+    Ok(())
+}
 
-        tokio::time::sleep(Duration::from_secs(REQUEST_PERIOD)).await;
+fn build_watcher<P, Q>(
+    source: Arc<dyn BlockSource>,
+    heuristics_path: P,
+    out_path: Q,
+    options: &WatchOptions,
+) -> Result<FlowWatcher>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    if !heuristics_path.as_ref().exists() {
+        return Err(Error::HeuristicsNotFound(
+            heuristics_path.as_ref().to_string_lossy().into_owned(),
+        ));
     }
+
+    let mut heuristics = Heuristics::load(heuristics_path)?;
+    let version = heuristics.import_label_files(&options.label_files)?;
+
+    Ok(FlowWatcher::new(
+        source,
+        &heuristics,
+        version,
+        out_path.as_ref().to_path_buf(),
+        options.checkpoint_path.clone(),
+        options.native_only,
+    )
+    .with_source_label(&options.source)
+    .with_confirmations(options.confirmations)
+    .with_internal_transfers(options.trace_internal))
 }
 
 pub fn print_latest_summary<P>(path: P, latest: usize) -> Result<()>
 where
     P: AsRef<Path>,
 {
-    let file = File::open(path)?;
-    let reader = BufReader::new(file);
-
-    let lines: Vec<_> = reader.lines().map_while(|line| line.ok()).collect();
-
-    let count = lines.len().min(latest);
-
-    for line in lines.iter().rev().take(count).rev() {
-        let summary: FlowSummary = from_str(line)?;
+    for summary in read_latest_summaries(path, latest)? {
         println!("{summary:#?}");
     }
 
@@ -8,6 +8,8 @@ pub enum Error {
     Io(std::io::Error, String),
     Scanner(netrascan::error::Error),
     HeuristicsNotFound(String),
+    InvalidBlock(String),
+    EmptyArchive(String),
 }
 
 impl std::error::Error for Error {}
@@ -27,6 +29,8 @@ impl std::fmt::Display for Error {
             }
             Self::Scanner(err) => write!(f, "{err}"),
             Self::HeuristicsNotFound(path) => write!(f, "Heuristic file not found: {path}"),
+            Self::InvalidBlock(number) => write!(f, "Invalid block number: {number}"),
+            Self::EmptyArchive(source) => write!(f, "No stored blocks in {source}"),
         }
     }
 }
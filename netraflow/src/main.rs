@@ -1,15 +1,28 @@
 mod aggregator;
+mod checkpoint;
 mod cli;
 mod constants;
 mod controller;
 mod error;
 mod monitor;
+mod pending;
 mod storage;
+mod watcher;
+
+use std::{path::PathBuf, sync::Arc, time::Duration};
 
 use clap::Parser;
+use common::{model::Chain, utils::chain_path};
+use futures::future::join_all;
 
-use cli::{Cli, Command};
-use controller::{init_directory, print_latest_summary, run};
+use cli::{ChainUrl, Cli, Command};
+use controller::{init_directory, print_latest_summary, replay, run, WatchOptions};
+use netracrawl::{
+    archive::BlockArchive,
+    constants::{ARBITRUM_RPC_URL, BASE_RPC_URL, BSC_RPC_URL, POLYGON_RPC_URL},
+    ethereum::EthereumClient,
+    source::{BlockSource, SourceKind},
+};
 
 #[tokio::main]
 async fn main() {
@@ -17,16 +30,141 @@ async fn main() {
 
     match cli.command {
         Command::Watch {
+            source,
+            chains,
             rpc,
+            chain_rpc,
+            ws,
+            chain_ws,
+            quorum,
+            rpc_timeout,
+            heuristics,
+            labels,
+            out,
+            checkpoint,
+            start_block,
+            confirmations,
+            native_only,
+            pending,
+            trace_internal,
+            archive,
+        } => {
+            let mut chains = chains;
+            chains.sort();
+            chains.dedup();
+
+            if quorum.is_some() && source != SourceKind::Rpc {
+                eprintln!("❌ {}", source.unsupported("--quorum"));
+                return;
+            }
+
+            if chains.len() > 1 {
+                if source != SourceKind::Rpc {
+                    eprintln!("❌ {}", source.unsupported("several chains at once"));
+                    return;
+                }
+                if start_block.is_some() {
+                    eprintln!("❌ --start-block only applies when watching a single chain");
+                    return;
+                }
+            }
+
+            let mut watches = Vec::with_capacity(chains.len());
+
+            for chain in chains {
+                let endpoints = RpcOptions {
+                    rpc: rpc_endpoints(chain, &rpc, &chain_rpc),
+                    quorum: quorum.unwrap_or(1),
+                    timeout: Duration::from_secs(rpc_timeout),
+                    archive: archive.as_ref().map(|dir| chain_path(dir, chain)),
+                };
+
+                let (blocks, mempool) =
+                    match open_watched(&chain_source(&source, chain), chain, endpoints).await {
+                        Ok(opened) => opened,
+                        Err(err) => {
+                            eprintln!("❌ [{chain}] Failed to open source {source}: {err}");
+                            return;
+                        }
+                    };
+
+                let mempool = match (pending, mempool) {
+                    (true, Some(client)) => Some(client),
+                    (true, None) => {
+                        eprintln!("❌ {}", source.unsupported("pending transactions"));
+                        return;
+                    }
+                    (false, _) => None,
+                };
+
+                let options = WatchOptions {
+                    source: source.to_string(),
+                    ws_url: ws_endpoint(chain, ws.as_ref(), &chain_ws),
+                    label_files: labels.iter().map(PathBuf::from).collect(),
+                    checkpoint_path: chain_path(&checkpoint, chain),
+                    start_block,
+                    confirmations,
+                    native_only,
+                    trace_internal,
+                };
+
+                let heuristics = PathBuf::from(&heuristics);
+                let out = chain_path(&out, chain);
+
+                watches.push(async move {
+                    if let Err(err) = run(blocks, mempool, heuristics, out, options).await {
+                        eprintln!("❌ [{chain}] Watch error: {err}");
+                    }
+                });
+            }
+
+            join_all(watches).await;
+        }
+        Command::Replay {
+            source,
+            chain,
             heuristics,
+            labels,
             out,
+            checkpoint,
+            start_block,
+            end_block,
+            native_only,
+            trace_internal,
         } => {
-            if let Err(err) = run(&rpc, heuristics, out).await {
-                eprintln!("❌ Watch error: {err}");
+            let source = chain_source(&source, chain);
+            let blocks: Arc<dyn BlockSource> = match open_stored(&source, chain) {
+                Ok(blocks) => blocks,
+                Err(err) => {
+                    eprintln!("❌ Failed to open source {source}: {err}");
+                    return;
+                }
+            };
+
+            let options = WatchOptions {
+                source: source.to_string(),
+                ws_url: None,
+                label_files: labels.iter().map(PathBuf::from).collect(),
+                checkpoint_path: chain_path(&checkpoint, chain),
+                start_block,
+                confirmations: 0,
+                native_only,
+                trace_internal,
+            };
+
+            let label = source.to_string();
+            let out = chain_path(&out, chain);
+            let heuristics = PathBuf::from(heuristics);
+            if let Err(err) = replay(blocks, &label, heuristics, out, options, end_block).await {
+                eprintln!("❌ Replay error: {err}");
             }
         }
-        Command::Summary { input, latest } => {
-            if let Err(err) = print_latest_summary(input, latest) {
+        Command::Summary {
+            input,
+            chain,
+            latest,
+        } => {
+            if let Err(err) = print_latest_summary(chain_path(input, chain), latest) {
                 eprintln!("❌ Summary error: {err}");
             }
         }
@@ -39,3 +177,92 @@ async fn main() {
         }
     }
 }
+
+/// Endpoint options for the node of one watched chain.
+struct RpcOptions {
+    rpc: Vec<String>,
+    quorum: usize,
+    timeout: Duration,
+    archive: Option<PathBuf>,
+}
+
+/// Blocks of a watched chain, and the node streaming its mempool if any.
+type Watched = (Arc<dyn BlockSource>, Option<Arc<EthereumClient>>);
+
+/// Opens the blocks of `chain`, plus the node client itself when reading
+/// over RPC so its mempool can be streamed. An RPC endpoint serving another
+/// chain is refused before anything is written under `chain`'s name.
+async fn open_watched(
+    source: &SourceKind,
+    chain: Chain,
+    options: RpcOptions,
+) -> netracrawl::error::Result<Watched> {
+    if let Some(blocks) = source.open_blocks(chain)? {
+        return Ok((Arc::from(blocks), None));
+    }
+
+    let mut client = EthereumClient::from_endpoints(options.rpc)
+        .with_chain(chain)
+        .with_timeout(options.timeout)
+        .with_quorum(options.quorum)?;
+    client.check_chain().await?;
+
+    if let Some(dir) = options.archive {
+        client = client.with_archive(BlockArchive::open(dir, chain)?);
+    }
+
+    let client = Arc::new(client);
+    Ok((client.clone() as Arc<dyn BlockSource>, Some(client)))
+}
+
+/// Endpoints given for `chain` with `--chain-rpc`, falling back to `--rpc`
+/// on Ethereum and to a public endpoint elsewhere.
+fn rpc_endpoints(chain: Chain, rpc: &[String], chain_rpc: &[ChainUrl]) -> Vec<String> {
+    let endpoints: Vec<String> = chain_rpc
+        .iter()
+        .filter(|endpoint| endpoint.chain == chain)
+        .map(|endpoint| endpoint.url.clone())
+        .collect();
+
+    if !endpoints.is_empty() {
+        return endpoints;
+    }
+
+    let public = match chain {
+        Chain::Ethereum => return rpc.to_vec(),
+        Chain::Bsc => BSC_RPC_URL,
+        Chain::Polygon => POLYGON_RPC_URL,
+        Chain::Arbitrum => ARBITRUM_RPC_URL,
+        Chain::Base => BASE_RPC_URL,
+    };
+
+    vec![public.to_string()]
+}
+
+fn ws_endpoint(chain: Chain, ws: Option<&String>, chain_ws: &[ChainUrl]) -> Option<String> {
+    chain_ws
+        .iter()
+        .find(|endpoint| endpoint.chain == chain)
+        .map(|endpoint| endpoint.url.clone())
+        .or_else(|| ws.filter(|_| chain == Chain::Ethereum).cloned())
+}
+
+/// `source` with an `archive:` directory moved to where `chain` keeps its
+/// archive, matching the per-chain directories `watch --archive` fills.
+fn chain_source(source: &SourceKind, chain: Chain) -> SourceKind {
+    match source {
+        SourceKind::Archive(dir) => SourceKind::Archive(chain_path(dir, chain)),
+        other => other.clone(),
+    }
+}
+
+/// Replays never touch the network, so only file-backed sources are accepted.
+fn open_stored(
+    source: &SourceKind,
+    chain: Chain,
+) -> netracrawl::error::Result<Arc<dyn BlockSource>> {
+    source
+        .open_blocks(chain)?
+        .map(Arc::from)
+        .ok_or_else(|| source.unsupported("offline replays"))
+}
@@ -1,8 +1,17 @@
 use std::collections::{HashMap, VecDeque};
 
-use common::model::{FlowSummary, TxCategory, Wei};
+use common::model::{Chain, FlowSummary, TxCategory, Wei};
 
-use crate::constants::ETH_THRESHOLDS;
+use crate::constants::{BNB_THRESHOLDS, ETH_THRESHOLDS, POL_THRESHOLDS};
+
+/// Native value moved in one category that raises an alert on `chain`.
+pub fn alert_threshold(chain: Chain) -> Wei {
+    match chain {
+        Chain::Ethereum | Chain::Arbitrum | Chain::Base => ETH_THRESHOLDS,
+        Chain::Bsc => BNB_THRESHOLDS,
+        Chain::Polygon => POL_THRESHOLDS,
+    }
+}
 
 #[derive(Debug)]
 pub struct FlowDelta {
@@ -12,6 +21,7 @@ pub struct FlowDelta {
 
 #[derive(Debug)]
 pub struct Alert {
+    pub chain: Chain,
     pub level: u8,
     pub reason: String,
     pub affected_category: TxCategory,
@@ -21,27 +31,34 @@ pub struct Alert {
 
 impl Alert {
     pub fn report(&self) {
-        let eth = self.delta_wei as f64 / 1e18;
+        let amount = self.delta_wei as f64 / 1e18;
         println!(
-            "🚨 [Level {}] Block {}: {} → {eth:.2} ETH in {:?}",
-            self.level, self.block_number, self.reason, self.affected_category
+            "🚨 [{}] [Level {}] Block {}: {} → {amount:.2} {} in {:?}",
+            self.chain,
+            self.level,
+            self.block_number,
+            self.reason,
+            self.chain.native_symbol(),
+            self.affected_category
         );
     }
 }
 
 #[derive(Debug)]
 pub struct FlowMonitor {
+    chain: Chain,
     window: VecDeque<FlowSummary>,
     max_blocks: usize,
     alert_thresholds: HashMap<TxCategory, Wei>,
 }
 
 impl FlowMonitor {
-    pub fn new(max_blocks: usize) -> Self {
+    pub fn new(chain: Chain, max_blocks: usize) -> Self {
         Self {
+            chain,
             window: VecDeque::with_capacity(max_blocks),
             max_blocks,
-            alert_thresholds: HashMap::from([(TxCategory::Foreign, ETH_THRESHOLDS)]),
+            alert_thresholds: HashMap::from([(TxCategory::Foreign, alert_threshold(chain))]),
         }
     }
 
@@ -49,12 +66,34 @@ impl FlowMonitor {
         let delta = self.compute_delta(&new_summary);
         let alerts = self.detect_alerts(&delta, new_summary.block_number);
 
+        self.record(new_summary);
+
+        (delta, alerts)
+    }
+
+    /// Refills the window from previously stored summaries (oldest first)
+    /// without raising alerts.
+    pub fn restore(&mut self, summaries: impl IntoIterator<Item = FlowSummary>) {
+        for summary in summaries {
+            if summary.is_retracted() {
+                self.retract(&summary.block_hash);
+            } else {
+                self.record(summary);
+            }
+        }
+    }
+
+    /// Drops a reorganized block from the window.
+    pub fn retract(&mut self, block_hash: &str) {
+        self.window
+            .retain(|summary| summary.block_hash != block_hash);
+    }
+
+    fn record(&mut self, summary: FlowSummary) {
         if self.window.len() == self.max_blocks {
             self.window.pop_front();
         }
-        self.window.push_front(new_summary);
-
-        (delta, alerts)
+        self.window.push_back(summary);
     }
 
     fn compute_delta(&self, current: &FlowSummary) -> Option<FlowDelta> {
@@ -84,6 +123,7 @@ impl FlowMonitor {
             if let Some(&threshold) = self.alert_thresholds.get(category) {
                 if change >= threshold {
                     alerts.push(Alert {
+                        chain: self.chain,
                         level: 1,
                         reason: format!("High flow delta for {category:?}"),
                         affected_category: *category,
@@ -116,7 +156,11 @@ impl FlowMonitor {
     }
 
     pub fn print_summary(&self) {
-        println!("Rolling window ({} blocks):", self.window.len());
+        println!(
+            "Rolling window on {} ({} blocks):",
+            self.chain,
+            self.window.len()
+        );
         for summary in &self.window {
             println!(
                 "Block {} | Foreign: {} | tx_count: {}",
@@ -0,0 +1,149 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use common::{
+    model::{Chain, TxCategory, TxEthereum, Wei},
+    utils::Heuristics,
+};
+
+use crate::monitor::alert_threshold;
+
+/// A large transfer flagged while it was still in the mempool.
+#[derive(Debug, Clone)]
+pub struct ProvisionalAlert {
+    pub chain: Chain,
+    pub hash: String,
+    pub from: String,
+    pub to: Option<String>,
+    pub category: TxCategory,
+    pub value: Wei,
+    seen_at: Instant,
+}
+
+impl ProvisionalAlert {
+    pub fn report(&self) {
+        let amount = self.value as f64 / 1e18;
+        println!(
+            "⏳ [{}] [Provisional] Pending tx {}: {} → {} moving {amount:.2} {} in {:?}",
+            self.chain,
+            self.hash,
+            self.from,
+            self.to.as_deref().unwrap_or("contract creation"),
+            self.chain.native_symbol(),
+            self.category
+        );
+    }
+
+    pub fn report_mined(&self, block_number: u64) {
+        println!(
+            "✅ [{}] [Confirmed] Provisional alert for tx {} mined in block {block_number}",
+            self.chain, self.hash
+        );
+    }
+
+    pub fn report_dropped(&self) {
+        println!(
+            "🗑️  [{}] [Dropped] Provisional alert for tx {} withdrawn, it left the mempool unmined",
+            self.chain, self.hash
+        );
+    }
+}
+
+/// Provisional alerts raised on unconfirmed transactions, kept until each
+/// transaction is either mined or dropped from the mempool.
+#[derive(Debug)]
+pub struct PendingTracker {
+    chain: Chain,
+    alerts: HashMap<String, ProvisionalAlert>,
+    alert_thresholds: HashMap<TxCategory, Wei>,
+    timeout: Duration,
+}
+
+impl PendingTracker {
+    /// Alerts not reconciled by a processed block within `timeout` are
+    /// re-checked against the node.
+    pub fn new(chain: Chain, timeout: Duration) -> Self {
+        Self {
+            chain,
+            alerts: HashMap::new(),
+            alert_thresholds: HashMap::from([(TxCategory::Foreign, alert_threshold(chain))]),
+            timeout,
+        }
+    }
+
+    /// Categorizes unconfirmed transactions and returns the newly raised
+    /// provisional alerts.
+    pub fn observe(
+        &mut self,
+        transactions: Vec<TxEthereum>,
+        heuristics: &Heuristics,
+    ) -> Vec<ProvisionalAlert> {
+        let mut raised = vec![];
+
+        for tx in transactions {
+            if self.alerts.contains_key(&tx.hash) {
+                continue;
+            }
+
+            let category = tx.categorize(heuristics);
+            let Some(&threshold) = self.alert_thresholds.get(&category) else {
+                continue;
+            };
+            let Some(value) = tx.value_wei().filter(|&value| value >= threshold) else {
+                continue;
+            };
+
+            let alert = ProvisionalAlert {
+                chain: self.chain,
+                hash: tx.hash,
+                from: tx.from,
+                to: tx.to,
+                category,
+                value,
+                seen_at: Instant::now(),
+            };
+
+            self.alerts.insert(alert.hash.clone(), alert.clone());
+            raised.push(alert);
+        }
+
+        raised
+    }
+
+    /// Resolves the provisional alerts of transactions included in a block.
+    pub fn reconcile_block<'a>(
+        &mut self,
+        hashes: impl IntoIterator<Item = &'a str>,
+    ) -> Vec<ProvisionalAlert> {
+        if self.alerts.is_empty() {
+            return vec![];
+        }
+
+        hashes
+            .into_iter()
+            .filter_map(|hash| self.alerts.remove(hash))
+            .collect()
+    }
+
+    /// Hashes whose alerts have waited longer than the timeout.
+    pub fn stale(&self) -> Vec<String> {
+        self.alerts
+            .values()
+            .filter(|alert| alert.seen_at.elapsed() >= self.timeout)
+            .map(|alert| alert.hash.clone())
+            .collect()
+    }
+
+    pub fn resolve(&mut self, hash: &str) -> Option<ProvisionalAlert> {
+        self.alerts.remove(hash)
+    }
+
+    /// Restarts the timeout of a transaction that is still pending.
+    pub fn refresh(&mut self, hash: &str) {
+        if let Some(alert) = self.alerts.get_mut(hash) {
+            alert.seen_at = Instant::now();
+        }
+    }
+}
@@ -1,11 +1,12 @@
 use std::{
-    fs::{create_dir_all, OpenOptions},
-    io::Write,
-    path::Path,
+    fs::{create_dir_all, rename, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
 };
 
+use chrono::Utc;
 use common::model::FlowSummary;
-use serde_json::to_string;
+use serde_json::{from_str, to_string};
 
 use crate::error::Result;
 
@@ -23,3 +24,54 @@ where
     writeln!(file, "{json}")?;
     Ok(())
 }
+
+/// Reads the last `count` summaries from a flow report, oldest first.
+pub fn read_latest_summaries<P>(path: P, count: usize) -> Result<Vec<FlowSummary>>
+where
+    P: AsRef<Path>,
+{
+    let file = File::open(path)?;
+    let reader = BufReader::new(file);
+
+    let lines: Vec<_> = reader.lines().map_while(|line| line.ok()).collect();
+    let skip = lines.len().saturating_sub(count);
+
+    lines
+        .iter()
+        .skip(skip)
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Ok(from_str(line)?))
+        .collect()
+}
+
+/// Moves a non-empty report at `path` aside as `<stem>-<unix time>.<ext>`
+/// so a new run starts an empty one instead of appending to it. Returns where it went.
+pub fn rotate_report<P>(path: P) -> Result<Option<PathBuf>>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    if path.metadata().map_or(true, |meta| meta.len() == 0) {
+        return Ok(None);
+    }
+
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+    let stamp = Utc::now().timestamp();
+
+    // Two runs within the same second get `-1`, `-2`, ... appended.
+    let mut rotated = path.with_file_name(format!("{stem}-{stamp}{extension}"));
+    let mut n = 0;
+    while rotated.exists() {
+        n += 1;
+        rotated = path.with_file_name(format!("{stem}-{stamp}-{n}{extension}"));
+    }
+    rename(path, &rotated)?;
+    Ok(Some(rotated))
+}
@@ -0,0 +1,439 @@
+use std::{collections::VecDeque, path::PathBuf, sync::Arc, time::Duration};
+
+use common::{
+    model::{
+        BlockEthereum, Chain, FlowSummary, HeuristicsVersion, Provenance, TxCategory, TxEthereum,
+    },
+    utils::Heuristics,
+};
+use netracrawl::source::BlockSource;
+use netrascan::classification::{
+    classify_block, classify_internal_transfers, classify_token_transfers,
+};
+
+use crate::{
+    aggregator::summarize_block,
+    checkpoint::Checkpoint,
+    constants::{BACKFILL_CHUNK_SIZE, MONITOR_WINDOW, REORG_HISTORY, TOOL_VERSION},
+    error::{Error, Result},
+    monitor::FlowMonitor,
+    pending::PendingTracker,
+    storage::{read_latest_summaries, save_summary},
+};
+
+/// Classifies blocks in order, appending their flows and advancing the
+/// checkpoint after each one.
+///
+/// The last `REORG_HISTORY` written summaries are kept so a block whose
+/// `parentHash` disagrees with the recorded chain can be traced back to the
+/// common ancestor and the orphaned flows retracted.
+pub struct FlowWatcher {
+    chain: Chain,
+    source: Arc<dyn BlockSource>,
+    heuristics: Heuristics,
+    heuristics_version: HeuristicsVersion,
+    source_label: String,
+    out_path: PathBuf,
+    checkpoint_path: PathBuf,
+    flow_monitor: FlowMonitor,
+    pending: Option<PendingTracker>,
+    recent: VecDeque<FlowSummary>,
+    native_only: bool,
+    trace_internal: bool,
+    confirmations: u64,
+    next_block: Option<u64>,
+}
+
+impl FlowWatcher {
+    /// Classifies blocks of the chain `source` serves, with the labels
+    /// `heuristics` holds for that chain. Records are stamped with
+    /// `heuristics_version`, the version of the files those labels came from.
+    pub fn new(
+        source: Arc<dyn BlockSource>,
+        heuristics: &Heuristics,
+        heuristics_version: HeuristicsVersion,
+        out_path: PathBuf,
+        checkpoint_path: PathBuf,
+        native_only: bool,
+    ) -> Self {
+        let chain = source.chain();
+        let heuristics = heuristics.for_chain(chain);
+
+        Self {
+            chain,
+            source,
+            heuristics_version,
+            heuristics,
+            source_label: String::new(),
+            out_path,
+            checkpoint_path,
+            flow_monitor: FlowMonitor::new(chain, MONITOR_WINDOW),
+            pending: None,
+            recent: VecDeque::with_capacity(REORG_HISTORY),
+            native_only,
+            trace_internal: false,
+            confirmations: 0,
+            next_block: None,
+        }
+    }
+
+    /// Names the data source in the provenance of every written record.
+    pub fn with_source_label(mut self, label: impl Into<String>) -> Self {
+        self.source_label = label.into();
+        self
+    }
+
+    /// Only processes blocks at least `confirmations` blocks below the head.
+    pub fn with_confirmations(mut self, confirmations: u64) -> Self {
+        self.confirmations = confirmations;
+        self
+    }
+
+    /// Also classifies native value moved by contracts, traced per block.
+    pub fn with_internal_transfers(mut self, trace_internal: bool) -> Self {
+        self.trace_internal = trace_internal;
+        self
+    }
+
+    /// Tracks provisional alerts raised on mempool transactions, re-checking
+    /// the ones still unmined after `timeout`.
+    pub fn with_pending_tracking(mut self, timeout: Duration) -> Self {
+        self.pending = Some(PendingTracker::new(self.chain, timeout));
+        self
+    }
+
+    pub fn chain(&self) -> Chain {
+        self.chain
+    }
+
+    pub fn source(&self) -> &dyn BlockSource {
+        self.source.as_ref()
+    }
+
+    /// Resumes after the stored checkpoint, or at `start_block` when there is
+    /// none, and refills the rolling window from the flow report. A block
+    /// the report already holds is not processed again.
+    pub fn resume(&mut self, start_block: Option<u64>) -> Result<()> {
+        match Checkpoint::load(&self.checkpoint_path)? {
+            Some(checkpoint) => {
+                println!(
+                    "📍 [{}] Resuming after checkpoint block {} ({})",
+                    self.chain, checkpoint.block_number, checkpoint.block_hash
+                );
+                self.next_block = Some(checkpoint.block_number + 1);
+            }
+            None => self.next_block = start_block,
+        }
+
+        if self.out_path.exists() {
+            let summaries =
+                read_latest_summaries(&self.out_path, REORG_HISTORY.max(MONITOR_WINDOW))?;
+
+            for summary in &summaries {
+                if summary.is_retracted() {
+                    self.recent
+                        .retain(|recorded| recorded.block_hash != summary.block_hash);
+                } else {
+                    self.remember(summary.clone());
+                }
+            }
+
+            self.flow_monitor.restore(summaries);
+        }
+
+        // A summary is appended before the checkpoint is saved, so a crash in
+        // between leaves the next block in the report only. Adopt it rather
+        // than appending it twice; a reorg since then is still caught by the
+        // parent hash of the block after it.
+        let written = self
+            .recent
+            .back()
+            .filter(|tail| Some(tail.block_number) == self.next_block)
+            .map(|tail| (tail.block_number, tail.block_hash.clone()));
+
+        if let Some((block_number, block_hash)) = written {
+            println!(
+                "📍 [{}] Block {block_number} is already in the report, resuming after it",
+                self.chain
+            );
+            Checkpoint::new(block_number, block_hash).save(&self.checkpoint_path)?;
+            self.next_block = Some(block_number + 1);
+        }
+
+        Ok(())
+    }
+
+    /// Processes every block from the last checkpoint up to `head` minus the
+    /// confirmation depth, fetching backlogs in batched chunks. Stops at the
+    /// first failing block so it is retried on the next head.
+    pub async fn catch_up(&mut self, head: u64) -> Result<()> {
+        let target = head.saturating_sub(self.confirmations);
+        let from = *self.next_block.get_or_insert(target);
+
+        if target > from {
+            println!(
+                "⏩ [{}] Catching up {} blocks ({from}..={target})",
+                self.chain,
+                target - from + 1
+            );
+        }
+
+        while let Some(start) = self.next_block.filter(|&next| next <= target) {
+            let end = target.min(start + BACKFILL_CHUNK_SIZE - 1);
+
+            let blocks = if start == end {
+                vec![self.source.block(start).await?]
+            } else {
+                self.source.blocks(start..=end).await?
+            };
+
+            for block in blocks {
+                if let Some(ancestor) = self.find_reorg_ancestor(&block).await? {
+                    self.rollback(ancestor).await?;
+                    break;
+                }
+
+                self.process_block(block).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the last recorded block still on the canonical chain when
+    /// `block` does not extend the recorded chain.
+    async fn find_reorg_ancestor(&self, block: &BlockEthereum) -> Result<Option<u64>> {
+        let Some(previous) = self.recent.back() else {
+            return Ok(None);
+        };
+
+        let extends_previous = block.block_number() == Some(previous.block_number + 1);
+
+        if !extends_previous
+            || previous.block_hash.is_empty()
+            || previous.block_hash.eq_ignore_ascii_case(&block.parent_hash)
+        {
+            return Ok(None);
+        }
+
+        eprintln!(
+            "🔀 [{}] Reorg detected at block {}: parent {} does not match recorded {}",
+            self.chain,
+            previous.block_number + 1,
+            block.parent_hash,
+            previous.block_hash
+        );
+
+        for recorded in self.recent.iter().rev() {
+            let canonical = self.source.block_hash(recorded.block_number).await?;
+
+            if canonical.eq_ignore_ascii_case(&recorded.block_hash) {
+                return Ok(Some(recorded.block_number));
+            }
+        }
+
+        let oldest = self
+            .recent
+            .front()
+            .map(|summary| summary.block_number)
+            .unwrap_or(previous.block_number);
+
+        eprintln!(
+            "⚠️  [{}] Reorg is deeper than the {REORG_HISTORY} tracked blocks",
+            self.chain
+        );
+
+        Ok(Some(oldest.saturating_sub(1)))
+    }
+
+    /// Appends a retraction for every recorded block above `ancestor` and
+    /// rewinds the checkpoint so the canonical blocks are processed again.
+    async fn rollback(&mut self, ancestor: u64) -> Result<()> {
+        while self
+            .recent
+            .back()
+            .is_some_and(|summary| summary.block_number > ancestor)
+        {
+            let Some(orphan) = self.recent.pop_back() else {
+                break;
+            };
+
+            let retraction = orphan
+                .retraction()
+                .with_provenance(self.provenance(orphan.block_number));
+            save_summary(&retraction, &self.out_path)?;
+            self.source.forget(orphan.block_number)?;
+            self.flow_monitor.retract(&orphan.block_hash);
+
+            println!(
+                "↩️  [{}] Retracted flows of block {} ({})",
+                self.chain, orphan.block_number, orphan.block_hash
+            );
+        }
+
+        let ancestor_hash = match self.recent.back() {
+            Some(summary) => summary.block_hash.clone(),
+            None => self.source.block_hash(ancestor).await?,
+        };
+
+        Checkpoint::new(ancestor, ancestor_hash).save(&self.checkpoint_path)?;
+        self.next_block = Some(ancestor + 1);
+
+        Ok(())
+    }
+
+    /// Raises provisional alerts for large unconfirmed transfers.
+    pub fn observe_pending(&mut self, transactions: Vec<TxEthereum>) {
+        let Some(tracker) = self.pending.as_mut() else {
+            return;
+        };
+
+        for alert in tracker.observe(transactions, &self.heuristics) {
+            alert.report();
+        }
+    }
+
+    /// Re-checks provisional alerts that no processed block has reconciled,
+    /// resolving those mined in a block not yet processed or dropped from the
+    /// mempool.
+    pub async fn sweep_pending(&mut self) -> Result<()> {
+        let Some(tracker) = self.pending.as_mut() else {
+            return Ok(());
+        };
+
+        let stale = tracker.stale();
+        if stale.is_empty() {
+            return Ok(());
+        }
+
+        let transactions = self.source.transactions(&stale).await?;
+
+        for (hash, tx) in stale.iter().zip(transactions) {
+            match tx {
+                None => {
+                    if let Some(alert) = tracker.resolve(hash) {
+                        alert.report_dropped();
+                    }
+                }
+                Some(tx) => match tx.mined_block() {
+                    Some(block_number) => {
+                        if let Some(alert) = tracker.resolve(hash) {
+                            alert.report_mined(block_number);
+                        }
+                    }
+                    None => tracker.refresh(hash),
+                },
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn process_block(&mut self, block: BlockEthereum) -> Result<()> {
+        let block_number = block
+            .block_number()
+            .ok_or_else(|| Error::InvalidBlock(block.number.clone()))?;
+
+        if let Some(tracker) = self.pending.as_mut() {
+            let hashes = block.transactions.iter().map(|tx| tx.hash.as_str());
+
+            for alert in tracker.reconcile_block(hashes) {
+                alert.report_mined(block_number);
+            }
+        }
+        let block_hash = block.hash.clone();
+        let timestamp = block.timestamp_secs().unwrap_or(0);
+
+        self.source
+            .verify_block_hash(block_number, &block_hash)
+            .await?;
+
+        let mut classified = classify_block(self.chain, block, &self.heuristics);
+
+        if !self.native_only {
+            let transfers = self.source.token_transfers(block_number).await?;
+            classified.extend(classify_token_transfers(
+                self.chain,
+                transfers,
+                timestamp,
+                &self.heuristics,
+            ));
+        }
+
+        if self.trace_internal {
+            let transfers = self.source.internal_transfers(block_number).await?;
+            classified.extend(classify_internal_transfers(
+                self.chain,
+                transfers,
+                timestamp,
+                &self.heuristics,
+            ));
+        }
+
+        let summary = summarize_block(
+            self.chain,
+            block_number,
+            &block_hash,
+            timestamp,
+            &classified,
+        )
+        .with_provenance(self.provenance(block_number));
+        save_summary(&summary, &self.out_path)?;
+        Checkpoint::new(block_number, block_hash).save(&self.checkpoint_path)?;
+        self.next_block = Some(block_number + 1);
+
+        self.remember(summary.clone());
+        self.report(summary);
+
+        Ok(())
+    }
+
+    /// Heuristics, tool and source behind the record of `block_number`.
+    fn provenance(&self, block_number: u64) -> Provenance {
+        Provenance::new(
+            self.heuristics_version.clone(),
+            TOOL_VERSION,
+            &self.source_label,
+        )
+        .with_block_range(block_number, block_number)
+    }
+
+    fn remember(&mut self, summary: FlowSummary) {
+        if self.recent.len() == REORG_HISTORY {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(summary);
+    }
+
+    fn report(&mut self, summary: FlowSummary) {
+        let (delta, alerts) = self.flow_monitor.push(summary);
+
+        if let Some(delta) = delta {
+            println!(
+                "Δ [{}] Flow @ block {}: {:?}",
+                self.chain, delta.block_number, delta.deltas
+            );
+        }
+
+        for alert in alerts {
+            alert.report();
+        }
+
+        if let Some(avg) = self.flow_monitor.avg_flow(&TxCategory::Foreign) {
+            println!(
+                "📊 [{}] Avg Foreign flow: {:.2} {}",
+                self.chain,
+                avg as f64 / 1e18,
+                self.chain.native_symbol()
+            );
+        }
+
+        if let Some(block) = self.flow_monitor.latest_block() {
+            println!("🔁 [{}] Monitoring up to block {block}", self.chain);
+        }
+
+        self.flow_monitor.print_summary();
+
+        println!("\n======================================================================\n");
+    }
+}
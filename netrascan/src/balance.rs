@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+
+use common::model::{AssetKind, BalanceSnapshot, Chain, TxRecord};
+use netracrawl::source::BalanceSource;
+
+use crate::{constants::MAX_TRACKED_TOKENS, error::Result};
+
+/// Reads native and token balances of analyzed wallets at the chain head
+/// and at chosen past blocks.
+pub struct BalanceTracker {
+    source: Box<dyn BalanceSource>,
+    blocks: Vec<Option<u64>>,
+}
+
+impl BalanceTracker {
+    pub fn new(source: Box<dyn BalanceSource>, past_blocks: &[u64]) -> Self {
+        let mut blocks = vec![None];
+        blocks.extend(past_blocks.iter().copied().map(Some));
+
+        Self { source, blocks }
+    }
+
+    /// Balances of `wallet` in the native coin and in the tokens it moved
+    /// in `records`, one snapshot per asset and block. Tokens whose
+    /// contract did not answer are left out.
+    pub async fn snapshot(
+        &self,
+        chain: Chain,
+        wallet: &str,
+        records: &[TxRecord],
+    ) -> Result<Vec<BalanceSnapshot>> {
+        let tokens = tracked_tokens(records);
+        let contracts: Vec<String> = tokens.iter().map(|(token, _)| token.clone()).collect();
+        let mut snapshots = vec![];
+
+        for &block in &self.blocks {
+            let native = self
+                .source
+                .native_balances(&[wallet.to_string()], block)
+                .await?;
+
+            snapshots.extend(native.into_iter().map(|balance| BalanceSnapshot {
+                block,
+                token: None,
+                symbol: chain.native_symbol().to_string(),
+                balance,
+            }));
+
+            if contracts.is_empty() {
+                continue;
+            }
+
+            let balances = self
+                .source
+                .token_balances(wallet, &contracts, block)
+                .await?;
+
+            for ((token, symbol), balance) in tokens.iter().zip(balances) {
+                let Some(balance) = balance else {
+                    continue;
+                };
+
+                snapshots.push(BalanceSnapshot {
+                    block,
+                    token: Some(token.clone()),
+                    symbol: symbol.clone(),
+                    balance,
+                });
+            }
+        }
+
+        println!(
+            "💼 Read {} balance snapshots for {wallet} ({} tokens tracked)",
+            snapshots.len(),
+            tokens.len()
+        );
+
+        Ok(snapshots)
+    }
+}
+
+/// ERC-20 contracts moved in `records` with their symbols, most frequent
+/// first, capped at [`MAX_TRACKED_TOKENS`].
+pub fn tracked_tokens(records: &[TxRecord]) -> Vec<(String, String)> {
+    let mut counts: HashMap<String, (String, usize)> = HashMap::new();
+
+    for record in records.iter().filter(|tx| tx.kind == AssetKind::Erc20) {
+        if record.contract_address.is_empty() {
+            continue;
+        }
+
+        let entry = counts
+            .entry(record.contract_address.to_ascii_lowercase())
+            .or_insert_with(|| (record.token_symbol.clone(), 0));
+        entry.1 += 1;
+    }
+
+    let mut tokens: Vec<(String, String, usize)> = counts
+        .into_iter()
+        .map(|(token, (symbol, count))| (token, symbol, count))
+        .collect();
+    tokens.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+
+    tokens
+        .into_iter()
+        .take(MAX_TRACKED_TOKENS)
+        .map(|(token, symbol, _)| (token, symbol))
+        .collect()
+}
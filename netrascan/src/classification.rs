@@ -1,13 +1,23 @@
+use std::collections::{BTreeMap, HashMap};
+
 use common::{
-    model::{BlockEthereum, TxCategory, TxEthereum, WalletType},
+    model::{
+        AddressKind, BlockEthereum, Chain, InternalTransfer, TokenTransfer, TxCategory, TxEthereum,
+        TxRecord, WalletType,
+    },
     utils::Heuristics,
 };
 
+use crate::constants::CONTRACT_COUNTERPARTY_WEIGHT;
+
 pub struct TxClassified {
+    pub chain: Chain,
     pub hash: String,
     pub from: String,
     pub to: Option<String>,
     pub value: u128,
+    /// Token contract for ERC-20 transfers, `None` for native value.
+    pub token: Option<String>,
     pub category: TxCategory,
     pub block_number: u64,
     pub timestamp: u64,
@@ -15,6 +25,7 @@ pub struct TxClassified {
 
 impl TxClassified {
     pub fn from_tx(
+        chain: Chain,
         tx: TxEthereum,
         category: TxCategory,
         block_number: u64,
@@ -24,31 +35,111 @@ impl TxClassified {
         let timestamp = u64::from_str_radix(timestamp.into().trim_start_matches("0x"), 16).ok()?;
 
         Some(TxClassified {
+            chain,
             hash: tx.hash,
             from: tx.from,
             to: tx.to,
             value,
+            token: None,
             category,
             block_number,
             timestamp,
         })
     }
+
+    pub fn from_token_transfer(
+        chain: Chain,
+        transfer: TokenTransfer,
+        category: TxCategory,
+        timestamp: u64,
+    ) -> Self {
+        TxClassified {
+            chain,
+            hash: transfer.hash,
+            from: transfer.from,
+            to: Some(transfer.to),
+            value: transfer.value,
+            token: Some(transfer.contract_address),
+            category,
+            block_number: transfer.block_number,
+            timestamp,
+        }
+    }
+
+    pub fn from_internal_transfer(
+        chain: Chain,
+        transfer: InternalTransfer,
+        category: TxCategory,
+        timestamp: u64,
+    ) -> Self {
+        TxClassified {
+            chain,
+            hash: transfer.hash,
+            from: transfer.from,
+            to: Some(transfer.to),
+            value: transfer.value,
+            token: None,
+            category,
+            block_number: transfer.block_number,
+            timestamp,
+        }
+    }
 }
 
-pub fn classify_block(block: BlockEthereum, heuristics: &Heuristics) -> Vec<TxClassified> {
-    let block_number = u64::from_str_radix(block.number.trim_start_matches("0x"), 16).unwrap_or(0);
+pub fn classify_block(
+    chain: Chain,
+    block: BlockEthereum,
+    heuristics: &Heuristics,
+) -> Vec<TxClassified> {
+    let block_number = block.block_number().unwrap_or(0);
 
     block
         .transactions
         .into_iter()
         .filter_map(|tx| {
             let category = tx.categorize(heuristics);
-            TxClassified::from_tx(tx, category, block_number, &block.timestamp)
+            TxClassified::from_tx(chain, tx, category, block_number, &block.timestamp)
+        })
+        .collect()
+}
+
+pub fn classify_token_transfers(
+    chain: Chain,
+    transfers: Vec<TokenTransfer>,
+    timestamp: u64,
+    heuristics: &Heuristics,
+) -> Vec<TxClassified> {
+    transfers
+        .into_iter()
+        .map(|transfer| {
+            let category = heuristics.categorize(&transfer.from, Some(&transfer.to));
+            TxClassified::from_token_transfer(chain, transfer, category, timestamp)
         })
         .collect()
 }
 
-pub fn classify_wallet(score: f32) -> WalletType {
+pub fn classify_internal_transfers(
+    chain: Chain,
+    transfers: Vec<InternalTransfer>,
+    timestamp: u64,
+    heuristics: &Heuristics,
+) -> Vec<TxClassified> {
+    transfers
+        .into_iter()
+        .map(|transfer| {
+            let category = heuristics.categorize(&transfer.from, Some(&transfer.to));
+            TxClassified::from_internal_transfer(chain, transfer, category, timestamp)
+        })
+        .collect()
+}
+
+/// Type of a wallet from its score. A wallet that is itself a contract or
+/// proxy is nobody's retail wallet, whatever its score.
+pub fn classify_wallet(score: f32, kinds: Option<&WalletKinds>) -> WalletType {
+    if kinds.is_some_and(WalletKinds::is_contract) {
+        return WalletType::Contract;
+    }
+
     match score {
         s if s >= 0.6 => WalletType::Domestic,
         s if s > 0.2 && s < 0.6 => WalletType::Bridge,
@@ -57,3 +148,71 @@ pub fn classify_wallet(score: f32) -> WalletType {
         _ => WalletType::Unknown,
     }
 }
+
+/// Distinct lowercase addresses `wallet` exchanged value with, sorted.
+pub fn counterparties(wallet: &str, records: &[TxRecord]) -> Vec<String> {
+    let mut addresses: Vec<String> = records
+        .iter()
+        .flat_map(|tx| [&tx.from, &tx.to])
+        .filter(|address| !address.is_empty() && !address.eq_ignore_ascii_case(wallet))
+        .map(|address| address.to_ascii_lowercase())
+        .collect();
+
+    addresses.sort_unstable();
+    addresses.dedup();
+    addresses
+}
+
+/// What a scored wallet and its counterparties are, judged from their code.
+#[derive(Debug, Clone)]
+pub struct WalletKinds {
+    wallet: String,
+    pub kind: AddressKind,
+    /// Kinds of the counterparties, keyed by lowercase address.
+    pub counterparties: HashMap<String, AddressKind>,
+}
+
+impl WalletKinds {
+    pub fn new(
+        wallet: &str,
+        kind: AddressKind,
+        counterparties: HashMap<String, AddressKind>,
+    ) -> Self {
+        Self {
+            wallet: wallet.to_ascii_lowercase(),
+            kind,
+            counterparties,
+        }
+    }
+
+    /// Whether the wallet is a contract or proxy. Smart accounts are
+    /// someone's wallet and are scored like EOAs.
+    pub fn is_contract(&self) -> bool {
+        matches!(self.kind, AddressKind::Contract | AddressKind::Proxy)
+    }
+
+    /// Weight of `tx` in the wallet's score. Swaps and other calls into
+    /// contracts say less about the owner than transfers between wallets.
+    pub fn weight(&self, tx: &TxRecord) -> f32 {
+        let counterparty = match tx.from.eq_ignore_ascii_case(&self.wallet) {
+            true => &tx.to,
+            false => &tx.from,
+        };
+
+        match self.counterparties.get(&counterparty.to_ascii_lowercase()) {
+            Some(AddressKind::Contract | AddressKind::Proxy) => CONTRACT_COUNTERPARTY_WEIGHT,
+            _ => 1.0,
+        }
+    }
+
+    /// How many counterparties are of each kind.
+    pub fn counts(&self) -> BTreeMap<AddressKind, usize> {
+        let mut counts = BTreeMap::new();
+
+        for kind in self.counterparties.values() {
+            *counts.entry(*kind).or_insert(0) += 1;
+        }
+
+        counts
+    }
+}
@@ -1,4 +1,28 @@
-use clap::{Parser, Subcommand};
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, TimeZone};
+use clap::{Args, Parser, Subcommand};
+use common::{
+    model::{AssetKind, Chain, HeuristicsVersion, LabelKind, Provenance},
+    utils::{Heuristics, DEFAULT_LABEL_FILES},
+};
+use netracrawl::{
+    address::AddressTyper,
+    archive::BlockArchive,
+    constants::{ARBITRUM_RPC_URL, BASE_RPC_URL, BSC_RPC_URL, POLYGON_RPC_URL},
+    error::{Error, Result},
+    ethereum::EthereumClient,
+    etherscan::{ApiTier, BlockRange, Closest, EtherscanClient},
+    explorer::Explorer,
+    history::HistoryStore,
+    source::{BalanceSource, BlockSource, CachedSource, SourceKind, TransferSource},
+};
+use netrascan::{
+    balance::BalanceTracker,
+    constants::{
+        DEFAULT_ADDRESS_CACHE, DEFAULT_HISTORY_DIR, DEFAULT_RPC_URL, HEURISTICS_PATH, TOOL_VERSION,
+    },
+    error::{Error as ScanError, Result as ScanResult},
+    labels::{DumpFormat, ReportFormat},
+};
 
 #[derive(Parser)]
 #[command(name = "netrascan")]
@@ -10,6 +34,368 @@ pub struct Cli {
     pub command: Command,
 }
 
+#[derive(Args)]
+pub struct TransferArgs {
+    #[arg(
+        long = "source",
+        default_value = "etherscan",
+        env = "NETRA_SOURCE",
+        help = "Where to read transfers from: etherscan, rpc (ERC-20 only), fixtures:<dir> or jsonl:<file>"
+    )]
+    pub data_source: SourceKind,
+
+    #[arg(
+        long,
+        env = "ETHERSCAN_API_KEY",
+        help = "Etherscan API key used to fetch transaction data"
+    )]
+    pub etherscan_key: Option<String>,
+
+    #[arg(
+        long,
+        help = "RPC endpoint used by the rpc source; defaults to a public endpoint of --chain",
+        env = "ETH_RPC_URL"
+    )]
+    pub rpc: Option<String>,
+
+    #[arg(long, default_value_t = 0, help = "Start block number for pagination")]
+    pub start_block: u64,
+
+    #[arg(
+        long,
+        conflicts_with = "start_block",
+        value_parser = parse_since,
+        help = "Start date/time instead of --start-block, e.g. 2025-01-01 or \"2025-01-01 08:00 WIB\" (UTC unless a zone is given)"
+    )]
+    pub since: Option<DateTime<FixedOffset>>,
+
+    #[arg(
+        long,
+        help = "End block number (inclusive) for pagination, the chain head when omitted"
+    )]
+    pub end_block: Option<u64>,
+
+    #[arg(
+        long,
+        conflicts_with = "end_block",
+        value_parser = parse_until,
+        help = "End date/time (inclusive) instead of --end-block; a bare date covers the whole day"
+    )]
+    pub until: Option<DateTime<FixedOffset>>,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = ApiTier::Free,
+        env = "ETHERSCAN_API_TIER",
+        help = "Etherscan API plan, used to pace requests under its rate limit"
+    )]
+    pub api_tier: ApiTier,
+
+    #[arg(
+        long,
+        default_value_t = Chain::Ethereum,
+        env = "ETHERSCAN_CHAIN",
+        help = "Chain to query (ethereum, bsc, polygon, arbitrum, base)"
+    )]
+    pub chain: Chain,
+
+    #[arg(
+        long,
+        help = "Use the Etherscan v2 multichain endpoint instead of the chain's own explorer"
+    )]
+    pub multichain: bool,
+
+    #[arg(
+        long,
+        env = "ETHERSCAN_BASE_URL",
+        help = "Override the explorer API base URL (e.g. a local stand-in)"
+    )]
+    pub explorer_url: Option<String>,
+
+    #[arg(
+        long,
+        default_value = DEFAULT_HISTORY_DIR,
+        env = "NETRA_HISTORY_DIR",
+        help = "Local wallet history, so later runs only fetch new blocks"
+    )]
+    pub history_dir: String,
+
+    #[arg(
+        long,
+        help = "Fetch the whole range again without reading or updating local history"
+    )]
+    pub no_cache: bool,
+}
+
+impl TransferArgs {
+    pub fn explorer(&self) -> Explorer {
+        let explorer = if self.multichain {
+            Explorer::multichain(self.chain)
+        } else {
+            Explorer::for_chain(self.chain)
+        };
+
+        match &self.explorer_url {
+            Some(url) => explorer.with_base_url(url),
+            None => explorer,
+        }
+    }
+
+    pub fn client(&self) -> Result<EtherscanClient> {
+        let key = self.etherscan_key.as_deref().ok_or_else(|| {
+            Error::InvalidApiKey("missing, set --etherscan-key or ETHERSCAN_API_KEY".to_string())
+        })?;
+
+        Ok(EtherscanClient::new(key, self.explorer()).with_tier(self.api_tier))
+    }
+
+    /// Node client for `--rpc` on `--chain`.
+    pub async fn rpc_client(&self) -> Result<EthereumClient> {
+        connect(self.rpc.as_deref(), self.chain).await
+    }
+
+    pub async fn transfer_source(&self) -> Result<Box<dyn TransferSource>> {
+        if let Some(source) = self.data_source.open_transfers(self.chain)? {
+            return Ok(source);
+        }
+
+        let (source, label, cache): (Box<dyn TransferSource>, _, _) = match self.data_source {
+            SourceKind::Rpc => (Box::new(self.rpc_client().await?), "rpc", "rpc".to_string()),
+            _ => (
+                Box::new(self.client()?),
+                "etherscan",
+                self.explorer().cache_label(),
+            ),
+        };
+
+        let kinds = source.asset_kinds();
+        if kinds.len() < AssetKind::ALL.len() {
+            let kinds: Vec<String> = kinds.iter().map(AssetKind::to_string).collect();
+            eprintln!(
+                "⚠️  The {label} source only sees {} transfers; other kinds are missing from wallet history and reports note this in their provenance",
+                kinds.join(", ")
+            );
+        }
+
+        if self.no_cache {
+            return Ok(source);
+        }
+
+        let store = HistoryStore::new(&self.history_dir);
+        Ok(Box::new(CachedSource::new(source, store, &cache)))
+    }
+
+    /// Block window to fetch, with `--since`/`--until` resolved to blocks
+    /// through `source` and the end capped at its head, so the range stamped
+    /// into provenance is one that can be fetched again.
+    pub async fn range(&self, source: &dyn TransferSource) -> ScanResult<BlockRange> {
+        let head = source.head_block().await?;
+        let start = match self.since {
+            Some(since) => resolve_time(source, since, Closest::After).await?,
+            None => self.start_block,
+        };
+        let end = match self.until {
+            Some(until) => resolve_time(source, until, Closest::Before).await?,
+            None => self.end_block.unwrap_or(head),
+        };
+
+        let end = end.min(head);
+
+        if self.since.is_some() || self.until.is_some() {
+            println!("📅 Resolved dates to blocks {start}..={end}");
+        }
+
+        Ok(BlockRange::new(start, end))
+    }
+
+    /// Provenance of records built from the transfers `source` returned
+    /// in `range`.
+    pub fn provenance(
+        &self,
+        source: &dyn TransferSource,
+        range: BlockRange,
+    ) -> ScanResult<Provenance> {
+        let heuristics = Heuristics::load_or_default(HEURISTICS_PATH)?;
+
+        Ok(Provenance::new(
+            heuristics.version_info()?,
+            TOOL_VERSION,
+            self.data_source.to_string(),
+        )
+        .with_block_range(range.start, range.end)
+        .with_asset_kinds(source.asset_kinds()))
+    }
+}
+
+#[derive(Args)]
+pub struct BalanceArgs {
+    #[arg(
+        long,
+        help = "Record native and tracked token balances at the chain head"
+    )]
+    pub balances: bool,
+
+    #[arg(
+        long = "balance-at",
+        value_name = "BLOCK",
+        help = "Also record balances at this past block (repeatable, implies --balances)"
+    )]
+    pub balance_at: Vec<u64>,
+
+    #[arg(
+        long,
+        help = "Where to read balances from: etherscan or rpc (defaults to --source; past blocks need rpc)"
+    )]
+    pub balance_source: Option<SourceKind>,
+}
+
+impl BalanceArgs {
+    /// Balance reader for the requested snapshots, `None` when none were
+    /// requested.
+    pub async fn tracker(&self, transfers: &TransferArgs) -> Result<Option<BalanceTracker>> {
+        if !self.balances && self.balance_at.is_empty() {
+            return Ok(None);
+        }
+
+        let kind = self
+            .balance_source
+            .as_ref()
+            .unwrap_or(&transfers.data_source);
+
+        if *kind == SourceKind::Etherscan && !self.balance_at.is_empty() {
+            return Err(kind.unsupported("balances at past blocks, use --balance-source rpc"));
+        }
+
+        let source: Box<dyn BalanceSource> = match kind.open_balances()? {
+            Some(source) => source,
+            None if *kind == SourceKind::Rpc => Box::new(transfers.rpc_client().await?),
+            None => Box::new(transfers.client()?),
+        };
+
+        Ok(Some(BalanceTracker::new(source, &self.balance_at)))
+    }
+}
+
+#[derive(Args)]
+pub struct AddressArgs {
+    #[arg(
+        long,
+        help = "Tell EOAs, contracts, proxies and smart accounts apart via eth_getCode on --rpc"
+    )]
+    pub address_types: bool,
+
+    #[arg(
+        long,
+        default_value = DEFAULT_ADDRESS_CACHE,
+        env = "NETRA_ADDRESS_CACHE",
+        help = "File caching the types of addresses holding code"
+    )]
+    pub address_cache: String,
+}
+
+impl AddressArgs {
+    /// Address typer over `rpc` on `chain`, `None` unless `--address-types`
+    /// was given.
+    pub async fn typer(&self, rpc: Option<&str>, chain: Chain) -> Result<Option<AddressTyper>> {
+        if !self.address_types {
+            return Ok(None);
+        }
+
+        let typer =
+            AddressTyper::new(connect(rpc, chain).await?).with_cache_file(&self.address_cache)?;
+        Ok(Some(typer))
+    }
+}
+
+#[derive(Args)]
+pub struct NodeArgs {
+    #[arg(
+        long = "source",
+        default_value = "rpc",
+        env = "NETRA_SOURCE",
+        help = "Where to read blocks from: rpc, archive:<dir>, fixtures:<dir> or jsonl:<file>"
+    )]
+    pub data_source: SourceKind,
+
+    #[arg(
+        long,
+        default_value_t = Chain::Ethereum,
+        help = "Chain the blocks belong to (ethereum, bsc, polygon, arbitrum, base), selecting its heuristics"
+    )]
+    pub chain: Chain,
+
+    #[arg(
+        long,
+        help = "RPC endpoint to use; defaults to a public endpoint of --chain",
+        env = "ETH_RPC_URL"
+    )]
+    pub rpc: Option<String>,
+
+    #[arg(
+        long,
+        env = "NETRA_ARCHIVE_DIR",
+        help = "Local block archive to read first and fill with fetched blocks"
+    )]
+    pub archive: Option<String>,
+
+    #[arg(
+        long,
+        requires = "archive",
+        help = "Read only from the archive, without touching the RPC"
+    )]
+    pub offline: bool,
+
+    #[arg(
+        long,
+        value_delimiter = ',',
+        default_value = DEFAULT_LABEL_FILES,
+        help = "Region-tagged CEX label files deciding Domestic vs Foreign, comma-separated"
+    )]
+    pub labels: Vec<String>,
+
+    #[command(flatten)]
+    pub addresses: AddressArgs,
+}
+
+impl NodeArgs {
+    /// Labels to classify with on `--chain`: the heuristics file merged
+    /// with the `--labels` files, and the version of those files.
+    pub fn heuristics(&self) -> ScanResult<(Heuristics, HeuristicsVersion)> {
+        let mut heuristics = Heuristics::load_or_default(HEURISTICS_PATH)?;
+        let version = heuristics.import_label_files(&self.labels)?;
+        Ok((heuristics.for_chain(self.chain), version))
+    }
+
+    /// Node client for `--rpc` on `--chain`. With `--offline` the node is
+    /// never contacted, so its chain is not checked either.
+    pub async fn client(&self) -> Result<EthereumClient> {
+        let mut client = match self.offline {
+            true => {
+                EthereumClient::new(rpc_url(self.rpc.as_deref(), self.chain)).with_chain(self.chain)
+            }
+            false => connect(self.rpc.as_deref(), self.chain).await?,
+        };
+
+        if let Some(dir) = &self.archive {
+            client = client.with_archive(BlockArchive::open(dir, self.chain)?);
+        }
+
+        if self.offline {
+            client = client.offline();
+        }
+
+        Ok(client)
+    }
+
+    pub async fn block_source(&self) -> Result<Box<dyn BlockSource>> {
+        match self.data_source.open_blocks(self.chain)? {
+            Some(source) => Ok(source),
+            None => Ok(Box::new(self.client().await?)),
+        }
+    }
+}
+
 #[derive(Subcommand)]
 pub enum Command {
     // =========================
@@ -23,15 +409,14 @@ pub enum Command {
         #[arg(help = "Target wallet address to analyze")]
         wallet: String,
 
-        #[arg(
-            long,
-            env = "ETHERSCAN_API_KEY",
-            help = "Etherscan API key used to fetch transaction data"
-        )]
-        etherscan_key: String,
+        #[command(flatten)]
+        transfers: TransferArgs,
+
+        #[command(flatten)]
+        balances: BalanceArgs,
 
-        #[arg(long, default_value_t = 0, help = "Start block number for pagination")]
-        start_block: u64,
+        #[command(flatten)]
+        addresses: AddressArgs,
     },
 
     #[command(
@@ -45,15 +430,14 @@ pub enum Command {
         )]
         input: String,
 
-        #[arg(
-            long,
-            env = "ETHERSCAN_API_KEY",
-            help = "Etherscan API key used to fetch transaction data"
-        )]
-        etherscan_key: String,
+        #[command(flatten)]
+        transfers: TransferArgs,
+
+        #[command(flatten)]
+        balances: BalanceArgs,
 
-        #[arg(long, default_value_t = 0, help = "Start block number for pagination")]
-        start_block: u64,
+        #[command(flatten)]
+        addresses: AddressArgs,
 
         #[arg(
             long,
@@ -68,18 +452,14 @@ pub enum Command {
         long_about = "Fetches transactions to the source wallet and extracts unique sender addresses for training or further analysis."
     )]
     Crawl {
-        #[arg(long, help = "The known source wallet (e.g. a CEX deposit address)")]
-        source: String,
-
         #[arg(
-            long,
-            env = "ETHERSCAN_API_KEY",
-            help = "Etherscan API key used to fetch transaction data"
+            long = "wallet",
+            help = "The known source wallet (e.g. a CEX deposit address)"
         )]
-        etherscan_key: String,
+        source: String,
 
-        #[arg(long, default_value_t = 0, help = "Start block number for pagination")]
-        start_block: u64,
+        #[command(flatten)]
+        transfers: TransferArgs,
 
         #[arg(
             long,
@@ -97,15 +477,14 @@ pub enum Command {
         #[arg(long, help = "Path to file containing wallet addresses (one per line)")]
         input: String,
 
-        #[arg(
-            long,
-            env = "ETHERSCAN_API_KEY",
-            help = "Etherscan API key used to fetch transaction data"
-        )]
-        etherscan_key: String,
+        #[command(flatten)]
+        transfers: TransferArgs,
+
+        #[command(flatten)]
+        balances: BalanceArgs,
 
-        #[arg(long, default_value_t = 0, help = "Start block number for pagination")]
-        start_block: u64,
+        #[command(flatten)]
+        addresses: AddressArgs,
 
         #[arg(
             long,
@@ -123,15 +502,8 @@ pub enum Command {
         #[arg(help = "Wallet address to fetch transaction history for")]
         wallet: String,
 
-        #[arg(
-            long,
-            env = "ETHERSCAN_API_KEY",
-            help = "Etherscan API key used to fetch transaction data"
-        )]
-        etherscan_key: String,
-
-        #[arg(long, default_value_t = 0, help = "Start block number for pagination")]
-        start_block: u64,
+        #[command(flatten)]
+        transfers: TransferArgs,
 
         #[arg(
             long,
@@ -158,13 +530,8 @@ pub enum Command {
     // =========================
     /// Scan the latest block and classify its transactions
     ScanLatest {
-        #[arg(
-            long,
-            default_value = "https://rpc.ankr.com/eth/6570a5941c65a2a7deffce485080569aa0bf85c6dc4b0f1dc95002557569af40",
-            help = "RPC endpoint to use",
-            env = "ETH_RPC_URL"
-        )]
-        rpc: String,
+        #[command(flatten)]
+        node: NodeArgs,
     },
 
     /// Scan a specific block by number
@@ -172,13 +539,8 @@ pub enum Command {
         #[arg(help = "Ethereum block number to scan")]
         block_number: u64,
 
-        #[arg(
-            long,
-            default_value = "https://rpc.ankr.com/eth/6570a5941c65a2a7deffce485080569aa0bf85c6dc4b0f1dc95002557569af40",
-            help = "RPC endpoint to use",
-            env = "ETH_RPC_URL"
-        )]
-        rpc: String,
+        #[command(flatten)]
+        node: NodeArgs,
     },
 
     /// Classify a single transaction manually
@@ -186,21 +548,364 @@ pub enum Command {
         #[arg(help = "Transaction hash to classify")]
         tx_hash: String,
 
-        #[arg(
-            long,
-            default_value = "https://rpc.ankr.com/eth/6570a5941c65a2a7deffce485080569aa0bf85c6dc4b0f1dc95002557569af40",
-            help = "RPC endpoint to use",
-            env = "ETH_RPC_URL"
-        )]
-        rpc: String,
+        #[command(flatten)]
+        node: NodeArgs,
     },
 
     /// List known bridge and CEX addresses
     ListHeuristics {
+        #[arg(
+            long,
+            help = "Show the labels that apply on this chain instead of the shared ones"
+        )]
+        chain: Option<Chain>,
+
+        #[arg(
+            long,
+            value_delimiter = ',',
+            default_value = DEFAULT_LABEL_FILES,
+            help = "Region-tagged CEX label files to list along with the heuristics, comma-separated"
+        )]
+        labels: Vec<String>,
+
         #[arg(long, help = "Show only bridge addresses")]
         bridge_only: bool,
 
         #[arg(long, help = "Show only CEX addresses")]
         cex_only: bool,
     },
+
+    /// Maintain the heuristics labels and review the ones scans learned
+    Heuristics {
+        #[command(subcommand)]
+        command: HeuristicsCommand,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum HeuristicsCommand {
+    /// List queued label candidates with their evidence
+    Review {
+        #[arg(long, help = "Only show candidates seen on this chain")]
+        chain: Option<Chain>,
+
+        #[arg(
+            long,
+            default_value_t = 1,
+            help = "Only show candidates seen in at least this many transactions"
+        )]
+        min_count: u64,
+    },
+
+    /// Add a queued candidate to the heuristics file
+    Accept {
+        #[arg(help = "Address of the candidate")]
+        address: String,
+
+        #[arg(long, default_value_t = Chain::Ethereum, help = "Chain the candidate was seen on")]
+        chain: Chain,
+
+        #[arg(
+            long,
+            help = "Entity to accept when the address was queued under several"
+        )]
+        entity: Option<String>,
+    },
+
+    /// Drop a queued candidate and stop scans from queueing it again
+    Reject {
+        #[arg(help = "Address of the candidate")]
+        address: String,
+
+        #[arg(long, default_value_t = Chain::Ethereum, help = "Chain the candidate was seen on")]
+        chain: Chain,
+    },
+
+    /// Label an address by hand
+    Add {
+        #[command(flatten)]
+        label: LabelArgs,
+
+        #[arg(long, help = "Only label the address on this chain")]
+        chain: Option<Chain>,
+
+        #[command(flatten)]
+        file: HeuristicsFileArgs,
+    },
+
+    /// Remove an address from the labels
+    Remove {
+        #[arg(help = "Address to remove")]
+        address: String,
+
+        #[arg(
+            long,
+            help = "Remove it from this chain's labels instead of the shared ones"
+        )]
+        chain: Option<Chain>,
+
+        #[arg(long, help = "Only remove the labels naming this entity")]
+        entity: Option<String>,
+
+        #[command(flatten)]
+        file: HeuristicsFileArgs,
+    },
+
+    /// Import labels from a CSV or JSON label dump
+    Import {
+        #[arg(
+            value_name = "FILE",
+            help = "Label set JSON, JSON array or CSV with address and entity columns"
+        )]
+        input: String,
+
+        #[arg(
+            long,
+            value_enum,
+            help = "Dump format [default: from the file extension]"
+        )]
+        format: Option<DumpFormat>,
+
+        #[arg(
+            long,
+            help = "Import into this chain's labels instead of the shared ones"
+        )]
+        chain: Option<Chain>,
+
+        #[arg(
+            long,
+            default_value_t = LabelKind::Cex,
+            help = "Kind of rows that do not state one"
+        )]
+        kind: LabelKind,
+
+        #[arg(
+            long,
+            help = "Region of rows that do not state one [default: Global for CEX rows]"
+        )]
+        region: Option<String>,
+
+        #[arg(
+            long,
+            help = "Source of rows that do not state one [default: the file name]"
+        )]
+        source: Option<String>,
+
+        #[command(flatten)]
+        file: HeuristicsFileArgs,
+    },
+
+    /// Export the labels as JSON or CSV
+    Export {
+        #[arg(long, value_enum, default_value_t = DumpFormat::Json, help = "Output format")]
+        format: DumpFormat,
+
+        #[arg(
+            long,
+            help = "Export the labels that apply on this chain, shared ones included"
+        )]
+        chain: Option<Chain>,
+
+        #[arg(long, help = "File to write to instead of stdout")]
+        out: Option<String>,
+
+        #[command(flatten)]
+        file: HeuristicsFileArgs,
+    },
+
+    /// Show the labels added, removed or changed between two heuristics files
+    Diff {
+        #[arg(help = "Heuristics file before the change")]
+        old: String,
+
+        #[arg(help = "Heuristics file after the change")]
+        new: String,
+
+        #[arg(long, value_enum, default_value_t = ReportFormat::Table, help = "Output format")]
+        format: ReportFormat,
+    },
+
+    /// List the past versions of the heuristics file
+    History {
+        #[command(flatten)]
+        file: HeuristicsFileArgs,
+    },
+
+    /// Check addresses, duplicates and CEX/bridge conflicts, failing on any problem
+    Validate {
+        #[arg(
+            long,
+            value_delimiter = ',',
+            help = "Region-tagged label files to check along with the heuristics, comma-separated"
+        )]
+        labels: Vec<String>,
+
+        #[arg(long, value_enum, default_value_t = ReportFormat::Table, help = "Output format")]
+        format: ReportFormat,
+
+        #[command(flatten)]
+        file: HeuristicsFileArgs,
+    },
+}
+
+#[derive(Args)]
+pub struct LabelArgs {
+    #[arg(help = "Address to label")]
+    pub address: String,
+
+    #[arg(long, help = "Entity the address belongs to, e.g. Binance")]
+    pub entity: String,
+
+    #[arg(
+        long,
+        default_value_t = LabelKind::Cex,
+        help = "Kind of entity (cex, bridge, mixer, defi, sanctioned)"
+    )]
+    pub kind: LabelKind,
+
+    #[arg(
+        long,
+        help = "Region of the entity; plain CEX and bridge labels without one go to the cex/bridge maps"
+    )]
+    pub region: Option<String>,
+
+    #[arg(long, help = "Where the label comes from [default: manual]")]
+    pub source: Option<String>,
+
+    #[arg(long, help = "Confidence between 0 and 1 [default: 1]")]
+    pub confidence: Option<f32>,
+}
+
+#[derive(Args)]
+pub struct HeuristicsFileArgs {
+    #[arg(
+        long = "file",
+        default_value = HEURISTICS_PATH,
+        help = "Heuristics file to work on"
+    )]
+    pub path: String,
+}
+
+/// `rpc` when given, else the default endpoint of `chain`.
+fn rpc_url(rpc: Option<&str>, chain: Chain) -> String {
+    let default = match chain {
+        Chain::Ethereum => DEFAULT_RPC_URL,
+        Chain::Bsc => BSC_RPC_URL,
+        Chain::Polygon => POLYGON_RPC_URL,
+        Chain::Arbitrum => ARBITRUM_RPC_URL,
+        Chain::Base => BASE_RPC_URL,
+    };
+
+    rpc.unwrap_or(default).to_string()
+}
+
+/// Client for `rpc` on `chain`, refusing an endpoint that serves another
+/// chain.
+async fn connect(rpc: Option<&str>, chain: Chain) -> Result<EthereumClient> {
+    let client = EthereumClient::new(rpc_url(rpc, chain)).with_chain(chain);
+    client.check_chain().await?;
+    Ok(client)
+}
+
+/// Zone abbreviations accepted after a date, as hours east of UTC.
+const TIME_ZONES: [(&str, i32); 5] = [("UTC", 0), ("GMT", 0), ("WIB", 7), ("WITA", 8), ("WIT", 9)];
+
+fn parse_since(value: &str) -> core::result::Result<DateTime<FixedOffset>, String> {
+    parse_time(value, false)
+}
+
+fn parse_until(value: &str) -> core::result::Result<DateTime<FixedOffset>, String> {
+    parse_time(value, true)
+}
+
+/// Parses a unix timestamp, an RFC 3339 time, or `YYYY-MM-DD[ HH:MM[:SS]]`
+/// followed by an optional zone (`WIB`, `+07:00`, ...). A bare date starts
+/// at midnight, or ends at 23:59:59 when `end_of_day` is set.
+fn parse_time(
+    value: &str,
+    end_of_day: bool,
+) -> core::result::Result<DateTime<FixedOffset>, String> {
+    let value = value.trim();
+    let utc = FixedOffset::east_opt(0).expect("UTC offset");
+
+    if let Ok(timestamp) = value.parse::<i64>() {
+        return utc
+            .timestamp_opt(timestamp, 0)
+            .single()
+            .ok_or_else(|| format!("Timestamp out of range: {value}"));
+    }
+
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time);
+    }
+
+    let (text, offset) = match value.rsplit_once(' ') {
+        Some((text, zone)) => match parse_zone(zone) {
+            Some(offset) => (text.trim(), offset),
+            None => (value, utc),
+        },
+        None => (value, utc),
+    };
+
+    let naive = ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+        .or_else(|| {
+            let date = NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()?;
+            match end_of_day {
+                true => date.and_hms_opt(23, 59, 59),
+                false => date.and_hms_opt(0, 0, 0),
+            }
+        })
+        .ok_or_else(|| {
+            format!("Invalid date `{value}` (expected e.g. 2025-01-01, \"2025-01-01 08:00 WIB\" or a unix timestamp)")
+        })?;
+
+    offset
+        .from_local_datetime(&naive)
+        .single()
+        .ok_or_else(|| format!("Invalid date `{value}`"))
+}
+
+fn parse_zone(zone: &str) -> Option<FixedOffset> {
+    if let Some((_, hours)) = TIME_ZONES
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(zone))
+    {
+        return FixedOffset::east_opt(hours * 3600);
+    }
+
+    let sign = match zone.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    let digits = zone[1..].replace(':', "");
+    if digits.len() != 4 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let hours: i32 = digits[..2].parse().ok()?;
+    let minutes: i32 = digits[2..].parse().ok()?;
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+async fn resolve_time(
+    source: &dyn TransferSource,
+    time: DateTime<FixedOffset>,
+    closest: Closest,
+) -> ScanResult<u64> {
+    let timestamp = u64::try_from(time.timestamp())
+        .map_err(|_| ScanError::NoBlockAtTime(format!("before {time}")))?;
+
+    source
+        .block_by_time(timestamp, closest)
+        .await?
+        .ok_or_else(|| {
+            let side = match closest {
+                Closest::Before => "at or before",
+                Closest::After => "at or after",
+            };
+            ScanError::NoBlockAtTime(format!("{side} {time}"))
+        })
 }
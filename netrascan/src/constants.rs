@@ -1,3 +1,11 @@
 pub const API_KEY: &str = "6570a5941c65a2a7deffce485080569aa0bf85c6dc4b0f1dc95002557569af40";
-pub const DEFAULT_OFFSET: usize = 1000;
+pub const DEFAULT_RPC_URL: &str =
+    "https://rpc.ankr.com/eth/6570a5941c65a2a7deffce485080569aa0bf85c6dc4b0f1dc95002557569af40";
 pub const HEURISTICS_PATH: &str = "netrascan/data/heuristics/heuristics.json";
+pub const TOOL_VERSION: &str = concat!("netrascan ", env!("CARGO_PKG_VERSION"));
+pub const REVIEW_QUEUE_PATH: &str = "netrascan/data/heuristics/review-queue.json";
+pub const MAX_CANDIDATE_EVIDENCE: usize = 10;
+pub const DEFAULT_ADDRESS_CACHE: &str = "netracrawl/data/address-kinds.json";
+pub const DEFAULT_HISTORY_DIR: &str = "netracrawl/data/history";
+pub const MAX_TRACKED_TOKENS: usize = 25;
+pub const CONTRACT_COUNTERPARTY_WEIGHT: f32 = 0.5;
@@ -7,6 +7,10 @@ pub enum Error {
     EmptyTransaction(String),
     ParsingError(serde_json::Error),
     IoError(std::io::Error, String),
+    NoBlockAtTime(String),
+    UnknownCandidate(String),
+    InvalidLabels(String),
+    UnknownLabel(String),
 }
 
 impl std::error::Error for Error {}
@@ -20,6 +24,12 @@ impl std::fmt::Display for Error {
                 write!(f, "ℹ️ Wallet {wallet} has no token transfer history.")
             }
             Self::ParsingError(err) => write!(f, "{err}"),
+            Self::NoBlockAtTime(detail) => write!(f, "❌ No block found {detail}"),
+            Self::UnknownCandidate(address) => {
+                write!(f, "❌ No label candidate queued for {address}")
+            }
+            Self::InvalidLabels(detail) => write!(f, "❌ Invalid labels: {detail}"),
+            Self::UnknownLabel(address) => write!(f, "❌ No label found for {address}"),
             Self::IoError(err, context) => {
                 if context.is_empty() {
                     write!(f, "{err}")
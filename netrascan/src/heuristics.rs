@@ -1,15 +1,21 @@
 use common::model::TxRecord;
 
-pub fn calculate_score(txs: &[TxRecord]) -> f32 {
+use crate::classification::WalletKinds;
+
+/// Sums what each transfer suggests about where the wallet's owner is.
+/// With `kinds`, transfers with a contract counterparty count for less.
+pub fn calculate_score(txs: &[TxRecord], kinds: Option<&WalletKinds>) -> f32 {
     let mut score = 0f32;
 
     for tx in txs {
+        let weight = kinds.map_or(1.0, |kinds| kinds.weight(tx));
+
         if tx.token_symbol == "IDRT" || tx.token_symbol == "BIDR" {
-            score += 0.5;
+            score += 0.5 * weight;
         }
 
         if tx.to.contains("binance") || tx.to.contains("kucoin") || tx.to.contains("okx") {
-            score -= 0.5;
+            score -= 0.5 * weight;
         }
 
         // TODO: Add timezone heuristic if timestamp is decoded.
@@ -0,0 +1,452 @@
+use std::{
+    fs::File,
+    io::{stdout, Write},
+    path::Path,
+};
+
+use chrono::Utc;
+use common::{
+    model::{Chain, LabelKind},
+    utils::{default_region, Heuristics, Label, LabelMetadata},
+};
+use netrascan::{
+    constants::{HEURISTICS_PATH, REVIEW_QUEUE_PATH},
+    error::{Error, Result},
+    labels::{self, is_valid_address, DumpDefaults, DumpFormat, ReportFormat},
+    model::{ChangeKind, LabelEntry},
+    review::ReviewQueue,
+};
+
+use crate::cli::{HeuristicsFileArgs, LabelArgs};
+
+/// Source recorded on labels promoted from the review queue.
+const REVIEW_SOURCE: &str = "netrascan review";
+
+/// Source recorded on labels added by hand.
+const MANUAL_SOURCE: &str = "manual";
+
+/// Lists queued label candidates seen in at least `min_count` transactions.
+pub fn review(chain: Option<Chain>, min_count: u64) -> Result<()> {
+    let queue = ReviewQueue::load(REVIEW_QUEUE_PATH)?;
+    let pending: Vec<_> = queue
+        .pending(chain)
+        .into_iter()
+        .filter(|candidate| candidate.count >= min_count)
+        .collect();
+
+    if pending.is_empty() {
+        println!("✅ No label candidates waiting for review");
+        return Ok(());
+    }
+
+    println!("📝 {} label candidates waiting for review:", pending.len());
+    for candidate in pending {
+        let region = candidate
+            .region
+            .clone()
+            .or_else(|| default_region(candidate.kind))
+            .map(|region| format!(" ({region})"))
+            .unwrap_or_default();
+        let blocks = match (candidate.first_seen_block, candidate.last_seen_block) {
+            (Some(first), Some(last)) => format!(", blocks {first}..={last}"),
+            _ => String::new(),
+        };
+        println!(
+            "\n- {} [{}] → {} {}{region}, {} txs{blocks}",
+            candidate.address, candidate.chain, candidate.entity, candidate.kind, candidate.count
+        );
+        for evidence in &candidate.evidence {
+            let block = evidence
+                .block
+                .map(|block| format!(" in block {block}"))
+                .unwrap_or_default();
+            println!(
+                "  • {} → {} ({}){block}",
+                evidence.tx_hash, evidence.counterparty, evidence.entity
+            );
+        }
+    }
+
+    println!("\nUse `netrascan heuristics accept <ADDRESS>` or `reject <ADDRESS>` to settle them.");
+
+    Ok(())
+}
+
+/// Promotes the candidate for `address` into the `chain` labels of the
+/// heuristics file.
+pub fn accept(address: &str, chain: Chain, entity: Option<&str>) -> Result<()> {
+    let mut queue = ReviewQueue::load(REVIEW_QUEUE_PATH)?;
+    let candidate = queue.accept(chain, address, entity)?;
+
+    let metadata = LabelMetadata {
+        region: candidate
+            .region
+            .clone()
+            .or_else(|| default_region(candidate.kind)),
+        source: REVIEW_SOURCE.to_string(),
+        last_updated: today(),
+        kind: candidate.kind,
+    };
+    let label = Label {
+        name: candidate.entity.clone(),
+        address: candidate.address.clone(),
+        kind: None,
+        confidence: None,
+    };
+
+    let mut heuristics = Heuristics::load_or_default(HEURISTICS_PATH)?;
+    let region = metadata
+        .region
+        .as_ref()
+        .map(|region| format!(" ({region})"))
+        .unwrap_or_default();
+    heuristics.chain_mut(chain).add_label(metadata, label);
+    let version = heuristics.save_version(HEURISTICS_PATH)?;
+    queue.save(REVIEW_QUEUE_PATH)?;
+
+    println!(
+        "✅ Labelled {} on {chain} as {} {}{region}, heuristics now {version}",
+        candidate.address, candidate.entity, candidate.kind
+    );
+
+    Ok(())
+}
+
+/// Drops the candidates for `address` and keeps scans from queueing it again.
+pub fn reject(address: &str, chain: Chain) -> Result<()> {
+    let mut queue = ReviewQueue::load(REVIEW_QUEUE_PATH)?;
+    let dropped = queue.reject(chain, address)?;
+    queue.save(REVIEW_QUEUE_PATH)?;
+
+    println!(
+        "🗑️ Rejected {dropped} candidates for {} on {chain}",
+        address.to_ascii_lowercase()
+    );
+
+    Ok(())
+}
+
+/// Labels `label.address` in the heuristics file, on `chain` only when given.
+pub fn add(label: &LabelArgs, chain: Option<Chain>, file: &HeuristicsFileArgs) -> Result<()> {
+    if !is_valid_address(&label.address) {
+        return Err(Error::InvalidLabels(format!(
+            "invalid address `{}`",
+            label.address
+        )));
+    }
+    if label
+        .confidence
+        .is_some_and(|confidence| !(0.0..=1.0).contains(&confidence))
+    {
+        return Err(Error::InvalidLabels(
+            "confidence must be between 0 and 1".to_string(),
+        ));
+    }
+
+    let mut heuristics = Heuristics::load_or_default(&file.path)?;
+    let target = scope(&mut heuristics, chain);
+
+    let plain = label.region.is_none() && label.source.is_none() && label.confidence.is_none();
+    let map = match label.kind {
+        LabelKind::Cex if plain => Some(&target.cex),
+        LabelKind::Bridge if plain => Some(&target.bridge),
+        _ => None,
+    };
+
+    match map {
+        Some(map) => {
+            let listed = map.get(&label.entity).is_some_and(|addresses| {
+                addresses
+                    .iter()
+                    .any(|known| known.eq_ignore_ascii_case(&label.address))
+            });
+
+            if !listed && label.kind == LabelKind::Cex {
+                target.push_cex(&label.entity, &label.address);
+            } else if !listed {
+                target.push_bridge(&label.entity, &label.address);
+            }
+        }
+        None => {
+            let metadata = LabelMetadata {
+                region: label.region.clone().or_else(|| default_region(label.kind)),
+                source: label
+                    .source
+                    .clone()
+                    .unwrap_or_else(|| MANUAL_SOURCE.to_string()),
+                last_updated: today(),
+                kind: label.kind,
+            };
+            let entry = Label {
+                name: label.entity.clone(),
+                address: label.address.clone(),
+                kind: None,
+                confidence: label.confidence,
+            };
+            target.add_label(metadata, entry);
+        }
+    }
+
+    let version = heuristics.save_version(&file.path)?;
+
+    println!(
+        "✅ Labelled {} as {} {} on {}, heuristics now {version}",
+        label.address,
+        label.entity,
+        label.kind,
+        scope_name(chain)
+    );
+
+    Ok(())
+}
+
+/// Removes the labels of `address`, only those naming `entity` when given.
+pub fn remove(
+    address: &str,
+    chain: Option<Chain>,
+    entity: Option<&str>,
+    file: &HeuristicsFileArgs,
+) -> Result<()> {
+    let mut heuristics = Heuristics::load(&file.path)?;
+    let removed = scope(&mut heuristics, chain).remove_address(address, entity);
+
+    if removed == 0 {
+        return Err(Error::UnknownLabel(format!(
+            "{address} on {}",
+            scope_name(chain)
+        )));
+    }
+
+    let version = heuristics.save_version(&file.path)?;
+
+    println!(
+        "🗑️ Removed {removed} labels of {address} from {}, heuristics now {version}",
+        scope_name(chain)
+    );
+
+    Ok(())
+}
+
+/// Imports every label of the dump at `input`. Rows without a kind, region
+/// or source take the given ones, and the source defaults to the file name.
+/// Exchange rows left without a region fall back to the untagged one.
+pub fn import(
+    input: &str,
+    format: Option<DumpFormat>,
+    chain: Option<Chain>,
+    kind: LabelKind,
+    region: Option<&str>,
+    source: Option<&str>,
+    file: &HeuristicsFileArgs,
+) -> Result<()> {
+    let path = Path::new(input);
+    let defaults = DumpDefaults {
+        kind,
+        region: region.map(str::to_string),
+        source: source
+            .map(str::to_string)
+            .or_else(|| {
+                path.file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+            })
+            .unwrap_or_else(|| input.to_string()),
+        last_updated: today(),
+    };
+
+    let format = format.unwrap_or_else(|| DumpFormat::of(path));
+    let dump = labels::read_dump(path, format, &defaults)?;
+
+    let mut heuristics = Heuristics::load_or_default(&file.path)?;
+    let target = scope(&mut heuristics, chain);
+    let count = dump.len();
+    for (metadata, label) in dump {
+        target.add_label(metadata, label);
+    }
+
+    let version = heuristics.save_version(&file.path)?;
+
+    println!(
+        "📥 Imported {count} labels from {input} into {}, heuristics now {version}",
+        scope_name(chain)
+    );
+
+    Ok(())
+}
+
+/// Writes the labels, or those that apply on `chain`, to `out` or stdout.
+pub fn export(
+    format: DumpFormat,
+    chain: Option<Chain>,
+    out: Option<&str>,
+    file: &HeuristicsFileArgs,
+) -> Result<()> {
+    let heuristics = Heuristics::load(&file.path)?;
+
+    let mut writer: Box<dyn Write> = match out {
+        Some(out) => Box::new(
+            File::create(out)
+                .map_err(|err| Error::from_io(err, format!("❌ Failed to create {out}")))?,
+        ),
+        None => Box::new(stdout().lock()),
+    };
+
+    match format {
+        DumpFormat::Json => {
+            let view = match chain {
+                Some(chain) => heuristics.for_chain(chain),
+                None => heuristics,
+            };
+            serde_json::to_writer_pretty(&mut writer, &view)?;
+            writeln!(writer)?;
+        }
+        DumpFormat::Csv => {
+            let entries: Vec<LabelEntry> = labels::entries(&heuristics)
+                .into_iter()
+                .filter(|entry| chain.is_none() || entry.chain.is_none() || entry.chain == chain)
+                .collect();
+            labels::write_csv(&mut writer, &entries)?;
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Prints the labels added, removed or changed between two heuristics files.
+pub fn diff(old: &str, new: &str, format: ReportFormat) -> Result<()> {
+    let changes = labels::diff(&Heuristics::load(old)?, &Heuristics::load(new)?);
+
+    if format == ReportFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&changes)?);
+        return Ok(());
+    }
+
+    if changes.is_empty() {
+        println!("✅ No label changes between {old} and {new}");
+        return Ok(());
+    }
+
+    let mut counts = [0; 3];
+    for change in &changes {
+        let (sign, at) = match change.change {
+            ChangeKind::Added => ('+', 0),
+            ChangeKind::Removed => ('-', 1),
+            ChangeKind::Changed => ('~', 2),
+        };
+        counts[at] += 1;
+
+        let label = match &change.previous {
+            Some(previous) => format!("{} → {}", describe(previous), describe(&change.entry)),
+            None => describe(&change.entry),
+        };
+        println!(
+            "{sign} {:<10} {:<42} {label}",
+            scope_name(change.entry.chain),
+            change.entry.address
+        );
+    }
+
+    println!(
+        "\n{} added, {} removed, {} changed",
+        counts[0], counts[1], counts[2]
+    );
+
+    Ok(())
+}
+
+/// Checks the heuristics file, with `label_files` merged in, and fails when
+/// anything is wrong.
+pub fn validate(
+    label_files: &[String],
+    format: ReportFormat,
+    file: &HeuristicsFileArgs,
+) -> Result<()> {
+    let mut heuristics = Heuristics::load(&file.path)?;
+    heuristics.import_label_files(label_files)?;
+    let issues = labels::validate(&heuristics);
+
+    match format {
+        ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&issues)?),
+        ReportFormat::Table if issues.is_empty() => println!(
+            "✅ {} labels in {} look fine",
+            labels::entries(&heuristics).len(),
+            file.path
+        ),
+        ReportFormat::Table => {
+            for issue in &issues {
+                println!(
+                    "{:<16} {:<10} {:<42} {}",
+                    issue.issue.to_string(),
+                    scope_name(issue.chain),
+                    issue.address,
+                    issue.detail
+                );
+            }
+        }
+    }
+
+    if !issues.is_empty() {
+        return Err(Error::InvalidLabels(format!(
+            "{} problems in {}",
+            issues.len(),
+            file.path
+        )));
+    }
+
+    Ok(())
+}
+
+/// The shared labels, or `chain`'s own.
+fn scope(heuristics: &mut Heuristics, chain: Option<Chain>) -> &mut Heuristics {
+    match chain {
+        Some(chain) => heuristics.chain_mut(chain),
+        None => heuristics,
+    }
+}
+
+fn scope_name(chain: Option<Chain>) -> String {
+    chain
+        .map(|chain| chain.to_string())
+        .unwrap_or_else(|| "all chains".to_string())
+}
+
+/// `kind Entity (Region) from source`.
+fn describe(entry: &LabelEntry) -> String {
+    let region = entry
+        .region
+        .as_deref()
+        .map(|region| format!(" ({region})"))
+        .unwrap_or_default();
+    format!(
+        "{} {}{region} from {}",
+        entry.kind, entry.entity, entry.source
+    )
+}
+
+fn today() -> String {
+    Utc::now().format("%Y-%m-%d").to_string()
+}
+
+/// Lists the versions kept for the heuristics file.
+pub fn history(file: &HeuristicsFileArgs) -> Result<()> {
+    let versions = Heuristics::history(&file.path)?;
+
+    if versions.is_empty() {
+        println!("ℹ️ No versions of {} recorded yet", file.path);
+        return Ok(());
+    }
+
+    let current = Heuristics::load_or_default(&file.path)?.version_info()?;
+    println!("📚 Versions of {}:", file.path);
+    for (version, path) in versions {
+        let marker = if version == current { " (current)" } else { "" };
+        println!(
+            "- v{} {}{marker}: {}",
+            version.version,
+            version.hash,
+            path.display()
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,462 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    io::Write,
+    path::Path,
+};
+
+use clap::ValueEnum;
+use common::{
+    model::{Chain, LabelKind},
+    utils::{default_region, Heuristics, Label, LabelMetadata, LabelSet},
+};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    error::{Error, Result},
+    model::{ChangeKind, IssueKind, LabelChange, LabelEntry, LabelIssue},
+};
+
+/// Layout of a label dump read by `heuristics import` or written by
+/// `heuristics export`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DumpFormat {
+    Json,
+    Csv,
+}
+
+impl DumpFormat {
+    /// Format implied by the extension of `path`, JSON unless it is `.csv`.
+    pub fn of(path: &Path) -> DumpFormat {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("csv") => DumpFormat::Csv,
+            _ => DumpFormat::Json,
+        }
+    }
+}
+
+/// How `heuristics diff` and `heuristics validate` print their findings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    Table,
+    Json,
+}
+
+/// Metadata for dump rows that do not carry their own.
+#[derive(Debug, Clone)]
+pub struct DumpDefaults {
+    pub kind: LabelKind,
+    pub region: Option<String>,
+    pub source: String,
+    pub last_updated: String,
+}
+
+/// A row of a CSV or flat JSON label dump.
+#[derive(Deserialize)]
+struct LabelRow {
+    address: String,
+    #[serde(alias = "name", alias = "label")]
+    entity: String,
+    #[serde(default)]
+    kind: Option<String>,
+    #[serde(default)]
+    region: Option<String>,
+    #[serde(default)]
+    confidence: Option<f32>,
+    #[serde(default)]
+    source: Option<String>,
+}
+
+/// Whether `address` is a `0x`-prefixed 20-byte hex address.
+pub fn is_valid_address(address: &str) -> bool {
+    address
+        .strip_prefix("0x")
+        .is_some_and(|hex| hex.len() == 40 && hex.bytes().all(|b| b.is_ascii_hexdigit()))
+}
+
+/// Every label in `heuristics`, the shared ones first and then each chain's
+/// own, sorted by kind and entity within each.
+pub fn entries(heuristics: &Heuristics) -> Vec<LabelEntry> {
+    let scopes = [(None, heuristics)].into_iter().chain(
+        heuristics
+            .chains
+            .iter()
+            .map(|(chain, own)| (Some(*chain), own)),
+    );
+
+    let mut entries: Vec<LabelEntry> = scopes
+        .flat_map(|(chain, scope)| {
+            scope
+                .address_labels()
+                .into_iter()
+                .map(move |(address, label)| LabelEntry {
+                    chain,
+                    address: address.to_string(),
+                    entity: label.entity,
+                    kind: label.kind,
+                    region: label.region,
+                    confidence: label.confidence,
+                    source: label.source,
+                })
+        })
+        .collect();
+
+    entries.sort_by(|a, b| {
+        (a.chain, a.kind, &a.entity, &a.address).cmp(&(b.chain, b.kind, &b.entity, &b.address))
+    });
+    entries
+}
+
+/// Malformed addresses, addresses labelled twice with the same kind, and
+/// addresses labelled both as an exchange and as a bridge. A chain's own
+/// labels are checked together with the shared ones they are merged with.
+pub fn validate(heuristics: &Heuristics) -> Vec<LabelIssue> {
+    let entries = entries(heuristics);
+    let mut issues = Vec::new();
+
+    for entry in &entries {
+        if !is_valid_address(&entry.address) {
+            issues.push(LabelIssue {
+                issue: IssueKind::InvalidAddress,
+                chain: entry.chain,
+                address: entry.address.clone(),
+                detail: format!("{} {} from {}", entry.entity, entry.kind, entry.source),
+            });
+        }
+    }
+
+    let mut scopes: Vec<Option<Chain>> = entries.iter().map(|entry| entry.chain).collect();
+    scopes.dedup();
+
+    for scope in scopes {
+        let mut by_address: BTreeMap<String, Vec<&LabelEntry>> = BTreeMap::new();
+        for entry in &entries {
+            if entry.chain.is_none() || entry.chain == scope {
+                by_address
+                    .entry(entry.address.to_ascii_lowercase())
+                    .or_default()
+                    .push(entry);
+            }
+        }
+
+        for (address, labels) in by_address {
+            // Conflicts among shared labels are reported once, not per chain.
+            if scope.is_some() && labels.iter().all(|entry| entry.chain.is_none()) {
+                continue;
+            }
+
+            let mut by_kind: BTreeMap<LabelKind, Vec<&LabelEntry>> = BTreeMap::new();
+            for entry in &labels {
+                by_kind.entry(entry.kind).or_default().push(entry);
+            }
+
+            for (kind, same) in &by_kind {
+                let shared = same.iter().all(|entry| entry.chain.is_none());
+                if same.len() > 1 && !(scope.is_some() && shared) {
+                    issues.push(LabelIssue {
+                        issue: IssueKind::Duplicate,
+                        chain: scope,
+                        address: address.clone(),
+                        detail: format!("{} {kind} labels: {}", same.len(), describe(same)),
+                    });
+                }
+            }
+
+            if by_kind.contains_key(&LabelKind::Cex) && by_kind.contains_key(&LabelKind::Bridge) {
+                issues.push(LabelIssue {
+                    issue: IssueKind::CexAndBridge,
+                    chain: scope,
+                    address,
+                    detail: describe(&labels),
+                });
+            }
+        }
+    }
+
+    issues
+}
+
+/// Labels added, removed or changed going from `old` to `new`. Labels are
+/// matched by chain, address, kind and source.
+pub fn diff(old: &Heuristics, new: &Heuristics) -> Vec<LabelChange> {
+    let old = keyed(entries(old));
+    let mut new = keyed(entries(new));
+    let mut changes = Vec::new();
+
+    for (key, before) in old {
+        match new.remove(&key) {
+            Some(after) => {
+                if after.entity != before.entity
+                    || after.region != before.region
+                    || after.confidence != before.confidence
+                {
+                    changes.push(LabelChange {
+                        change: ChangeKind::Changed,
+                        entry: after,
+                        previous: Some(before),
+                    });
+                }
+            }
+            None => changes.push(LabelChange {
+                change: ChangeKind::Removed,
+                entry: before,
+                previous: None,
+            }),
+        }
+    }
+
+    changes.extend(new.into_values().map(|entry| LabelChange {
+        change: ChangeKind::Added,
+        entry,
+        previous: None,
+    }));
+
+    changes.sort_by(|a, b| {
+        (a.entry.chain, &a.entry.address, a.entry.kind).cmp(&(
+            b.entry.chain,
+            &b.entry.address,
+            b.entry.kind,
+        ))
+    });
+    changes
+}
+
+type EntryKey = (Option<Chain>, String, LabelKind, String);
+
+/// Entries by their diff key, keeping the first of any duplicates.
+fn keyed(entries: Vec<LabelEntry>) -> HashMap<EntryKey, LabelEntry> {
+    let mut keyed = HashMap::new();
+    for entry in entries {
+        let key = (
+            entry.chain,
+            entry.address.to_ascii_lowercase(),
+            entry.kind,
+            entry.source.clone(),
+        );
+        keyed.entry(key).or_insert(entry);
+    }
+    keyed
+}
+
+fn describe(entries: &[&LabelEntry]) -> String {
+    entries
+        .iter()
+        .map(|entry| format!("{} {} ({})", entry.entity, entry.kind, entry.source))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Reads a label dump: a label set file like `data/global-cex.json`, a JSON
+/// array of rows, or a CSV file with an `address` and an `entity` (or
+/// `name`) column, optionally followed by `kind`, `region`, `confidence` and
+/// `source`. Rows without their own metadata take it from `defaults`.
+pub fn read_dump(
+    path: &Path,
+    format: DumpFormat,
+    defaults: &DumpDefaults,
+) -> Result<Vec<(LabelMetadata, Label)>> {
+    let text = fs::read_to_string(path)
+        .map_err(|err| Error::from_io(err, format!("❌ Failed to read {}", path.display())))?;
+
+    let rows: Vec<LabelRow> = match format {
+        DumpFormat::Csv => {
+            let mut reader = csv::ReaderBuilder::new()
+                .trim(csv::Trim::All)
+                .from_reader(text.as_bytes());
+            reader
+                .deserialize()
+                .collect::<core::result::Result<_, _>>()
+                .map_err(|err| Error::InvalidLabels(format!("{}: {err}", path.display())))?
+        }
+        DumpFormat::Json => match serde_json::from_str::<Value>(&text)? {
+            Value::Object(object) if object.contains_key("metadata") => {
+                let set: LabelSet = serde_json::from_value(Value::Object(object))?;
+                return check_dump(path, labels_of_set(set));
+            }
+            rows => serde_json::from_value(rows)?,
+        },
+    };
+
+    let mut labels = Vec::with_capacity(rows.len());
+    for row in rows {
+        let kind = match row.kind.as_deref().filter(|kind| !kind.is_empty()) {
+            Some(kind) => kind
+                .parse()
+                .map_err(|err| Error::InvalidLabels(format!("{}: {err}", path.display())))?,
+            None => defaults.kind,
+        };
+        let metadata = LabelMetadata {
+            region: row
+                .region
+                .or_else(|| defaults.region.clone())
+                .or_else(|| default_region(kind)),
+            source: row.source.unwrap_or_else(|| defaults.source.clone()),
+            last_updated: defaults.last_updated.clone(),
+            kind,
+        };
+        let label = Label {
+            name: row.entity,
+            address: row.address,
+            kind: None,
+            confidence: row.confidence,
+        };
+        labels.push((metadata, label));
+    }
+
+    check_dump(path, labels)
+}
+
+fn labels_of_set(set: LabelSet) -> Vec<(LabelMetadata, Label)> {
+    let metadata = set.metadata;
+    set.data
+        .into_iter()
+        .map(|label| (metadata.clone(), label))
+        .collect()
+}
+
+/// Refuses a dump with a malformed address, before any of it is imported.
+fn check_dump(
+    path: &Path,
+    labels: Vec<(LabelMetadata, Label)>,
+) -> Result<Vec<(LabelMetadata, Label)>> {
+    if let Some((_, label)) = labels
+        .iter()
+        .find(|(_, label)| !is_valid_address(&label.address))
+    {
+        return Err(Error::InvalidLabels(format!(
+            "{}: invalid address `{}` for {}",
+            path.display(),
+            label.address,
+            label.name
+        )));
+    }
+
+    Ok(labels)
+}
+
+/// Writes `entries` as CSV with a header row.
+pub fn write_csv<W>(writer: W, entries: &[LabelEntry]) -> Result<()>
+where
+    W: Write,
+{
+    let mut writer = csv::Writer::from_writer(writer);
+    for entry in entries {
+        writer
+            .serialize(entry)
+            .map_err(|err| Error::InvalidLabels(err.to_string()))?;
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BINANCE: &str = "0x28c6c06298d514db089934071355e5743bf21d60";
+    const KUCOIN: &str = "0xd6216fc19db775df9774a6e33526131da7d19a2c";
+    const ARBITRUM_BRIDGE: &str = "0x8315177ab297ba92a06054ce80a67ed4dbd7ed3a";
+
+    fn found(issues: &[LabelIssue]) -> Vec<(IssueKind, Option<Chain>, &str)> {
+        issues
+            .iter()
+            .map(|issue| (issue.issue, issue.chain, issue.address.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn validate_reports_bad_addresses_duplicates_and_cex_bridges() {
+        let mut heuristics = Heuristics::new();
+        heuristics.push_cex("Binance", BINANCE);
+        let upper = format!("0x{}", BINANCE[2..].to_ascii_uppercase());
+        heuristics.push_cex("Binance 14", upper);
+        heuristics.push_cex("KuCoin", KUCOIN);
+        heuristics.push_cex("Arbitrum", ARBITRUM_BRIDGE);
+        heuristics.push_bridge("Arbitrum", ARBITRUM_BRIDGE);
+        heuristics.push_cex("Broken", "0x1234");
+
+        let issues = validate(&heuristics);
+        let mut found = found(&issues);
+        found.sort_by_key(|(issue, _, address)| (*address, issue.to_string()));
+
+        assert_eq!(
+            found,
+            [
+                (IssueKind::InvalidAddress, None, "0x1234"),
+                (IssueKind::Duplicate, None, BINANCE),
+                (IssueKind::CexAndBridge, None, ARBITRUM_BRIDGE),
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_checks_chain_labels_against_the_shared_ones_once() {
+        let mut heuristics = Heuristics::new();
+        heuristics.push_cex("Binance", BINANCE);
+        heuristics.push_cex("Binance 14", BINANCE);
+        heuristics.push_cex("KuCoin", KUCOIN);
+        heuristics
+            .chain_mut(Chain::Bsc)
+            .push_bridge("KuCoin Bridge", KUCOIN);
+        heuristics.chain_mut(Chain::Base).push_cex("KuCoin", KUCOIN);
+
+        let issues = validate(&heuristics);
+
+        assert_eq!(
+            found(&issues),
+            [
+                (IssueKind::Duplicate, None, BINANCE),
+                (IssueKind::CexAndBridge, Some(Chain::Bsc), KUCOIN),
+                (IssueKind::Duplicate, Some(Chain::Base), KUCOIN),
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_reports_added_removed_and_changed_labels() {
+        let mut old = Heuristics::new();
+        old.push_cex("Binance", BINANCE);
+        old.push_cex("KuCoin", KUCOIN);
+
+        let mut new = Heuristics::new();
+        new.push_cex("Binance 14", BINANCE);
+        new.push_bridge("Arbitrum", ARBITRUM_BRIDGE);
+        new.chain_mut(Chain::Bsc).push_cex("KuCoin", KUCOIN);
+
+        let changes = diff(&old, &new);
+        let changes: Vec<(ChangeKind, Option<Chain>, &str, &str)> = changes
+            .iter()
+            .map(|change| {
+                (
+                    change.change,
+                    change.entry.chain,
+                    change.entry.address.as_str(),
+                    change.entry.entity.as_str(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            changes,
+            [
+                (ChangeKind::Changed, None, BINANCE, "Binance 14"),
+                (ChangeKind::Added, None, ARBITRUM_BRIDGE, "Arbitrum"),
+                (ChangeKind::Removed, None, KUCOIN, "KuCoin"),
+                (ChangeKind::Added, Some(Chain::Bsc), KUCOIN, "KuCoin"),
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_of_identical_heuristics_is_empty() {
+        let mut heuristics = Heuristics::new();
+        heuristics.push_cex("Binance", BINANCE);
+        heuristics
+            .chain_mut(Chain::Bsc)
+            .push_bridge("Arbitrum", ARBITRUM_BRIDGE);
+
+        assert!(diff(&heuristics, &heuristics).is_empty());
+    }
+}
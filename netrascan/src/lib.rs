@@ -1,7 +1,10 @@
+pub mod balance;
 pub mod classification;
 pub mod constants;
 pub mod error;
 pub mod heuristics;
 pub mod input;
+pub mod labels;
 pub mod model;
 pub mod output;
+pub mod review;
@@ -1,9 +1,10 @@
 mod cli;
+mod heuristics_admin;
 mod tx_analyzer;
 mod wallet_analyzer;
 
 use clap::Parser;
-use cli::{Cli, Command};
+use cli::{Cli, Command, HeuristicsCommand};
 
 use netrascan::error::Result;
 
@@ -14,43 +15,100 @@ async fn main() -> Result<()> {
     match cli.command {
         Command::Analyze {
             wallet,
-            etherscan_key,
-            start_block,
-        } => wallet_analyzer::analyze(&wallet, &etherscan_key, start_block).await?,
+            transfers,
+            balances,
+            addresses,
+        } => wallet_analyzer::analyze(&wallet, &transfers, &balances, &addresses).await?,
         Command::Batch {
             input,
-            etherscan_key,
-            start_block,
+            transfers,
+            balances,
+            addresses,
             out,
-        } => wallet_analyzer::batch(&input, &etherscan_key, start_block, &out).await?,
+        } => wallet_analyzer::batch(&input, &transfers, &balances, &addresses, &out).await?,
         Command::Crawl {
             source,
-            etherscan_key,
-            start_block,
+            transfers,
             out,
-        } => wallet_analyzer::crawl(&source, &etherscan_key, start_block, &out).await?,
+        } => wallet_analyzer::crawl(&source, &transfers, &out).await?,
         Command::Train {
             input,
-            etherscan_key,
-            start_block,
+            transfers,
+            balances,
+            addresses,
             out,
-        } => wallet_analyzer::train(&input, &etherscan_key, start_block, &out).await?,
+        } => wallet_analyzer::train(&input, &transfers, &balances, &addresses, &out).await?,
         Command::Fetch {
             wallet,
-            etherscan_key,
-            start_block,
+            transfers,
             out,
-        } => wallet_analyzer::fetch(&wallet, &etherscan_key, start_block, &out).await?,
+        } => wallet_analyzer::fetch(&wallet, &transfers, &out).await?,
         Command::Score { input } => wallet_analyzer::score(&input).await?,
-        Command::ScanLatest { rpc } => tx_analyzer::scan_latest(&rpc).await?,
-        Command::ScanBlock { block_number, rpc } => {
-            tx_analyzer::scan_block(block_number, &rpc).await?
+        Command::ScanLatest { node } => tx_analyzer::scan_latest(&node).await?,
+        Command::ScanBlock { block_number, node } => {
+            tx_analyzer::scan_block(block_number, &node).await?
         }
-        Command::Classify { tx_hash, rpc } => tx_analyzer::classify(&tx_hash, &rpc).await?,
+        Command::Classify { tx_hash, node } => tx_analyzer::classify(&tx_hash, &node).await?,
         Command::ListHeuristics {
+            chain,
+            labels,
             bridge_only,
             cex_only,
-        } => tx_analyzer::list_heuristics(bridge_only, cex_only).await?,
+        } => tx_analyzer::list_heuristics(chain, &labels, bridge_only, cex_only).await?,
+        Command::Heuristics { command } => match command {
+            HeuristicsCommand::Review { chain, min_count } => {
+                heuristics_admin::review(chain, min_count)?
+            }
+            HeuristicsCommand::Accept {
+                address,
+                chain,
+                entity,
+            } => heuristics_admin::accept(&address, chain, entity.as_deref())?,
+            HeuristicsCommand::Reject { address, chain } => {
+                heuristics_admin::reject(&address, chain)?
+            }
+            HeuristicsCommand::Add { label, chain, file } => {
+                heuristics_admin::add(&label, chain, &file)?
+            }
+            HeuristicsCommand::Remove {
+                address,
+                chain,
+                entity,
+                file,
+            } => heuristics_admin::remove(&address, chain, entity.as_deref(), &file)?,
+            HeuristicsCommand::Import {
+                input,
+                format,
+                chain,
+                kind,
+                region,
+                source,
+                file,
+            } => heuristics_admin::import(
+                &input,
+                format,
+                chain,
+                kind,
+                region.as_deref(),
+                source.as_deref(),
+                &file,
+            )?,
+            HeuristicsCommand::Export {
+                format,
+                chain,
+                out,
+                file,
+            } => heuristics_admin::export(format, chain, out.as_deref(), &file)?,
+            HeuristicsCommand::Diff { old, new, format } => {
+                heuristics_admin::diff(&old, &new, format)?
+            }
+            HeuristicsCommand::History { file } => heuristics_admin::history(&file)?,
+            HeuristicsCommand::Validate {
+                labels,
+                format,
+                file,
+            } => heuristics_admin::validate(&labels, format, &file)?,
+        },
     }
 
     Ok(())
@@ -0,0 +1,41 @@
+use std::collections::BTreeSet;
+
+use common::model::{Chain, LabelKind};
+use serde::{Deserialize, Serialize};
+
+/// An address a scan suspects belongs to a known entity, waiting for review
+/// before it is added to the heuristics.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LabelCandidate {
+    pub chain: Chain,
+    pub address: String,
+    pub kind: LabelKind,
+    /// Entity the address appears to belong to.
+    pub entity: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    /// Transactions seen supporting the label, including those beyond the
+    /// kept evidence.
+    pub count: u64,
+    pub evidence: Vec<Evidence>,
+    /// Hash of every transaction counted, so a re-scanned one is not
+    /// counted again once `evidence` is full.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub seen_txs: BTreeSet<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_seen_block: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_seen_block: Option<u64>,
+}
+
+/// A transaction behind a [`LabelCandidate`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Evidence {
+    pub tx_hash: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block: Option<u64>,
+    /// Labelled address on the other end of the transaction.
+    pub counterparty: String,
+    /// Entity of `counterparty`.
+    pub entity: String,
+}
@@ -0,0 +1,34 @@
+use common::model::{Chain, LabelKind};
+use serde::{Deserialize, Serialize};
+
+/// One label of a heuristics file, flattened for export, diffs and checks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LabelEntry {
+    /// Chain the label is limited to, `None` for the shared labels.
+    pub chain: Option<Chain>,
+    pub address: String,
+    pub entity: String,
+    pub kind: LabelKind,
+    pub region: Option<String>,
+    pub confidence: f32,
+    pub source: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// A label that differs between two heuristics files.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LabelChange {
+    pub change: ChangeKind,
+    /// The label as it is now, or as it was when removed.
+    pub entry: LabelEntry,
+    /// The label before a change.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous: Option<LabelEntry>,
+}
@@ -0,0 +1,33 @@
+use common::model::Chain;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    /// Not a `0x`-prefixed 20-byte hex address.
+    InvalidAddress,
+    /// Labelled more than once with the same kind.
+    Duplicate,
+    /// Labelled both as an exchange and as a bridge.
+    CexAndBridge,
+}
+
+impl std::fmt::Display for IssueKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IssueKind::InvalidAddress => write!(f, "invalid_address"),
+            IssueKind::Duplicate => write!(f, "duplicate"),
+            IssueKind::CexAndBridge => write!(f, "cex_and_bridge"),
+        }
+    }
+}
+
+/// A problem found while validating a heuristics file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LabelIssue {
+    pub issue: IssueKind,
+    /// Chain whose labels conflict, `None` for the shared labels.
+    pub chain: Option<Chain>,
+    pub address: String,
+    pub detail: String,
+}
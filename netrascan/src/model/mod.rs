@@ -1,5 +1,11 @@
+mod label_candidate;
+mod label_entry;
+mod label_issue;
 mod wallet_feature;
 mod wallet_report;
 
+pub use label_candidate::{Evidence, LabelCandidate};
+pub use label_entry::{ChangeKind, LabelChange, LabelEntry};
+pub use label_issue::{IssueKind, LabelIssue};
 pub use wallet_feature::WalletFeature;
 pub use wallet_report::WalletReport;
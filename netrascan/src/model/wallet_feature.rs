@@ -1,7 +1,7 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 
 use common::{
-    model::{TxRecord, TxSample},
+    model::{AddressKind, BalanceSnapshot, Provenance, TxRecord, TxSample},
     utils::dummy_price_lookup,
 };
 use serde::Serialize;
@@ -15,6 +15,24 @@ pub struct WalletFeature {
     pub interacts_with_cex: bool,
     pub used_bridges: bool,
     pub mixer_pattern_score: f32,
+    /// Native balance at the chain head, in whole coins.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub native_balance: Option<f64>,
+    /// Tracked tokens with a non-zero balance at the chain head.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tokens_held: Option<usize>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub balances: Vec<BalanceSnapshot>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address_kind: Option<AddressKind>,
+    /// Share of distinct counterparties that hold code.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contract_counterparty_ratio: Option<f32>,
+    /// Distinct counterparties of each address kind.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub counterparty_kinds: BTreeMap<AddressKind, usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
 }
 
 impl WalletFeature {
@@ -75,6 +93,51 @@ impl WalletFeature {
             interacts_with_cex,
             used_bridges,
             mixer_pattern_score,
+            native_balance: None,
+            tokens_held: None,
+            balances: vec![],
+            address_kind: None,
+            contract_counterparty_ratio: None,
+            counterparty_kinds: BTreeMap::new(),
+            provenance: None,
         }
     }
+
+    pub fn with_balances(mut self, balances: Vec<BalanceSnapshot>) -> Self {
+        let current = || balances.iter().filter(|snapshot| snapshot.block.is_none());
+
+        self.native_balance = current()
+            .find(|snapshot| snapshot.is_native())
+            .map(|snapshot| snapshot.balance as f64 / 1e18);
+        self.tokens_held = Some(
+            current()
+                .filter(|snapshot| !snapshot.is_native() && snapshot.balance > 0)
+                .count(),
+        );
+        self.balances = balances;
+        self
+    }
+
+    pub fn with_address_kinds(
+        mut self,
+        kind: AddressKind,
+        counterparty_kinds: BTreeMap<AddressKind, usize>,
+    ) -> Self {
+        let total: usize = counterparty_kinds.values().sum();
+        let with_code: usize = counterparty_kinds
+            .iter()
+            .filter(|(kind, _)| kind.has_code())
+            .map(|(_, count)| count)
+            .sum();
+
+        self.address_kind = Some(kind);
+        self.contract_counterparty_ratio = Some(with_code as f32 / total.max(1) as f32);
+        self.counterparty_kinds = counterparty_kinds;
+        self
+    }
+
+    pub fn with_provenance(mut self, provenance: Provenance) -> Self {
+        self.provenance = Some(provenance);
+        self
+    }
 }
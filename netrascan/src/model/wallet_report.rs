@@ -1,26 +1,64 @@
-use common::model::{TxRecord, WalletType};
+use std::collections::BTreeMap;
+
+use common::model::{AddressKind, BalanceSnapshot, Chain, Provenance, TxRecord, WalletType};
 use serde::Serialize;
 
 #[derive(Debug, Serialize)]
 pub struct WalletReport {
     pub wallet: String,
+    pub chain: Chain,
     pub score: f32,
     pub category: WalletType,
     pub txs: Vec<TxRecord>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub balances: Vec<BalanceSnapshot>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address_kind: Option<AddressKind>,
+    /// Distinct counterparties of each address kind.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub counterparty_kinds: BTreeMap<AddressKind, usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
 }
 
 impl WalletReport {
     pub fn new(
         wallet: impl Into<String>,
+        chain: Chain,
         score: f32,
         category: WalletType,
         txs: &[TxRecord],
     ) -> Self {
         Self {
             wallet: wallet.into(),
+            chain,
             score,
             category,
             txs: txs.to_vec(),
+            balances: vec![],
+            address_kind: None,
+            counterparty_kinds: BTreeMap::new(),
+            provenance: None,
         }
     }
+
+    pub fn with_balances(mut self, balances: Vec<BalanceSnapshot>) -> Self {
+        self.balances = balances;
+        self
+    }
+
+    pub fn with_address_kinds(
+        mut self,
+        kind: AddressKind,
+        counterparty_kinds: BTreeMap<AddressKind, usize>,
+    ) -> Self {
+        self.address_kind = Some(kind);
+        self.counterparty_kinds = counterparty_kinds;
+        self
+    }
+
+    pub fn with_provenance(mut self, provenance: Provenance) -> Self {
+        self.provenance = Some(provenance);
+        self
+    }
 }
@@ -0,0 +1,311 @@
+use std::{collections::BTreeSet, fs, path::Path};
+
+use common::{
+    model::{Chain, LabelKind, TxEthereum},
+    utils::Heuristics,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    constants::MAX_CANDIDATE_EVIDENCE,
+    error::{Error, Result},
+    model::{Evidence, LabelCandidate},
+};
+
+/// Labels learned by transaction scans, kept apart from the heuristics until
+/// someone accepts them.
+///
+/// Scans only ever add here. An unlabelled address sending to a labelled
+/// exchange is most likely one of its deposit addresses, so it is queued as
+/// that exchange with the transaction as evidence; seeing it again bumps the
+/// count. Rejected addresses are remembered so later scans do not queue them
+/// again.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ReviewQueue {
+    pub candidates: Vec<LabelCandidate>,
+    /// `chain:address` of every rejected candidate.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub rejected: BTreeSet<String>,
+}
+
+impl ReviewQueue {
+    /// Loads the queue at `path`, empty when there is none yet.
+    pub fn load<P>(path: P) -> Result<ReviewQueue>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(ReviewQueue::default());
+        }
+
+        let text = fs::read_to_string(path)
+            .map_err(|err| Error::from_io(err, format!("❌ Failed to read {}", path.display())))?;
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    pub fn save<P>(&self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, serde_json::to_vec_pretty(self)?)?;
+        fs::rename(tmp_path, path)?;
+
+        Ok(())
+    }
+
+    /// Queues what `tx` suggests about its sender, given the labels `known`
+    /// on `chain`. Returns whether a candidate was added or strengthened.
+    pub fn observe(&mut self, chain: Chain, tx: &TxEthereum, known: &Heuristics) -> bool {
+        let Some(to) = tx.to.as_deref() else {
+            return false;
+        };
+        let from = tx.from.to_ascii_lowercase();
+
+        if !known.labels_of(&from).is_empty() || self.is_rejected(chain, &from) {
+            return false;
+        }
+
+        let Some(exchange) = known.registry().label(to, LabelKind::Cex) else {
+            return false;
+        };
+
+        let block = tx.mined_block();
+        let evidence = Evidence {
+            tx_hash: tx.hash.to_ascii_lowercase(),
+            block,
+            counterparty: to.to_ascii_lowercase(),
+            entity: exchange.entity.clone(),
+        };
+
+        let existing = self.candidates.iter_mut().find(|candidate| {
+            candidate.chain == chain
+                && candidate.address == from
+                && candidate.entity.eq_ignore_ascii_case(&exchange.entity)
+        });
+
+        let candidate = match existing {
+            Some(candidate) => candidate,
+            None => {
+                self.candidates.push(LabelCandidate {
+                    chain,
+                    address: from,
+                    kind: LabelKind::Cex,
+                    entity: exchange.entity.clone(),
+                    region: exchange.region.clone(),
+                    count: 0,
+                    evidence: Vec::new(),
+                    seen_txs: BTreeSet::new(),
+                    first_seen_block: block,
+                    last_seen_block: block,
+                });
+                self.candidates.last_mut().expect("candidate just pushed")
+            }
+        };
+
+        if !candidate.seen_txs.insert(evidence.tx_hash.clone()) {
+            return false;
+        }
+
+        candidate.count += 1;
+        candidate.first_seen_block = min_block(candidate.first_seen_block, block);
+        candidate.last_seen_block = candidate.last_seen_block.max(block);
+        if candidate.evidence.len() < MAX_CANDIDATE_EVIDENCE {
+            candidate.evidence.push(evidence);
+        }
+
+        true
+    }
+
+    /// Candidates on `chain`, or on every chain, best supported first.
+    pub fn pending(&self, chain: Option<Chain>) -> Vec<&LabelCandidate> {
+        let mut pending: Vec<&LabelCandidate> = self
+            .candidates
+            .iter()
+            .filter(|candidate| chain.is_none_or(|chain| candidate.chain == chain))
+            .collect();
+        pending.sort_by(|a, b| b.count.cmp(&a.count).then(a.address.cmp(&b.address)));
+        pending
+    }
+
+    /// Removes the candidates for `address` on `chain` and returns the one
+    /// naming `entity`, or the best supported one when no entity is given.
+    pub fn accept(
+        &mut self,
+        chain: Chain,
+        address: &str,
+        entity: Option<&str>,
+    ) -> Result<LabelCandidate> {
+        let address = address.to_ascii_lowercase();
+        let (taken, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut self.candidates)
+            .into_iter()
+            .partition(|candidate| candidate.chain == chain && candidate.address == address);
+        self.candidates = kept;
+
+        let accepted = taken
+            .iter()
+            .filter(|candidate| {
+                entity.is_none_or(|entity| candidate.entity.eq_ignore_ascii_case(entity))
+            })
+            .max_by_key(|candidate| candidate.count)
+            .cloned();
+
+        match accepted {
+            Some(candidate) => Ok(candidate),
+            None => {
+                self.candidates.extend(taken);
+                Err(Error::UnknownCandidate(format!("{chain}:{address}")))
+            }
+        }
+    }
+
+    /// Drops every candidate for `address` on `chain` and keeps it from
+    /// being queued again.
+    pub fn reject(&mut self, chain: Chain, address: &str) -> Result<usize> {
+        let address = address.to_ascii_lowercase();
+        let before = self.candidates.len();
+        self.candidates
+            .retain(|candidate| !(candidate.chain == chain && candidate.address == address));
+
+        let dropped = before - self.candidates.len();
+        if dropped == 0 {
+            return Err(Error::UnknownCandidate(format!("{chain}:{address}")));
+        }
+
+        self.rejected.insert(rejected_key(chain, &address));
+        Ok(dropped)
+    }
+
+    pub fn is_rejected(&self, chain: Chain, address: &str) -> bool {
+        self.rejected.contains(&rejected_key(chain, address))
+    }
+}
+
+fn rejected_key(chain: Chain, address: &str) -> String {
+    format!("{chain}:{}", address.to_ascii_lowercase())
+}
+
+fn min_block(a: Option<u64>, b: Option<u64>) -> Option<u64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BINANCE: &str = "0x28c6c06298d514db089934071355e5743bf21d60";
+    const KUCOIN: &str = "0xd6216fc19db775df9774a6e33526131da7d19a2c";
+    const DEPOSIT: &str = "0x1111111111111111111111111111111111111111";
+
+    fn tx(hash: &str, from: &str, to: &str, block: u64) -> TxEthereum {
+        TxEthereum {
+            hash: hash.to_string(),
+            from: from.to_string(),
+            to: Some(to.to_string()),
+            input: "0x".to_string(),
+            value: "0x0".to_string(),
+            block_number: Some(format!("0x{block:x}")),
+        }
+    }
+
+    fn known() -> Heuristics {
+        let mut heuristics = Heuristics::new();
+        heuristics.push_cex("Binance", BINANCE);
+        heuristics.push_cex("KuCoin", KUCOIN);
+        heuristics
+    }
+
+    /// A queue holding `DEPOSIT` as Binance twice and as KuCoin once.
+    fn queued() -> ReviewQueue {
+        let known = known();
+        let mut queue = ReviewQueue::default();
+        queue.observe(Chain::Ethereum, &tx("0xa", DEPOSIT, BINANCE, 10), &known);
+        queue.observe(Chain::Ethereum, &tx("0xb", DEPOSIT, BINANCE, 12), &known);
+        queue.observe(Chain::Ethereum, &tx("0xc", DEPOSIT, KUCOIN, 11), &known);
+        queue
+    }
+
+    #[test]
+    fn observe_counts_each_transaction_once() {
+        let known = known();
+        let mut queue = queued();
+        let upper = format!("0x{}", DEPOSIT[2..].to_ascii_uppercase());
+
+        assert!(!queue.observe(Chain::Ethereum, &tx("0xA", &upper, BINANCE, 10), &known));
+        assert!(!queue.observe(Chain::Ethereum, &tx("0xd", BINANCE, KUCOIN, 13), &known));
+
+        let binance = queue.pending(None)[0];
+        assert_eq!(binance.entity, "Binance");
+        assert_eq!(binance.count, 2);
+        assert_eq!(
+            (binance.first_seen_block, binance.last_seen_block),
+            (Some(10), Some(12))
+        );
+    }
+
+    #[test]
+    fn accept_takes_the_best_supported_candidate_and_drops_the_rest() {
+        let mut queue = queued();
+
+        let accepted = queue
+            .accept(Chain::Ethereum, DEPOSIT, None)
+            .expect("accept");
+
+        assert_eq!(accepted.entity, "Binance");
+        assert!(queue.candidates.is_empty());
+        assert!(!queue.is_rejected(Chain::Ethereum, DEPOSIT));
+    }
+
+    #[test]
+    fn accept_by_entity_ignores_case() {
+        let mut queue = queued();
+
+        let accepted = queue
+            .accept(Chain::Ethereum, DEPOSIT, Some("kucoin"))
+            .expect("accept");
+
+        assert_eq!(accepted.entity, "KuCoin");
+        assert!(queue.candidates.is_empty());
+    }
+
+    #[test]
+    fn accept_of_an_unknown_candidate_keeps_the_queue() {
+        let mut queue = queued();
+
+        assert!(matches!(
+            queue.accept(Chain::Ethereum, DEPOSIT, Some("Kraken")),
+            Err(Error::UnknownCandidate(_))
+        ));
+        assert!(matches!(
+            queue.accept(Chain::Bsc, DEPOSIT, None),
+            Err(Error::UnknownCandidate(_))
+        ));
+        assert_eq!(queue.candidates.len(), 2);
+    }
+
+    #[test]
+    fn reject_drops_the_address_and_keeps_it_out() {
+        let known = known();
+        let mut queue = queued();
+
+        assert_eq!(queue.reject(Chain::Ethereum, DEPOSIT).expect("reject"), 2);
+        assert!(queue.is_rejected(Chain::Ethereum, DEPOSIT));
+        assert!(!queue.is_rejected(Chain::Bsc, DEPOSIT));
+
+        assert!(!queue.observe(Chain::Ethereum, &tx("0xe", DEPOSIT, BINANCE, 14), &known));
+        assert!(queue.candidates.is_empty());
+        assert!(matches!(
+            queue.reject(Chain::Ethereum, DEPOSIT),
+            Err(Error::UnknownCandidate(_))
+        ));
+    }
+}
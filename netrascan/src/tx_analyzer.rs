@@ -1,102 +1,173 @@
-use common::utils::Heuristics;
-use netracrawl::ethereum::EthereumClient;
-use netrascan::{constants::HEURISTICS_PATH, error::Result};
-
-pub async fn scan_latest(rpc: &str) -> Result<()> {
-    let client = EthereumClient::new(rpc);
-    let block_number = client.get_latest_block_number().await?;
-    let block = client.get_block_by_number(block_number).await?;
-
-    let mut heuristics = Heuristics::new();
-
-    for tx in block.transactions {
-        if let Some(to) = tx.to.as_ref() {
-            let to = to.to_ascii_lowercase();
-            let category = tx.categorize(&heuristics);
-            heuristics.push_by_tx_category(&category, &to);
-            println!("Tx {} -> {to} | category: {category:?}", tx.from);
-        }
-    }
+use std::collections::HashMap;
+
+use common::{
+    model::{AddressKind, Chain, TxEthereum},
+    utils::Heuristics,
+};
+use netracrawl::address::AddressTyper;
+use netrascan::{
+    constants::{HEURISTICS_PATH, REVIEW_QUEUE_PATH},
+    error::Result,
+    review::ReviewQueue,
+};
+
+use crate::cli::NodeArgs;
+
+pub async fn scan_latest(node: &NodeArgs) -> Result<()> {
+    let source = node.block_source().await?;
+    let block_number = source.latest_block_number().await?;
+    let block = source.block(block_number).await?;
+
+    classify_txs(node, &block.transactions).await
+}
 
-    heuristics.write(HEURISTICS_PATH)?;
+pub async fn scan_block(block_number: u64, node: &NodeArgs) -> Result<()> {
+    let source = node.block_source().await?;
+    let block = source.block(block_number).await?;
 
-    Ok(())
+    classify_txs(node, &block.transactions).await
 }
 
-pub async fn scan_block(block_number: u64, rpc: &str) -> Result<()> {
-    let client = EthereumClient::new(rpc);
-    let block = client.get_block_by_number(block_number).await?;
+pub async fn classify(tx_hash: &str, node: &NodeArgs) -> Result<()> {
+    let tx = node.block_source().await?.transaction(tx_hash).await?;
 
-    let mut heuristics = Heuristics::new();
+    classify_txs(node, std::slice::from_ref(&tx)).await
+}
 
-    for tx in block.transactions {
+/// Prints the category of each of `txs` and queues what they suggest about
+/// unlabelled addresses for review. The heuristics file is only read.
+async fn classify_txs(node: &NodeArgs, txs: &[TxEthereum]) -> Result<()> {
+    let (known, version) = node.heuristics()?;
+    let mut queue = ReviewQueue::load(REVIEW_QUEUE_PATH)?;
+    println!("📌 Heuristics {version} on {}", node.chain);
+
+    let typer = node
+        .addresses
+        .typer(node.rpc.as_deref(), node.chain)
+        .await?;
+    let kinds = recipient_kinds(typer.as_ref(), txs).await?;
+
+    let mut queued = 0;
+    for tx in txs {
         if let Some(to) = tx.to.as_ref() {
             let to = to.to_ascii_lowercase();
-            let category = tx.categorize(&heuristics);
-            heuristics.push_by_tx_category(&category, &to);
-            println!("Tx {} -> {to} | category: {category:?}", tx.from);
+            let category = tx.categorize(&known);
+            println!(
+                "Tx {} -> {to}{}{} | category: {category:?}",
+                tx.from,
+                entity_label(&known, &to),
+                kind_label(&kinds, &to)
+            );
+        }
+
+        if queue.observe(node.chain, tx, &known) {
+            queued += 1;
         }
     }
 
-    heuristics.write(HEURISTICS_PATH)?;
+    if queued > 0 {
+        queue.save(REVIEW_QUEUE_PATH)?;
+        println!("📝 Queued {queued} label candidates for review (netrascan heuristics review)");
+    }
 
     Ok(())
 }
 
-pub async fn classify(tx_hash: &str, rpc: &str) -> Result<()> {
-    let client = EthereumClient::new(rpc);
-    let tx = client.get_transaction_by_hash(tx_hash).await?;
-
-    let mut heuristics = Heuristics::new();
+/// Lists the labels shared by every chain, or those seen on `chain` when
+/// given, along with the region-tagged sets in `label_files`.
+pub async fn list_heuristics(
+    chain: Option<Chain>,
+    label_files: &[String],
+    bridge_only: bool,
+    cex_only: bool,
+) -> Result<()> {
+    let mut heuristics = Heuristics::load(HEURISTICS_PATH)?;
+    let version = heuristics.import_label_files(label_files)?;
+
+    let heuristics = match chain {
+        Some(chain) => heuristics.for_chain(chain),
+        None => heuristics,
+    };
+
+    println!("📌 Heuristics {version}\n");
+
+    if !cex_only {
+        println!("🔗 Known bridge addresses:");
+        print_labels(&heuristics.bridge);
+    }
 
-    if let Some(to) = tx.to.as_ref() {
-        let to = to.to_ascii_lowercase();
-        let category = tx.categorize(&heuristics);
-        heuristics.push_by_tx_category(&category, &to);
-        println!("Tx {} -> {to} | category: {category:?}", tx.from);
+    if !bridge_only {
+        if !cex_only {
+            println!();
+        }
+        println!("🏦 Known CEX addresses:");
+        print_labels(&heuristics.cex);
+
+        for set in &heuristics.labels {
+            let metadata = &set.metadata;
+            let region = metadata
+                .region
+                .as_ref()
+                .map(|region| format!("{region} "))
+                .unwrap_or_default();
+            println!(
+                "\n🌏 {region}{} addresses ({}, updated {}):",
+                metadata.kind, metadata.source, metadata.last_updated
+            );
+            for label in &set.data {
+                println!("- {}: {}", label.name, label.address);
+            }
+        }
     }
 
-    heuristics.write(HEURISTICS_PATH)?;
+    if !heuristics.chains.is_empty() {
+        let mut chains: Vec<String> = heuristics.chains.keys().map(Chain::to_string).collect();
+        chains.sort_unstable();
+        println!(
+            "\n⛓️  Chain-specific labels for {}, see --chain",
+            chains.join(", ")
+        );
+    }
 
     Ok(())
 }
 
-pub async fn list_heuristics(bridge_only: bool, cex_only: bool) -> Result<()> {
-    let heuristics = Heuristics::load(HEURISTICS_PATH)?;
-
-    if bridge_only {
-        println!("🔗 Known bridge addresses:");
-        for (name, addresses) in heuristics.bridge {
-            println!("- {name}:");
-            for addr in addresses {
-                println!("  • {addr}");
-            }
-        }
-    } else if cex_only {
-        println!("🏦 Known CEX addresses:");
-        for (name, addresses) in heuristics.cex {
-            println!("- {name}:");
-            for addr in addresses {
-                println!("  • {addr}");
-            }
-        }
-    } else {
-        println!("🔗 Known bridge addresses:");
-        for (name, addresses) in &heuristics.bridge {
-            println!("- {name}:");
-            for addr in addresses {
-                println!("  • {addr}");
-            }
+fn print_labels(labels: &HashMap<String, Vec<String>>) {
+    for (name, addresses) in labels {
+        println!("- {name}:");
+        for addr in addresses {
+            println!("  • {addr}");
         }
+    }
+}
 
-        println!("\n🏦 Known CEX addresses:");
-        for (name, addresses) in &heuristics.cex {
-            println!("- {name}:");
-            for addr in addresses {
-                println!("  • {addr}");
-            }
-        }
+/// Kinds of the recipients of `txs`, empty unless address typing is on.
+async fn recipient_kinds(
+    typer: Option<&AddressTyper>,
+    txs: &[TxEthereum],
+) -> Result<HashMap<String, AddressKind>> {
+    let Some(typer) = typer else {
+        return Ok(HashMap::new());
+    };
+
+    let recipients: Vec<String> = txs.iter().filter_map(|tx| tx.to.clone()).collect();
+    Ok(typer.kinds(&recipients).await?)
+}
+
+/// ` (Entity, Region)` of the strongest label on `address`.
+fn entity_label(heuristics: &Heuristics, address: &str) -> String {
+    match heuristics.labels_of(address).first() {
+        Some(label) => match &label.region {
+            Some(region) => format!(" ({}, {region})", label.entity),
+            None => format!(" ({})", label.entity),
+        },
+        None => String::new(),
     }
+}
 
-    Ok(())
+fn kind_label(kinds: &HashMap<String, AddressKind>, address: &str) -> String {
+    kinds
+        .get(address)
+        .map(|kind| format!(" [{kind}]"))
+        .unwrap_or_default()
 }
@@ -2,11 +2,14 @@ use std::{collections::HashSet, fs::create_dir_all};
 
 use chrono::{Datelike, Utc};
 
-use common::model::TxRecord;
-use netracrawl::etherscan::EtherscanClient;
+use common::model::{AddressKind, BalanceSnapshot, Chain, TxRecord};
+use netracrawl::{
+    address::AddressTyper, error::Error as DataClientError, etherscan::BlockRange,
+    source::TransferSource,
+};
 use netrascan::{
-    classification::classify_wallet,
-    constants::DEFAULT_OFFSET,
+    balance::BalanceTracker,
+    classification::{classify_wallet, counterparties, WalletKinds},
     error::{Error, Result},
     heuristics::calculate_score,
     input::{read_tx_records, read_wallets_from_file},
@@ -14,9 +17,21 @@ use netrascan::{
     output::{append_address_jsonl, append_jsonl, write_json},
 };
 
-pub async fn analyze(wallet: &str, api_key: &str, start_block: u64) -> Result<()> {
-    let client = EtherscanClient::new(api_key);
-    let report = wallet_to_report(&client, wallet, start_block).await?;
+use crate::cli::{AddressArgs, BalanceArgs, TransferArgs};
+
+pub async fn analyze(
+    wallet: &str,
+    transfers: &TransferArgs,
+    balances: &BalanceArgs,
+    addresses: &AddressArgs,
+) -> Result<()> {
+    let transfer_source = transfers.transfer_source().await?;
+    let range = transfers.range(transfer_source.as_ref()).await?;
+    let enrichment = Enrichment::new(transfers, balances, addresses).await?;
+    let provenance = transfers.provenance(transfer_source.as_ref(), range)?;
+    let report = wallet_to_report(transfer_source.as_ref(), &enrichment, wallet, range)
+        .await?
+        .with_provenance(provenance);
     let now = Utc::now();
     let dir = format!(
         "netrascan/data/reports/{:04}-{:02}",
@@ -36,8 +51,17 @@ pub async fn analyze(wallet: &str, api_key: &str, start_block: u64) -> Result<()
     Ok(())
 }
 
-pub async fn batch(source: &str, api_key: &str, start_block: u64, output: &str) -> Result<()> {
-    let client = EtherscanClient::new(api_key);
+pub async fn batch(
+    source: &str,
+    transfers: &TransferArgs,
+    balances: &BalanceArgs,
+    addresses: &AddressArgs,
+    output: &str,
+) -> Result<()> {
+    let transfer_source = transfers.transfer_source().await?;
+    let range = transfers.range(transfer_source.as_ref()).await?;
+    let enrichment = Enrichment::new(transfers, balances, addresses).await?;
+    let provenance = transfers.provenance(transfer_source.as_ref(), range)?;
 
     let wallets = read_wallets_from_file(source)
         .map_err(|err| Error::from_io(err, "❌ Failed to read wallet addresses"))?;
@@ -53,8 +77,8 @@ pub async fn batch(source: &str, api_key: &str, start_block: u64, output: &str)
     let mut fail_count = 0;
 
     for wallet in wallets {
-        match wallet_to_report(&client, &wallet, start_block).await {
-            Ok(report) => match append_jsonl(&path, &report) {
+        match wallet_to_report(transfer_source.as_ref(), &enrichment, &wallet, range).await {
+            Ok(report) => match append_jsonl(&path, &report.with_provenance(provenance.clone())) {
                 Ok(_) => {
                     println!("✅ Report saved to {path} for {wallet}");
                     success_count += 1;
@@ -64,6 +88,9 @@ pub async fn batch(source: &str, api_key: &str, start_block: u64, output: &str)
                     fail_count += 1;
                 }
             },
+            Err(Error::DataClient(err @ DataClientError::InvalidApiKey(_))) => {
+                return Err(err.into());
+            }
             Err(_) => {
                 eprintln!("⚠️  Skipped wallet {wallet} due to fetch/parse error.");
                 fail_count += 1;
@@ -76,11 +103,12 @@ pub async fn batch(source: &str, api_key: &str, start_block: u64, output: &str)
     Ok(())
 }
 
-pub async fn crawl(source: &str, api_key: &str, start_block: u64, output: &str) -> Result<()> {
-    let client = EtherscanClient::new(api_key);
+pub async fn crawl(source: &str, transfers: &TransferArgs, output: &str) -> Result<()> {
+    let transfer_source = transfers.transfer_source().await?;
+    let range = transfers.range(transfer_source.as_ref()).await?;
 
-    let txs = client
-        .fetch_all_token_transfer(source, start_block, DEFAULT_OFFSET)
+    let txs = transfer_source
+        .wallet_token_transfers(source, range)
         .await?;
 
     if txs.is_empty() {
@@ -118,8 +146,17 @@ pub async fn crawl(source: &str, api_key: &str, start_block: u64, output: &str)
     Ok(())
 }
 
-pub async fn train(source: &str, api_key: &str, start_block: u64, output: &str) -> Result<()> {
-    let client = EtherscanClient::new(api_key);
+pub async fn train(
+    source: &str,
+    transfers: &TransferArgs,
+    balances: &BalanceArgs,
+    addresses: &AddressArgs,
+    output: &str,
+) -> Result<()> {
+    let transfer_source = transfers.transfer_source().await?;
+    let range = transfers.range(transfer_source.as_ref()).await?;
+    let enrichment = Enrichment::new(transfers, balances, addresses).await?;
+    let provenance = transfers.provenance(transfer_source.as_ref(), range)?;
 
     let wallets = read_wallets_from_file(source)
         .map_err(|err| Error::from_io(err, "❌ Failed to read wallet addresses"))?;
@@ -141,17 +178,22 @@ pub async fn train(source: &str, api_key: &str, start_block: u64, output: &str)
     for (index, wallet) in wallets.iter().enumerate() {
         println!("🔍 [{index:03}] Processing wallet: {wallet}");
 
-        match wallet_to_feature(&client, wallet, start_block).await {
-            Ok(feature) => match append_jsonl(&path, &feature) {
-                Ok(_) => {
-                    println!("✅ Training data saved to {path} for {wallet}");
-                    success_count += 1;
-                }
-                Err(err) => {
-                    eprintln!("❌ Failed to write training data for {wallet}: {err}");
-                    fail_count += 1;
+        match wallet_to_feature(transfer_source.as_ref(), &enrichment, wallet, range).await {
+            Ok(feature) => {
+                match append_jsonl(&path, &feature.with_provenance(provenance.clone())) {
+                    Ok(_) => {
+                        println!("✅ Training data saved to {path} for {wallet}");
+                        success_count += 1;
+                    }
+                    Err(err) => {
+                        eprintln!("❌ Failed to write training data for {wallet}: {err}");
+                        fail_count += 1;
+                    }
                 }
-            },
+            }
+            Err(Error::DataClient(err @ DataClientError::InvalidApiKey(_))) => {
+                return Err(err.into());
+            }
             Err(_) => {
                 eprintln!("⚠️  Skipped wallet {wallet} due to fetch/parse error.");
                 fail_count += 1;
@@ -164,11 +206,12 @@ pub async fn train(source: &str, api_key: &str, start_block: u64, output: &str)
     Ok(())
 }
 
-pub async fn fetch(source: &str, api_key: &str, start_block: u64, output: &str) -> Result<()> {
-    let client = EtherscanClient::new(api_key);
+pub async fn fetch(source: &str, transfers: &TransferArgs, output: &str) -> Result<()> {
+    let transfer_source = transfers.transfer_source().await?;
+    let range = transfers.range(transfer_source.as_ref()).await?;
 
-    let txs = client
-        .fetch_all_token_transfer(source, start_block, DEFAULT_OFFSET)
+    let txs = transfer_source
+        .wallet_token_transfers(source, range)
         .await?;
 
     if txs.is_empty() {
@@ -189,8 +232,8 @@ pub async fn score(input: &str) -> Result<()> {
     let records =
         read_tx_records(input).map_err(|err| Error::from_io(err, "❌ Failed to load records"))?;
 
-    let score = calculate_score(&records);
-    let category = classify_wallet(score);
+    let score = calculate_score(&records, None);
+    let category = classify_wallet(score, None);
 
     println!("📄 Wallet classification result:");
     println!("  Score: {score:.2}");
@@ -199,14 +242,73 @@ pub async fn score(input: &str) -> Result<()> {
     Ok(())
 }
 
+/// Optional lookups layered over a wallet's transfer history.
+struct Enrichment {
+    balances: Option<BalanceTracker>,
+    addresses: Option<AddressTyper>,
+}
+
+impl Enrichment {
+    async fn new(
+        transfers: &TransferArgs,
+        balances: &BalanceArgs,
+        addresses: &AddressArgs,
+    ) -> Result<Self> {
+        Ok(Self {
+            balances: balances.tracker(transfers).await?,
+            addresses: addresses
+                .typer(transfers.rpc.as_deref(), transfers.chain)
+                .await?,
+        })
+    }
+
+    async fn balances(
+        &self,
+        chain: Chain,
+        wallet: &str,
+        records: &[TxRecord],
+    ) -> Result<Option<Vec<BalanceSnapshot>>> {
+        match &self.balances {
+            Some(tracker) => Ok(Some(tracker.snapshot(chain, wallet, records).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Kinds of `wallet` and of its counterparties.
+    async fn address_kinds(
+        &self,
+        wallet: &str,
+        records: &[TxRecord],
+    ) -> Result<Option<WalletKinds>> {
+        let Some(typer) = &self.addresses else {
+            return Ok(None);
+        };
+
+        let mut addresses = counterparties(wallet, records);
+        addresses.push(wallet.to_ascii_lowercase());
+
+        let mut kinds = typer.kinds(&addresses).await?;
+        let kind = kinds
+            .remove(&wallet.to_ascii_lowercase())
+            .unwrap_or(AddressKind::Eoa);
+        let kinds = WalletKinds::new(wallet, kind, kinds);
+
+        println!(
+            "🧬 {wallet} is {kind}, counterparties: {:?}",
+            kinds.counts()
+        );
+
+        Ok(Some(kinds))
+    }
+}
+
 async fn wallet_to_report(
-    client: &EtherscanClient,
+    source: &dyn TransferSource,
+    enrichment: &Enrichment,
     wallet: &str,
-    start_block: u64,
+    range: BlockRange,
 ) -> Result<WalletReport> {
-    let txs = client
-        .fetch_all_token_transfer(wallet, start_block, DEFAULT_OFFSET)
-        .await?;
+    let txs = source.wallet_transfers(wallet, range).await?;
 
     if txs.is_empty() {
         return Err(Error::EmptyTransaction(wallet.to_string()));
@@ -214,20 +316,32 @@ async fn wallet_to_report(
 
     let records: Vec<TxRecord> = txs.into_iter().map(|tx| tx.into()).collect();
 
-    let score = calculate_score(&records);
-    let category = classify_wallet(score);
+    let kinds = enrichment.address_kinds(wallet, &records).await?;
+    let score = calculate_score(&records, kinds.as_ref());
+    let category = classify_wallet(score, kinds.as_ref());
+
+    let mut report = WalletReport::new(wallet, source.chain(), score, category, &records);
 
-    Ok(WalletReport::new(wallet, score, category, &records))
+    if let Some(balances) = enrichment
+        .balances(source.chain(), wallet, &records)
+        .await?
+    {
+        report = report.with_balances(balances);
+    }
+    if let Some(kinds) = kinds {
+        report = report.with_address_kinds(kinds.kind, kinds.counts());
+    }
+
+    Ok(report)
 }
 
 async fn wallet_to_feature(
-    client: &EtherscanClient,
+    source: &dyn TransferSource,
+    enrichment: &Enrichment,
     wallet: &str,
-    start_block: u64,
+    range: BlockRange,
 ) -> Result<WalletFeature> {
-    let txs = client
-        .fetch_all_token_transfer(wallet, start_block, DEFAULT_OFFSET)
-        .await?;
+    let txs = source.wallet_transfers(wallet, range).await?;
 
     if txs.is_empty() {
         return Err(Error::EmptyTransaction(wallet.to_string()));
@@ -235,5 +349,17 @@ async fn wallet_to_feature(
 
     let records: Vec<TxRecord> = txs.into_iter().map(|tx| tx.into()).collect();
 
-    Ok(WalletFeature::from_records(wallet, &records))
+    let mut feature = WalletFeature::from_records(wallet, &records);
+
+    if let Some(balances) = enrichment
+        .balances(source.chain(), wallet, &records)
+        .await?
+    {
+        feature = feature.with_balances(balances);
+    }
+    if let Some(kinds) = enrichment.address_kinds(wallet, &records).await? {
+        feature = feature.with_address_kinds(kinds.kind, kinds.counts());
+    }
+
+    Ok(feature)
 }